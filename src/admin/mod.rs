@@ -1,12 +1,16 @@
 //! Tibco EMS admin functions.
 
+use super::backend::{self, Backend};
 use super::{Connection, Destination, MapMessage, Message, Session, TypedValue};
-use enum_extract::extract;
 use log::{error, trace, warn};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::io::Error;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+pub mod reconcile;
 
 const ADMIN_QUEUE_NAME: &str = "$sys.admin";
 const DESTINATION_TYPE_QUEUE: i32 = 1;
@@ -14,19 +18,739 @@ const DESTINATION_TYPE_TOPIC: i32 = 2;
 
 /// open a connection to the Tibco EMS server for administrative purposes
 pub fn connect(url: &str, user: &str, password: &str) -> Result<Connection, Error> {
-    let conn = super::connect(url, user, password);
+    #[cfg(feature = "ems-sys")]
+    let backend = super::native::NativeBackend;
+    #[cfg(not(feature = "ems-sys"))]
+    let backend = super::mock::MockBroker::current(url);
+    connect_with_backend(backend, url, user, password)
+}
+
+/// open an administrative connection through the given backend
+pub fn connect_with_backend<B: Backend + Clone + 'static>(
+    backend: B,
+    url: &str,
+    user: &str,
+    password: &str,
+) -> Result<Connection, Error> {
+    let conn = super::connect_with_backend(backend.clone(), url, user, password);
     match conn {
         Ok(conn) => {
             //check connection for active server
-            let active_url = conn.get_active_url().unwrap();
+            let active_url = conn.get_active_url()?;
             drop(conn);
             let admin_active_url = format!("<$admin>:{active_url}");
-            super::connect(&admin_active_url, user, password)
+            super::connect_with_backend(backend, &admin_active_url, user, password)
         }
         Err(err) => Err(err),
     }
 }
 
+//
+// Errors
+//
+
+/// return codes put in the `rc` header of admin replies by the admin queue
+/// emulated in [`crate::mock`], the error text goes into the `em` header
+///
+/// Tibco does not document the status of admin replies, so these codes are only
+/// classified for backends emulating the admin queue. The return code of any other
+/// server is reported as [`AdminErrorKind::Other`].
+pub(crate) const RC_OK: i32 = 0;
+pub(crate) const RC_INVALID_REQUEST: i32 = 1;
+pub(crate) const RC_NOT_FOUND: i32 = 2;
+pub(crate) const RC_ALREADY_EXISTS: i32 = 3;
+
+/// kind of error reported for an admin command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminErrorKind {
+    /// the request was malformed or contained invalid values
+    InvalidRequest,
+    /// the object does not exist
+    NotFound,
+    /// the object already exists
+    AlreadyExists,
+    /// the server did not reply in time
+    NoReply,
+    /// the reply carries no status, so it is unknown whether the command took effect
+    Unconfirmed,
+    /// any other error reported by the server
+    Other,
+}
+
+/// error reported by the server for an admin command
+///
+/// admin functions return it wrapped in a [`std::io::Error`], use
+/// [`AdminError::from_error`] to get it back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdminError {
+    /// kind of error
+    pub kind: AdminErrorKind,
+    /// return code of the admin reply, if there was one
+    pub code: Option<i32>,
+    /// error text of the admin reply
+    pub message: String,
+}
+
+impl AdminError {
+    /// extracts the admin error of an error returned by an admin function
+    pub fn from_error(err: &Error) -> Option<&AdminError> {
+        err.get_ref()
+            .and_then(|inner| inner.downcast_ref::<AdminError>())
+    }
+
+    fn new(kind: AdminErrorKind, message: &str) -> AdminError {
+        AdminError {
+            kind,
+            code: None,
+            message: message.to_string(),
+        }
+    }
+
+    /// error for a return code, classified for the codes of the emulated admin queue only
+    fn from_code(code: i32, message: String, emulated: bool) -> AdminError {
+        let kind = match code {
+            RC_INVALID_REQUEST if emulated => AdminErrorKind::InvalidRequest,
+            RC_NOT_FOUND if emulated => AdminErrorKind::NotFound,
+            RC_ALREADY_EXISTS if emulated => AdminErrorKind::AlreadyExists,
+            _ => AdminErrorKind::Other,
+        };
+        AdminError {
+            kind,
+            code: Some(code),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for AdminError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "admin command failed ({}): {}", code, self.message),
+            None => write!(f, "admin command failed: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for AdminError {}
+
+impl From<AdminError> for Error {
+    fn from(err: AdminError) -> Self {
+        let kind = match err.kind {
+            AdminErrorKind::InvalidRequest => ErrorKind::InvalidInput,
+            AdminErrorKind::NotFound => ErrorKind::NotFound,
+            AdminErrorKind::AlreadyExists => ErrorKind::AlreadyExists,
+            AdminErrorKind::NoReply => ErrorKind::TimedOut,
+            AdminErrorKind::Unconfirmed => ErrorKind::Other,
+            AdminErrorKind::Other => ErrorKind::Other,
+        };
+        Error::new(kind, err)
+    }
+}
+
+/// error decoding a field of an admin reply
+///
+/// admin functions return it wrapped in a [`std::io::Error`] of kind
+/// [`ErrorKind::InvalidData`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    /// name of the object the field belongs to
+    pub name: String,
+    /// name of the field
+    pub field: String,
+    /// description of the problem
+    pub message: String,
+}
+
+impl DecodeError {
+    /// extracts the decode error of an error returned by an admin function
+    pub fn from_error(err: &Error) -> Option<&DecodeError> {
+        err.get_ref()
+            .and_then(|inner| inner.downcast_ref::<DecodeError>())
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid field {} of {}: {}",
+            self.field, self.name, self.message
+        )
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<DecodeError> for Error {
+    fn from(err: DecodeError) -> Self {
+        Error::new(ErrorKind::InvalidData, err)
+    }
+}
+
+/// sends an admin command and waits for the server to confirm it
+///
+/// commands which are not [verified](AdminCommands::is_verified) are refused with
+/// [`ErrorKind::Unsupported`] unless the backend emulates the admin queue.
+///
+/// the reply carries the return code in the `rc` header, [`RC_OK`] on success, and
+/// the error text in the `em` header. A query is answered by its result, so its reply
+/// may go without a return code, while any other command is reported as
+/// [`AdminErrorKind::Unconfirmed`] if its reply carries no return code.
+fn execute(session: &Session, msg: MapMessage) -> Result<MapMessage, Error> {
+    execute_with_timeout(session, msg, AdminOptions::default().timeout)
+}
+
+fn execute_with_timeout(
+    session: &Session,
+    msg: MapMessage,
+    timeout: i64,
+) -> Result<MapMessage, Error> {
+    let emulated = backend::backend_of(session.pointer, "session")?.emulates_admin();
+    let command = AdminCommands::of(&msg);
+    if !emulated && !command.is_some_and(|command| command.is_verified()) {
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            format!("admin command {command:?} has not been verified against a Tibco EMS server"),
+        ));
+    }
+    let query = command.is_some_and(|command| command.is_query());
+    let admin_queue = Destination::Queue(ADMIN_QUEUE_NAME.to_string());
+    let reply = match session.request_reply(&admin_queue, msg, timeout)? {
+        Some(Message::MapMessage(ref reply)) => reply.clone(),
+        Some(_) => {
+            return Err(
+                AdminError::new(AdminErrorKind::Other, "unknown response to admin command").into(),
+            )
+        }
+        None => {
+            return Err(
+                AdminError::new(AdminErrorKind::NoReply, "no reply to admin command").into(),
+            )
+        }
+    };
+    let header = reply.header.clone().unwrap_or_default();
+    match header.get("rc") {
+        Some(TypedValue::Integer(RC_OK)) => Ok(reply),
+        Some(TypedValue::Integer(code)) => {
+            let message = match header.get("em") {
+                Some(TypedValue::String(val)) => val.clone(),
+                _ => "admin command failed".to_string(),
+            };
+            Err(AdminError::from_code(*code, message, emulated).into())
+        }
+        None if query => Ok(reply),
+        None => Err(AdminError::new(
+            AdminErrorKind::Unconfirmed,
+            "admin reply carries no return code",
+        )
+        .into()),
+        Some(code) => Err(AdminError::new(
+            AdminErrorKind::Unconfirmed,
+            &format!("admin reply carries an unknown return code {code:?}"),
+        )
+        .into()),
+    }
+}
+
+//
+// Destination properties
+//
+
+/// writes the settable properties of a queue to an admin message body
+fn queue_properties(queue: &QueueInfo, body: &mut HashMap<String, TypedValue>) {
+    destination_properties(
+        body,
+        DestinationProperties {
+            max_bytes: queue.max_bytes,
+            max_messages: queue.max_messages,
+            overflow_policy: queue.overflow_policy.clone(),
+            failsafe: queue.failsafe,
+            secure: queue.secure,
+            global: queue.global,
+            sender_name: queue.sender_name,
+            sender_name_enforced: queue.sender_name_enforced,
+            prefetch: queue.prefetch,
+            expiry_override: queue.expiry_override,
+            flow_control: queue.flow_control,
+            store: queue.store.clone(),
+            trace: queue.trace.clone(),
+            import_channels: queue.import_channels.clone(),
+            export_channels: None,
+        },
+    );
+    if let Some(val) = queue.redelivery_delay {
+        body.insert("rdd".to_string(), TypedValue::Long(val));
+    }
+    if let Some(val) = queue.max_redelivery {
+        body.insert("mrd".to_string(), TypedValue::Integer(val));
+    }
+    if let Some(val) = queue.exclusive {
+        body.insert("excl".to_string(), TypedValue::Boolean(val));
+    }
+}
+
+/// writes the settable properties of a topic to an admin message body
+fn topic_properties(topic: &TopicInfo, body: &mut HashMap<String, TypedValue>) {
+    destination_properties(
+        body,
+        DestinationProperties {
+            max_bytes: topic.max_bytes,
+            max_messages: topic.max_messages,
+            overflow_policy: topic.overflow_policy.clone(),
+            failsafe: topic.failsafe,
+            secure: topic.secure,
+            global: topic.global,
+            sender_name: topic.sender_name,
+            sender_name_enforced: topic.sender_name_enforced,
+            prefetch: topic.prefetch,
+            expiry_override: topic.expiry_override,
+            flow_control: topic.flow_control,
+            store: topic.store.clone(),
+            trace: topic.trace.clone(),
+            import_channels: topic.import_channels.clone(),
+            export_channels: topic.export_channels.clone(),
+        },
+    );
+}
+
+/// properties shared by queues and topics
+struct DestinationProperties {
+    max_bytes: Option<i64>,
+    max_messages: Option<i64>,
+    overflow_policy: Option<OverflowPolicy>,
+    failsafe: Option<bool>,
+    secure: Option<bool>,
+    global: Option<bool>,
+    sender_name: Option<bool>,
+    sender_name_enforced: Option<bool>,
+    prefetch: Option<i32>,
+    expiry_override: Option<i64>,
+    flow_control: Option<i64>,
+    store: Option<String>,
+    trace: Option<TraceMode>,
+    import_channels: Option<Vec<String>>,
+    export_channels: Option<Vec<String>>,
+}
+
+fn destination_properties(body: &mut HashMap<String, TypedValue>, props: DestinationProperties) {
+    let longs = [
+        ("mb", props.max_bytes),
+        ("mm", props.max_messages),
+        ("expy", props.expiry_override),
+        ("fc", props.flow_control),
+    ];
+    for (key, val) in longs {
+        if let Some(val) = val {
+            body.insert(key.to_string(), TypedValue::Long(val));
+        }
+    }
+    let flags = [
+        ("failsafe", props.failsafe),
+        ("secure", props.secure),
+        ("global", props.global),
+        ("sname", props.sender_name),
+        ("snameenf", props.sender_name_enforced),
+    ];
+    for (key, val) in flags {
+        if let Some(val) = val {
+            body.insert(key.to_string(), TypedValue::Boolean(val));
+        }
+    }
+    if let Some(val) = props.overflow_policy {
+        body.insert("op".to_string(), TypedValue::Integer(val as i32));
+    }
+    if let Some(val) = props.prefetch {
+        body.insert("pf".to_string(), TypedValue::Integer(val));
+    }
+    if let Some(val) = props.store {
+        body.insert("store".to_string(), TypedValue::String(val));
+    }
+    if let Some(val) = props.trace {
+        body.insert(
+            "trace".to_string(),
+            TypedValue::String(val.as_str().to_string()),
+        );
+    }
+    if let Some(val) = props.import_channels {
+        body.insert("import".to_string(), TypedValue::String(val.join(",")));
+    }
+    if let Some(val) = props.export_channels {
+        body.insert("export".to_string(), TypedValue::String(val.join(",")));
+    }
+}
+
+/// change of a single destination property
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Patch<T> {
+    /// leave the property as it is
+    #[default]
+    Keep,
+    /// set the property to a value
+    Set(T),
+    /// reset the property to the server default
+    Clear,
+}
+
+impl<T> From<Option<T>> for Patch<T> {
+    fn from(val: Option<T>) -> Self {
+        match val {
+            Some(val) => Patch::Set(val),
+            None => Patch::Keep,
+        }
+    }
+}
+
+/// value to send for a patched property, cleared properties are collected by key
+fn patched<T: Clone>(
+    patch: &Patch<T>,
+    key: &'static str,
+    cleared: &mut Vec<&'static str>,
+) -> Option<T> {
+    match patch {
+        Patch::Keep => None,
+        Patch::Set(val) => Some(val.clone()),
+        Patch::Clear => {
+            cleared.push(key);
+            None
+        }
+    }
+}
+
+/// sends an alter destination command with the changed and cleared properties
+fn alter_destination(
+    session: &Session,
+    name: &str,
+    destination_type: i32,
+    mut body: HashMap<String, TypedValue>,
+    cleared: Vec<&'static str>,
+) -> Result<(), Error> {
+    let mut msg: MapMessage = Default::default();
+    body.insert("dn".to_string(), TypedValue::String(name.to_string()));
+    body.insert("dt".to_string(), TypedValue::Integer(destination_type));
+    if !cleared.is_empty() {
+        body.insert("clr".to_string(), TypedValue::String(cleared.join(",")));
+    }
+    msg.body = body;
+
+    //header
+    let mut header: HashMap<String, TypedValue> = HashMap::new();
+    //actual boolean
+    header.insert("JMS_TIBCO_MSG_EXT".to_string(), TypedValue::Boolean(true));
+    header.insert(
+        "code".to_string(),
+        TypedValue::Integer(AdminCommands::AlterDestination as i32),
+    );
+    header.insert("save".to_string(), TypedValue::Boolean(true));
+    header.insert("arseq".to_string(), TypedValue::Integer(1));
+    msg.header = Some(header);
+
+    let result = execute(session, msg);
+    match result {
+        Ok(_) => {}
+        Err(err) => {
+            error!("error while updating destination {}: {}", name, err);
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// decodes the fields of an entry of an admin reply
+///
+/// missing fields decode to None, fields which are not decoded end up as extra fields.
+struct Decoder<'a> {
+    name: &'a str,
+    info: &'a MapMessage,
+    known: Vec<&'static str>,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(name: &'a str, info: &'a MapMessage) -> Self {
+        Decoder {
+            name,
+            info,
+            known: Vec::new(),
+        }
+    }
+
+    fn value(&mut self, field: &'static str) -> Option<&'a TypedValue> {
+        self.known.push(field);
+        self.info.body.get(field)
+    }
+
+    fn error(&self, field: &str, message: String) -> DecodeError {
+        DecodeError {
+            name: self.name.to_string(),
+            field: field.to_string(),
+            message,
+        }
+    }
+
+    /// numbers are sent as strings, but numeric values are accepted as well
+    fn number<T: std::str::FromStr>(
+        &mut self,
+        field: &'static str,
+    ) -> Result<Option<T>, DecodeError> {
+        let text = match self.value(field) {
+            None => return Ok(None),
+            Some(TypedValue::String(val)) => val.trim().to_string(),
+            Some(TypedValue::Integer(val)) => val.to_string(),
+            Some(TypedValue::Long(val)) => val.to_string(),
+            Some(other) => {
+                return Err(self.error(field, format!("expected a number, got {other:?}")))
+            }
+        };
+        match text.parse::<T>() {
+            Ok(val) => Ok(Some(val)),
+            Err(_) => Err(self.error(field, format!("invalid number '{text}'"))),
+        }
+    }
+
+    /// flags are sent as 1 or 0
+    fn flag(&mut self, field: &'static str) -> Result<Option<bool>, DecodeError> {
+        match self.value(field) {
+            None => Ok(None),
+            Some(TypedValue::Boolean(val)) => Ok(Some(*val)),
+            Some(TypedValue::Integer(val)) => Ok(Some(*val != 0)),
+            Some(TypedValue::String(val)) => match val.trim() {
+                "1" | "true" => Ok(Some(true)),
+                "0" | "false" | "" => Ok(Some(false)),
+                other => Err(self.error(field, format!("invalid flag '{other}'"))),
+            },
+            Some(other) => Err(self.error(field, format!("expected a flag, got {other:?}"))),
+        }
+    }
+
+    fn string(&mut self, field: &'static str) -> Result<Option<String>, DecodeError> {
+        match self.value(field) {
+            None => Ok(None),
+            Some(TypedValue::String(val)) => Ok(Some(val.clone())),
+            Some(other) => Err(self.error(field, format!("expected a string, got {other:?}"))),
+        }
+    }
+
+    fn overflow_policy(
+        &mut self,
+        field: &'static str,
+    ) -> Result<Option<OverflowPolicy>, DecodeError> {
+        Ok(self.number::<i32>(field)?.map(|val| match val {
+            1 => OverflowPolicy::DiscardOld,
+            2 => OverflowPolicy::RejectIncoming,
+            _ => OverflowPolicy::Default,
+        }))
+    }
+
+    fn trace(&mut self, field: &'static str) -> Result<Option<TraceMode>, DecodeError> {
+        match self.string(field)? {
+            None => Ok(None),
+            Some(val) => match TraceMode::parse(&val) {
+                Some(mode) => Ok(Some(mode)),
+                None => Err(self.error(field, format!("invalid trace mode '{val}'"))),
+            },
+        }
+    }
+
+    /// destinations are sent as a type and a name, both fields are required
+    fn destination(
+        &mut self,
+        type_field: &'static str,
+        name_field: &'static str,
+    ) -> Result<Destination, DecodeError> {
+        let destination_type = self.number::<i32>(type_field)?;
+        let Some(name) = self.string(name_field)? else {
+            return Err(self.error(name_field, "missing destination name".to_string()));
+        };
+        match destination_type {
+            Some(DESTINATION_TYPE_QUEUE) => Ok(Destination::Queue(name)),
+            Some(DESTINATION_TYPE_TOPIC) => Ok(Destination::Topic(name)),
+            Some(other) => Err(self.error(type_field, format!("invalid destination type {other}"))),
+            None => Err(self.error(type_field, "missing destination type".to_string())),
+        }
+    }
+
+    /// all fields which have not been decoded
+    fn extra(&self) -> HashMap<String, TypedValue> {
+        self.info
+            .body
+            .iter()
+            .filter(|(field, _)| !self.known.contains(&field.as_str()))
+            .map(|(field, val)| (field.clone(), val.clone()))
+            .collect()
+    }
+}
+
+/// comma separated list of names
+fn name_list(val: &str) -> Vec<String> {
+    val.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+//
+// Paging
+//
+
+/// options for admin requests
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AdminOptions {
+    /// time in milliseconds to wait for the reply to a request
+    pub timeout: i64,
+    /// number of destinations fetched with each list request
+    pub page_size: i32,
+}
+
+impl Default for AdminOptions {
+    fn default() -> Self {
+        AdminOptions {
+            timeout: 60000,
+            page_size: 1000,
+        }
+    }
+}
+
+/// iterator over destinations, requesting the next page once the current one is used up
+///
+/// pages are requested in name order, each page starts after the `cursor`, the last
+/// name of the previous one. Only the emulated admin queue is known to support the
+/// cursor, so a Tibco EMS server is sent a single request for `page_size`
+/// destinations. If that page is full, or the server answers with a page repeating
+/// names of the previous one, the iteration ends with a warning that the list may be
+/// incomplete.
+pub struct DestinationIter<'a, T> {
+    session: &'a Session,
+    destination_type: i32,
+    pattern: String,
+    options: AdminOptions,
+    cursor: Option<String>,
+    page: std::vec::IntoIter<T>,
+    exhausted: bool,
+    decode: fn(&str, &MapMessage) -> Result<T, DecodeError>,
+}
+
+impl<'a, T> DestinationIter<'a, T> {
+    fn new(
+        session: &'a Session,
+        destination_type: i32,
+        pattern: &str,
+        options: &AdminOptions,
+        decode: fn(&str, &MapMessage) -> Result<T, DecodeError>,
+    ) -> Self {
+        DestinationIter {
+            session,
+            destination_type,
+            pattern: pattern.to_string(),
+            options: options.clone(),
+            cursor: None,
+            page: Vec::new().into_iter(),
+            exhausted: false,
+            decode,
+        }
+    }
+
+    fn fetch_page(&mut self) -> Result<Vec<T>, Error> {
+        let mut msg: MapMessage = Default::default();
+        msg.body
+            .insert("dt".to_string(), TypedValue::Integer(self.destination_type));
+        msg.body
+            .insert("permType".to_string(), TypedValue::Integer(6));
+        msg.body.insert(
+            "pattern".to_string(),
+            TypedValue::String(self.pattern.clone()),
+        );
+        msg.body.insert("ia".to_string(), TypedValue::Boolean(true));
+        msg.body.insert(
+            "first".to_string(),
+            TypedValue::Integer(self.options.page_size),
+        );
+        if let Some(cursor) = &self.cursor {
+            msg.body
+                .insert("cursor".to_string(), TypedValue::String(cursor.clone()));
+        }
+
+        //header
+        let mut header: HashMap<String, TypedValue> = HashMap::new();
+        header.insert(
+            "code".to_string(),
+            TypedValue::Integer(AdminCommands::ListDestination as i32),
+        );
+        header.insert("save".to_string(), TypedValue::Boolean(true));
+        header.insert("arseq".to_string(), TypedValue::Integer(1));
+        msg.header = Some(header);
+
+        let paging = backend::backend_of(self.session.pointer, "session")?.emulates_admin();
+        let reply = match execute_with_timeout(self.session, msg, self.options.timeout) {
+            Ok(reply) => reply,
+            Err(err) => {
+                error!("something went wrong retrieving destinations: {}", err);
+                return Err(err);
+            }
+        };
+        let mut entries: Vec<(&String, &MapMessage)> = Vec::new();
+        for (name, val) in &reply.body {
+            match val {
+                TypedValue::Map(info) => entries.push((name, info)),
+                _ => warn!("unknown entry {} in destination list", name),
+            }
+        }
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        // a server ignoring the cursor answers with the first page again
+        if let (Some(cursor), Some((name, _))) = (&self.cursor, entries.first()) {
+            if *name <= cursor {
+                warn!(
+                    "page repeats destination {}, the server ignored the cursor and the list may be incomplete",
+                    name
+                );
+                self.exhausted = true;
+                return Ok(Vec::new());
+            }
+        }
+        // a full page means there may be more
+        let full = entries.len() >= self.options.page_size.max(1) as usize;
+        self.cursor = entries.last().map(|(name, _)| name.to_string());
+        if !full || self.cursor.is_none() {
+            self.exhausted = true;
+        } else if !paging {
+            warn!(
+                "the server returned a full page of {} destinations and is not known to support paging, the list may be incomplete",
+                self.options.page_size
+            );
+            self.exhausted = true;
+        }
+        let mut page = Vec::new();
+        for (name, info) in entries {
+            page.push((self.decode)(name, info)?);
+        }
+        Ok(page)
+    }
+}
+
+impl<T> Iterator for DestinationIter<'_, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.page.next() {
+                return Some(Ok(item));
+            }
+            if self.exhausted {
+                return None;
+            }
+            match self.fetch_page() {
+                Ok(page) => self.page = page.into_iter(),
+                Err(err) => {
+                    self.exhausted = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
 //
 // Queues
 //
@@ -68,157 +792,99 @@ pub struct QueueInfo {
     pub incoming_total_count: Option<i64>,
     /// total count of outgoing messages
     pub outgoing_total_count: Option<i64>,
+    /// max number of redelivery attempts
+    pub max_redelivery: Option<i32>,
+    /// exclusive delivery to a single consumer
+    pub exclusive: Option<bool>,
+    /// flow control limit in bytes
+    pub flow_control: Option<i64>,
+    /// name of the store for persistent messages
+    pub store: Option<String>,
+    /// message tracing
+    pub trace: Option<TraceMode>,
+    /// channels messages are imported from
+    pub import_channels: Option<Vec<String>>,
+    /// fields reported by the server which are not modelled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub extra: HashMap<String, TypedValue>,
 }
 
 /// lists all queues present on the EMS
 ///
 /// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
 pub fn list_all_queues(session: &Session) -> Result<Vec<QueueInfo>, Error> {
-    let mut queues = Vec::new();
-    const TIMEOUT: i64 = 60000;
-    let mut msg: MapMessage = Default::default();
-    msg.body.insert(
-        "dt".to_string(),
-        TypedValue::Integer(DESTINATION_TYPE_QUEUE),
-    );
-    msg.body
-        .insert("permType".to_string(), TypedValue::Integer(6));
-    msg.body
-        .insert("pattern".to_string(), TypedValue::String(">".to_string()));
-    msg.body.insert("ia".to_string(), TypedValue::Boolean(true));
-    msg.body
-        .insert("first".to_string(), TypedValue::Integer(1000));
+    list_queues(session, ">")
+}
 
-    //header
-    let mut header: HashMap<String, TypedValue> = HashMap::new();
-    //actual boolean
-    header.insert(
-        "code".to_string(),
-        TypedValue::Integer(AdminCommands::ListDestination as i32),
-    );
-    header.insert("save".to_string(), TypedValue::Boolean(true));
-    header.insert("arseq".to_string(), TypedValue::Integer(1));
-    msg.header = Some(header);
+/// gets a single queue, None if the queue does not exist
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn get_queue(session: &Session, name: &str) -> Result<Option<QueueInfo>, Error> {
+    let queues = list_queues(session, name)?;
+    Ok(queues.into_iter().find(|queue| queue.name == name))
+}
 
-    let admin_queue = Destination::Queue(ADMIN_QUEUE_NAME.to_string());
-    let query_result = session.request_reply(&admin_queue, msg, TIMEOUT);
-    match query_result {
-        Ok(response) => {
-            if let Some(resp) = response {
-                match &resp {
-                    Message::MapMessage(map_message) => {
-                        //got response message
-                        for (key, val) in &map_message.body {
-                            let q_info: &MapMessage =
-                                extract!(TypedValue::Map(_), val).expect("extract inner message");
-                            let pending_messages =
-                                extract!(TypedValue::String(_), q_info.body.get("nm").unwrap())
-                                    .expect("extract pending messages");
-                            let max_bytes =
-                                extract!(TypedValue::String(_), q_info.body.get("mb").unwrap())
-                                    .expect("extract max bytes");
-                            let max_msgs =
-                                extract!(TypedValue::String(_), q_info.body.get("mm").unwrap())
-                                    .expect("extract max messages");
-                            let overflow =
-                                extract!(TypedValue::String(_), q_info.body.get("op").unwrap())
-                                    .expect("extract overflow");
-                            let overflow_policy: OverflowPolicy = match overflow.as_str() {
-                                "0" => OverflowPolicy::Default,
-                                "1" => OverflowPolicy::DiscardOld,
-                                "2" => OverflowPolicy::RejectIncoming,
-                                _ => OverflowPolicy::Default,
-                            };
-                            let mut bool_failsafe = false;
-                            if let Some(val) = q_info.body.get("failsafe") {
-                                let failsafe =
-                                    extract!(TypedValue::String(_), val).expect("extract failsafe");
-                                bool_failsafe = failsafe == "1";
-                            }
-                            let mut bool_secure = false;
-                            if let Some(val) = q_info.body.get("secure") {
-                                let secure = extract!(TypedValue::String(_), val)
-                                    .expect("extract secure flag");
-                                bool_secure = secure == "1";
-                            }
-                            let mut bool_global = false;
-                            if let Some(val) = q_info.body.get("global") {
-                                let global = extract!(TypedValue::String(_), val)
-                                    .expect("extract global flag");
-                                bool_global = global == "1";
-                            }
-                            let mut bool_sender_name = false;
-                            if let Some(val) = q_info.body.get("sname") {
-                                let sender_name = extract!(TypedValue::String(_), val)
-                                    .expect("extract sender name");
-                                bool_sender_name = sender_name == "1";
-                            }
-                            let mut bool_sn_enforced = false;
-                            if let Some(val) = q_info.body.get("snameenf") {
-                                let sn_enforced = extract!(TypedValue::String(_), val)
-                                    .expect("extrance sender name enforced");
-                                bool_sn_enforced = sn_enforced == "1";
-                            }
-                            let prefetch =
-                                extract!(TypedValue::String(_), q_info.body.get("pf").unwrap())
-                                    .expect("queue property");
-                            let consumer_count =
-                                extract!(TypedValue::String(_), q_info.body.get("cc").unwrap())
-                                    .expect("queue property");
-                            let expiry =
-                                extract!(TypedValue::String(_), q_info.body.get("expy").unwrap())
-                                    .expect("queue property");
-                            let redelivery_delay =
-                                extract!(TypedValue::String(_), q_info.body.get("rdd").unwrap())
-                                    .expect("queue property");
-                            let in_total_count =
-                                extract!(TypedValue::String(_), q_info.body.get("inct").unwrap())
-                                    .expect("queue property");
-                            let out_total_count =
-                                extract!(TypedValue::String(_), q_info.body.get("outct").unwrap())
-                                    .expect("queue property");
-
-                            let queue_info = QueueInfo {
-                                name: key.to_string(),
-                                pending_messages: Some(pending_messages.parse::<i64>().unwrap()),
-                                max_messages: Some(max_msgs.parse::<i64>().unwrap()),
-                                max_bytes: Some(max_bytes.parse::<i64>().unwrap()),
-                                overflow_policy: Some(overflow_policy),
-                                failsafe: Some(bool_failsafe),
-                                secure: Some(bool_secure),
-                                global: Some(bool_global),
-                                sender_name: Some(bool_sender_name),
-                                sender_name_enforced: Some(bool_sn_enforced),
-                                prefetch: Some(prefetch.parse::<i32>().unwrap()),
-                                expiry_override: Some(expiry.parse::<i64>().unwrap()),
-                                redelivery_delay: Some(redelivery_delay.parse::<i64>().unwrap()),
-                                consumer_count: Some(consumer_count.parse::<i32>().unwrap()),
-                                incoming_total_count: Some(in_total_count.parse::<i64>().unwrap()),
-                                outgoing_total_count: Some(out_total_count.parse::<i64>().unwrap()),
-                            };
-                            queues.push(queue_info);
-                        }
-                    }
-                    _ => {
-                        warn!("unkown response from queue information request")
-                    }
-                }
-            }
-        }
-        Err(err) => {
-            error!(
-                "something went wronge retrieving queue information: {}",
-                err
-            );
-            return Err(err);
-        }
-    }
-    Ok(queues)
+/// lists the queues matching a pattern, `*` matches one element and `>` all trailing elements
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_queues(session: &Session, pattern: &str) -> Result<Vec<QueueInfo>, Error> {
+    iter_queues(session, pattern, &AdminOptions::default()).collect()
+}
+
+/// iterates over the queues matching a pattern, fetching them page by page
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn iter_queues<'a>(
+    session: &'a Session,
+    pattern: &str,
+    options: &AdminOptions,
+) -> DestinationIter<'a, QueueInfo> {
+    DestinationIter::new(
+        session,
+        DESTINATION_TYPE_QUEUE,
+        pattern,
+        options,
+        decode_queue,
+    )
+}
+/// decodes the queue information of a list reply
+fn decode_queue(name: &str, q_info: &MapMessage) -> Result<QueueInfo, DecodeError> {
+    let mut decoder = Decoder::new(name, q_info);
+    Ok(QueueInfo {
+        name: name.to_string(),
+        pending_messages: decoder.number("nm")?,
+        max_messages: decoder.number("mm")?,
+        max_bytes: decoder.number("mb")?,
+        overflow_policy: decoder.overflow_policy("op")?,
+        failsafe: decoder.flag("failsafe")?,
+        secure: decoder.flag("secure")?,
+        global: decoder.flag("global")?,
+        sender_name: decoder.flag("sname")?,
+        sender_name_enforced: decoder.flag("snameenf")?,
+        prefetch: decoder.number("pf")?,
+        expiry_override: decoder.number("expy")?,
+        redelivery_delay: decoder.number("rdd")?,
+        consumer_count: decoder.number("cc")?,
+        incoming_total_count: decoder.number("inct")?,
+        outgoing_total_count: decoder.number("outct")?,
+        max_redelivery: decoder.number("mrd")?,
+        exclusive: decoder.flag("excl")?,
+        flow_control: decoder.number("fc")?,
+        store: decoder.string("store")?,
+        trace: decoder.trace("trace")?,
+        import_channels: decoder.string("import")?.map(|val| name_list(&val)),
+        extra: decoder.extra(),
+    })
 }
 
 /// creates a queue on the EMS
 ///
 /// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
 pub fn create_queue(session: &Session, queue: &QueueInfo) -> Result<(), Error> {
     //create queue map-message
     let mut msg: MapMessage = Default::default();
@@ -228,22 +894,7 @@ pub fn create_queue(session: &Session, queue: &QueueInfo) -> Result<(), Error> {
         "dt".to_string(),
         TypedValue::Integer(DESTINATION_TYPE_QUEUE),
     );
-    if let Some(val) = queue.max_bytes {
-        msg.body.insert("mb".to_string(), TypedValue::Long(val));
-    }
-    if let Some(val) = queue.max_messages {
-        msg.body.insert("mm".to_string(), TypedValue::Long(val));
-    }
-    if let Some(val) = queue.global {
-        msg.body
-            .insert("global".to_string(), TypedValue::Boolean(val));
-    }
-    if let Some(val) = queue.prefetch {
-        msg.body.insert("pf".to_string(), TypedValue::Integer(val));
-    }
-    if let Some(val) = queue.expiry_override {
-        msg.body.insert("expy".to_string(), TypedValue::Long(val));
-    }
+    queue_properties(queue, &mut msg.body);
 
     //header
     let mut header: HashMap<String, TypedValue> = HashMap::new();
@@ -257,8 +908,7 @@ pub fn create_queue(session: &Session, queue: &QueueInfo) -> Result<(), Error> {
     header.insert("arseq".to_string(), TypedValue::Integer(1));
     msg.header = Some(header);
 
-    let admin_queue = Destination::Queue(ADMIN_QUEUE_NAME.to_string());
-    let result = session.send_message(&admin_queue, msg);
+    let result = execute(session, msg);
     match result {
         Ok(_) => {}
         Err(err) => {
@@ -269,19 +919,133 @@ pub fn create_queue(session: &Session, queue: &QueueInfo) -> Result<(), Error> {
     Ok(())
 }
 
-/// deletes a queue from the EMS
-///
-/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
-pub fn delete_queue(session: &Session, queue: &str) -> Result<(), Error> {
-    trace!("deleting queue {}", queue);
-    //create queue map-message
-    let mut msg: MapMessage = Default::default();
-    msg.body
-        .insert("dn".to_string(), TypedValue::String(queue.to_string()));
-    msg.body.insert(
-        "dt".to_string(),
-        TypedValue::Integer(DESTINATION_TYPE_QUEUE),
-    );
+/// changes to the settable properties of a queue
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct QueueInfoPatch {
+    /// name of the queue
+    pub name: String,
+    /// max size
+    pub max_bytes: Patch<i64>,
+    /// max allowed messages
+    pub max_messages: Patch<i64>,
+    /// overflow policy
+    pub overflow_policy: Patch<OverflowPolicy>,
+    /// failsafe
+    pub failsafe: Patch<bool>,
+    /// secure
+    pub secure: Patch<bool>,
+    /// global
+    pub global: Patch<bool>,
+    /// sender name
+    pub sender_name: Patch<bool>,
+    /// sender name enforced
+    pub sender_name_enforced: Patch<bool>,
+    /// prefetch
+    pub prefetch: Patch<i32>,
+    /// expiration override
+    pub expiry_override: Patch<i64>,
+    /// redelivery delay
+    pub redelivery_delay: Patch<i64>,
+    /// max number of redelivery attempts
+    pub max_redelivery: Patch<i32>,
+    /// exclusive delivery to a single consumer
+    pub exclusive: Patch<bool>,
+    /// flow control limit in bytes
+    pub flow_control: Patch<i64>,
+    /// name of the store for persistent messages
+    pub store: Patch<String>,
+    /// message tracing
+    pub trace: Patch<TraceMode>,
+    /// channels messages are imported from
+    pub import_channels: Patch<Vec<String>>,
+}
+
+impl From<&QueueInfo> for QueueInfoPatch {
+    /// sets every property present in the queue info, keeps all others
+    fn from(queue: &QueueInfo) -> Self {
+        QueueInfoPatch {
+            name: queue.name.clone(),
+            max_bytes: queue.max_bytes.into(),
+            max_messages: queue.max_messages.into(),
+            overflow_policy: queue.overflow_policy.clone().into(),
+            failsafe: queue.failsafe.into(),
+            secure: queue.secure.into(),
+            global: queue.global.into(),
+            sender_name: queue.sender_name.into(),
+            sender_name_enforced: queue.sender_name_enforced.into(),
+            prefetch: queue.prefetch.into(),
+            expiry_override: queue.expiry_override.into(),
+            redelivery_delay: queue.redelivery_delay.into(),
+            max_redelivery: queue.max_redelivery.into(),
+            exclusive: queue.exclusive.into(),
+            flow_control: queue.flow_control.into(),
+            store: queue.store.clone().into(),
+            trace: queue.trace.clone().into(),
+            import_channels: queue.import_channels.clone().into(),
+        }
+    }
+}
+
+/// updates the properties of an existing queue, only properties present in the queue info are changed
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn update_queue(session: &Session, queue: &QueueInfo) -> Result<(), Error> {
+    patch_queue(session, &QueueInfoPatch::from(queue))
+}
+
+/// applies a patch to the properties of an existing queue
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn patch_queue(session: &Session, patch: &QueueInfoPatch) -> Result<(), Error> {
+    trace!("updating queue {}", patch.name);
+    let mut cleared = Vec::new();
+    let queue = QueueInfo {
+        name: patch.name.clone(),
+        max_bytes: patched(&patch.max_bytes, "mb", &mut cleared),
+        max_messages: patched(&patch.max_messages, "mm", &mut cleared),
+        overflow_policy: patched(&patch.overflow_policy, "op", &mut cleared),
+        failsafe: patched(&patch.failsafe, "failsafe", &mut cleared),
+        secure: patched(&patch.secure, "secure", &mut cleared),
+        global: patched(&patch.global, "global", &mut cleared),
+        sender_name: patched(&patch.sender_name, "sname", &mut cleared),
+        sender_name_enforced: patched(&patch.sender_name_enforced, "snameenf", &mut cleared),
+        prefetch: patched(&patch.prefetch, "pf", &mut cleared),
+        expiry_override: patched(&patch.expiry_override, "expy", &mut cleared),
+        redelivery_delay: patched(&patch.redelivery_delay, "rdd", &mut cleared),
+        max_redelivery: patched(&patch.max_redelivery, "mrd", &mut cleared),
+        exclusive: patched(&patch.exclusive, "excl", &mut cleared),
+        flow_control: patched(&patch.flow_control, "fc", &mut cleared),
+        store: patched(&patch.store, "store", &mut cleared),
+        trace: patched(&patch.trace, "trace", &mut cleared),
+        import_channels: patched(&patch.import_channels, "import", &mut cleared),
+        ..Default::default()
+    };
+    let mut body = HashMap::new();
+    queue_properties(&queue, &mut body);
+    alter_destination(session, &patch.name, DESTINATION_TYPE_QUEUE, body, cleared)
+}
+
+/// deletes a queue from the EMS
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn delete_queue(session: &Session, queue: &str) -> Result<(), Error> {
+    trace!("deleting queue {}", queue);
+    //create queue map-message
+    let mut msg: MapMessage = Default::default();
+    msg.body
+        .insert("dn".to_string(), TypedValue::String(queue.to_string()));
+    msg.body.insert(
+        "dt".to_string(),
+        TypedValue::Integer(DESTINATION_TYPE_QUEUE),
+    );
     //header
     let mut header: HashMap<String, TypedValue> = HashMap::new();
     //actual boolean
@@ -294,8 +1058,7 @@ pub fn delete_queue(session: &Session, queue: &str) -> Result<(), Error> {
     header.insert("arseq".to_string(), TypedValue::Integer(1));
     msg.header = Some(header);
 
-    let admin_queue = Destination::Queue(ADMIN_QUEUE_NAME.to_string());
-    let result = session.send_message(&admin_queue, msg);
+    let result = execute(session, msg);
     match result {
         Ok(_) => {}
         Err(err) => {
@@ -339,126 +1102,104 @@ pub struct TopicInfo {
     pub incoming_total_count: Option<i64>,
     /// total count of outgoing messages
     pub outgoing_total_count: Option<i64>,
+    /// failsafe
+    pub failsafe: Option<bool>,
+    /// secure
+    pub secure: Option<bool>,
+    /// sender name
+    pub sender_name: Option<bool>,
+    /// sender name enforced
+    pub sender_name_enforced: Option<bool>,
+    /// flow control limit in bytes
+    pub flow_control: Option<i64>,
+    /// name of the store for persistent messages
+    pub store: Option<String>,
+    /// message tracing
+    pub trace: Option<TraceMode>,
+    /// channels messages are imported from
+    pub import_channels: Option<Vec<String>>,
+    /// channels messages are exported to
+    pub export_channels: Option<Vec<String>>,
+    /// fields reported by the server which are not modelled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub extra: HashMap<String, TypedValue>,
 }
 
 /// lists all topics present on the EMS
 ///
 /// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
 pub fn list_all_topics(session: &Session) -> Result<Vec<TopicInfo>, Error> {
-    let mut topics = Vec::new();
-    const TIMEOUT: i64 = 60000;
-    let mut msg: MapMessage = Default::default();
-    msg.body.insert(
-        "dt".to_string(),
-        TypedValue::Integer(DESTINATION_TYPE_TOPIC),
-    );
-    msg.body
-        .insert("permType".to_string(), TypedValue::Integer(6));
-    msg.body
-        .insert("pattern".to_string(), TypedValue::String(">".to_string()));
-    msg.body.insert("ia".to_string(), TypedValue::Boolean(true));
-    msg.body
-        .insert("first".to_string(), TypedValue::Integer(1000));
+    list_topics(session, ">")
+}
 
-    //header
-    let mut header: HashMap<String, TypedValue> = HashMap::new();
-    //actual boolean
-    header.insert(
-        "code".to_string(),
-        TypedValue::Integer(AdminCommands::ListDestination as i32),
-    );
-    header.insert("save".to_string(), TypedValue::Boolean(true));
-    header.insert("arseq".to_string(), TypedValue::Integer(1));
-    msg.header = Some(header);
+/// gets a single topic, None if the topic does not exist
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn get_topic(session: &Session, name: &str) -> Result<Option<TopicInfo>, Error> {
+    let topics = list_topics(session, name)?;
+    Ok(topics.into_iter().find(|topic| topic.name == name))
+}
 
-    let admin_queue = Destination::Queue(ADMIN_QUEUE_NAME.to_string());
-    let query_result = session.request_reply(&admin_queue, msg, TIMEOUT);
-    match query_result {
-        Ok(response) => {
-            if let Some(resp) = response {
-                match &resp {
-                    Message::MapMessage(map_message) => {
-                        //got response message
-                        for (key, val) in &map_message.body {
-                            let t_info: &MapMessage =
-                                extract!(TypedValue::Map(_), val).expect("inner message");
-                            let mut bool_global = false;
-                            if let Some(val) = t_info.body.get("global") {
-                                let global =
-                                    extract!(TypedValue::String(_), val).expect("global flag");
-                                bool_global = global == "1";
-                            }
-                            let prefetch =
-                                extract!(TypedValue::String(_), t_info.body.get("pf").unwrap())
-                                    .expect("extract property");
-                            let expiry =
-                                extract!(TypedValue::String(_), t_info.body.get("expy").unwrap())
-                                    .expect("extract property");
-                            let max_bytes =
-                                extract!(TypedValue::String(_), t_info.body.get("mb").unwrap())
-                                    .expect("extract property");
-                            let max_msgs =
-                                extract!(TypedValue::String(_), t_info.body.get("mm").unwrap())
-                                    .expect("extract property");
-                            let durable_count =
-                                extract!(TypedValue::String(_), t_info.body.get("cd").unwrap())
-                                    .expect("extract property");
-                            let subscriber_count =
-                                extract!(TypedValue::String(_), t_info.body.get("sc").unwrap())
-                                    .expect("extract property");
-                            let pending_messages =
-                                extract!(TypedValue::String(_), t_info.body.get("nm").unwrap())
-                                    .expect("extract property");
-                            let in_total_count =
-                                extract!(TypedValue::String(_), t_info.body.get("inct").unwrap())
-                                    .expect("extract property");
-                            let out_total_count =
-                                extract!(TypedValue::String(_), t_info.body.get("outct").unwrap())
-                                    .expect("extract property");
-                            let overflow =
-                                extract!(TypedValue::String(_), t_info.body.get("op").unwrap())
-                                    .expect("extract property");
-                            let overflow_policy: OverflowPolicy = match overflow.as_str() {
-                                "0" => OverflowPolicy::Default,
-                                "1" => OverflowPolicy::DiscardOld,
-                                "2" => OverflowPolicy::RejectIncoming,
-                                _ => OverflowPolicy::Default,
-                            };
-
-                            let topic_info = TopicInfo {
-                                name: key.to_string(),
-                                expiry_override: Some(expiry.parse::<i64>().unwrap()),
-                                global: Some(bool_global),
-                                max_bytes: Some(max_bytes.parse::<i64>().unwrap()),
-                                max_messages: Some(max_msgs.parse::<i64>().unwrap()),
-                                overflow_policy: Some(overflow_policy),
-                                prefetch: Some(prefetch.parse::<i32>().unwrap()),
-                                durable_count: Some(durable_count.parse::<i32>().unwrap()),
-                                subscriber_count: Some(subscriber_count.parse::<i32>().unwrap()),
-                                pending_messages: Some(pending_messages.parse::<i64>().unwrap()),
-                                incoming_total_count: Some(in_total_count.parse::<i64>().unwrap()),
-                                outgoing_total_count: Some(out_total_count.parse::<i64>().unwrap()),
-                            };
-                            topics.push(topic_info);
-                        }
-                    }
-                    _ => {
-                        warn!("unkown response from topic information request")
-                    }
-                }
-            }
-        }
-        Err(err) => {
-            error!("something went wrong retrieving topic information: {}", err);
-            return Err(err);
-        }
-    }
-    Ok(topics)
+/// lists the topics matching a pattern, `*` matches one element and `>` all trailing elements
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_topics(session: &Session, pattern: &str) -> Result<Vec<TopicInfo>, Error> {
+    iter_topics(session, pattern, &AdminOptions::default()).collect()
+}
+
+/// iterates over the topics matching a pattern, fetching them page by page
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn iter_topics<'a>(
+    session: &'a Session,
+    pattern: &str,
+    options: &AdminOptions,
+) -> DestinationIter<'a, TopicInfo> {
+    DestinationIter::new(
+        session,
+        DESTINATION_TYPE_TOPIC,
+        pattern,
+        options,
+        decode_topic,
+    )
+}
+/// decodes the topic information of a list reply
+fn decode_topic(name: &str, t_info: &MapMessage) -> Result<TopicInfo, DecodeError> {
+    let mut decoder = Decoder::new(name, t_info);
+    Ok(TopicInfo {
+        name: name.to_string(),
+        expiry_override: decoder.number("expy")?,
+        global: decoder.flag("global")?,
+        max_bytes: decoder.number("mb")?,
+        max_messages: decoder.number("mm")?,
+        overflow_policy: decoder.overflow_policy("op")?,
+        prefetch: decoder.number("pf")?,
+        durable_count: decoder.number("cd")?,
+        subscriber_count: decoder.number("sc")?,
+        pending_messages: decoder.number("nm")?,
+        incoming_total_count: decoder.number("inct")?,
+        outgoing_total_count: decoder.number("outct")?,
+        failsafe: decoder.flag("failsafe")?,
+        secure: decoder.flag("secure")?,
+        sender_name: decoder.flag("sname")?,
+        sender_name_enforced: decoder.flag("snameenf")?,
+        flow_control: decoder.number("fc")?,
+        store: decoder.string("store")?,
+        trace: decoder.trace("trace")?,
+        import_channels: decoder.string("import")?.map(|val| name_list(&val)),
+        export_channels: decoder.string("export")?.map(|val| name_list(&val)),
+        extra: decoder.extra(),
+    })
 }
 
 /// creates a topic on the EMS
 ///
 /// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
 pub fn create_topic(session: &Session, topic: &TopicInfo) -> Result<(), Error> {
     let mut msg: MapMessage = Default::default();
     msg.body
@@ -467,22 +1208,7 @@ pub fn create_topic(session: &Session, topic: &TopicInfo) -> Result<(), Error> {
         "dt".to_string(),
         TypedValue::Integer(DESTINATION_TYPE_TOPIC),
     );
-    if let Some(val) = topic.max_bytes {
-        msg.body.insert("mb".to_string(), TypedValue::Long(val));
-    }
-    if let Some(val) = topic.max_messages {
-        msg.body.insert("mm".to_string(), TypedValue::Long(val));
-    }
-    if let Some(val) = topic.global {
-        msg.body
-            .insert("global".to_string(), TypedValue::Boolean(val));
-    }
-    if let Some(val) = topic.prefetch {
-        msg.body.insert("pf".to_string(), TypedValue::Integer(val));
-    }
-    if let Some(val) = topic.expiry_override {
-        msg.body.insert("expy".to_string(), TypedValue::Long(val));
-    }
+    topic_properties(topic, &mut msg.body);
 
     //header
     let mut header: HashMap<String, TypedValue> = HashMap::new();
@@ -496,8 +1222,7 @@ pub fn create_topic(session: &Session, topic: &TopicInfo) -> Result<(), Error> {
     header.insert("arseq".to_string(), TypedValue::Integer(1));
     msg.header = Some(header);
 
-    let admin_queue = Destination::Queue(ADMIN_QUEUE_NAME.to_string());
-    let result = session.send_message(&admin_queue, msg);
+    let result = execute(session, msg);
     match result {
         Ok(_) => {}
         Err(err) => {
@@ -508,9 +1233,115 @@ pub fn create_topic(session: &Session, topic: &TopicInfo) -> Result<(), Error> {
     Ok(())
 }
 
+/// changes to the settable properties of a topic
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TopicInfoPatch {
+    /// name of the topic
+    pub name: String,
+    /// expiration override
+    pub expiry_override: Patch<i64>,
+    /// global
+    pub global: Patch<bool>,
+    /// max size
+    pub max_bytes: Patch<i64>,
+    /// max number of messages
+    pub max_messages: Patch<i64>,
+    /// overflow policy
+    pub overflow_policy: Patch<OverflowPolicy>,
+    /// prefetch
+    pub prefetch: Patch<i32>,
+    /// failsafe
+    pub failsafe: Patch<bool>,
+    /// secure
+    pub secure: Patch<bool>,
+    /// sender name
+    pub sender_name: Patch<bool>,
+    /// sender name enforced
+    pub sender_name_enforced: Patch<bool>,
+    /// flow control limit in bytes
+    pub flow_control: Patch<i64>,
+    /// name of the store for persistent messages
+    pub store: Patch<String>,
+    /// message tracing
+    pub trace: Patch<TraceMode>,
+    /// channels messages are imported from
+    pub import_channels: Patch<Vec<String>>,
+    /// channels messages are exported to
+    pub export_channels: Patch<Vec<String>>,
+}
+
+impl From<&TopicInfo> for TopicInfoPatch {
+    /// sets every property present in the topic info, keeps all others
+    fn from(topic: &TopicInfo) -> Self {
+        TopicInfoPatch {
+            name: topic.name.clone(),
+            expiry_override: topic.expiry_override.into(),
+            global: topic.global.into(),
+            max_bytes: topic.max_bytes.into(),
+            max_messages: topic.max_messages.into(),
+            overflow_policy: topic.overflow_policy.clone().into(),
+            prefetch: topic.prefetch.into(),
+            failsafe: topic.failsafe.into(),
+            secure: topic.secure.into(),
+            sender_name: topic.sender_name.into(),
+            sender_name_enforced: topic.sender_name_enforced.into(),
+            flow_control: topic.flow_control.into(),
+            store: topic.store.clone().into(),
+            trace: topic.trace.clone().into(),
+            import_channels: topic.import_channels.clone().into(),
+            export_channels: topic.export_channels.clone().into(),
+        }
+    }
+}
+
+/// updates the properties of an existing topic, only properties present in the topic info are changed
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn update_topic(session: &Session, topic: &TopicInfo) -> Result<(), Error> {
+    patch_topic(session, &TopicInfoPatch::from(topic))
+}
+
+/// applies a patch to the properties of an existing topic
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn patch_topic(session: &Session, patch: &TopicInfoPatch) -> Result<(), Error> {
+    trace!("updating topic {}", patch.name);
+    let mut cleared = Vec::new();
+    let topic = TopicInfo {
+        name: patch.name.clone(),
+        expiry_override: patched(&patch.expiry_override, "expy", &mut cleared),
+        global: patched(&patch.global, "global", &mut cleared),
+        max_bytes: patched(&patch.max_bytes, "mb", &mut cleared),
+        max_messages: patched(&patch.max_messages, "mm", &mut cleared),
+        overflow_policy: patched(&patch.overflow_policy, "op", &mut cleared),
+        prefetch: patched(&patch.prefetch, "pf", &mut cleared),
+        failsafe: patched(&patch.failsafe, "failsafe", &mut cleared),
+        secure: patched(&patch.secure, "secure", &mut cleared),
+        sender_name: patched(&patch.sender_name, "sname", &mut cleared),
+        sender_name_enforced: patched(&patch.sender_name_enforced, "snameenf", &mut cleared),
+        flow_control: patched(&patch.flow_control, "fc", &mut cleared),
+        store: patched(&patch.store, "store", &mut cleared),
+        trace: patched(&patch.trace, "trace", &mut cleared),
+        import_channels: patched(&patch.import_channels, "import", &mut cleared),
+        export_channels: patched(&patch.export_channels, "export", &mut cleared),
+        ..Default::default()
+    };
+    let mut body = HashMap::new();
+    topic_properties(&topic, &mut body);
+    alter_destination(session, &patch.name, DESTINATION_TYPE_TOPIC, body, cleared)
+}
+
 /// deletes a topic from the EMS
 ///
 /// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
 pub fn delete_topic(session: &Session, topic: &str) -> Result<(), Error> {
     trace!("deleting topic {}", topic);
     //create topic map-message
@@ -533,8 +1364,7 @@ pub fn delete_topic(session: &Session, topic: &str) -> Result<(), Error> {
     header.insert("arseq".to_string(), TypedValue::Integer(1));
     msg.header = Some(header);
 
-    let admin_queue = Destination::Queue(ADMIN_QUEUE_NAME.to_string());
-    let result = session.send_message(&admin_queue, msg);
+    let result = execute(session, msg);
     match result {
         Ok(_) => {}
         Err(err) => {
@@ -546,196 +1376,1994 @@ pub fn delete_topic(session: &Session, topic: &str) -> Result<(), Error> {
 }
 
 //
-// Bridges
+// Purge
 //
 
-/// create a bridge
-pub fn create_bridge(session: &Session, bridge: &BridgeInfo) -> Result<(), Error> {
-    //create bridge map-message
+/// removes all pending messages from a queue
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// returns the number of removed messages if the server reports it.
+pub fn purge_queue(session: &Session, queue: &str) -> Result<Option<i64>, Error> {
     let mut msg: MapMessage = Default::default();
-    match bridge.source.clone() {
-        Destination::Queue(name) => {
-            msg.body.insert(
-                "st".to_string(),
-                TypedValue::Integer(DESTINATION_TYPE_QUEUE),
-            );
-            msg.body.insert("sn".to_string(), TypedValue::String(name));
-        }
-        Destination::Topic(name) => {
-            msg.body.insert(
-                "st".to_string(),
-                TypedValue::Integer(DESTINATION_TYPE_TOPIC),
-            );
-            msg.body.insert("sn".to_string(), TypedValue::String(name));
-        }
-    }
+    msg.body
+        .insert("dn".to_string(), TypedValue::String(queue.to_string()));
+    msg.body.insert(
+        "dt".to_string(),
+        TypedValue::Integer(DESTINATION_TYPE_QUEUE),
+    );
+    purge(session, AdminCommands::PurgeDestination, msg, queue)
+}
 
-    match bridge.target.clone() {
-        Destination::Queue(name) => {
-            msg.body.insert(
-                "tt".to_string(),
-                TypedValue::Integer(DESTINATION_TYPE_QUEUE),
-            );
-            msg.body.insert("tn".to_string(), TypedValue::String(name));
-        }
-        Destination::Topic(name) => {
-            msg.body.insert(
-                "tt".to_string(),
-                TypedValue::Integer(DESTINATION_TYPE_TOPIC),
-            );
-            msg.body.insert("tn".to_string(), TypedValue::String(name));
-        }
-    }
-    if let Some(sel) = bridge.selector.clone() {
-        msg.body.insert("sel".to_string(), TypedValue::String(sel));
-    }
-    //header
-    let mut header: HashMap<String, TypedValue> = HashMap::new();
-    //actual boolean
-    header.insert("JMS_TIBCO_MSG_EXT".to_string(), TypedValue::Boolean(true));
-    header.insert(
-        "code".to_string(),
-        TypedValue::Integer(AdminCommands::CreateBridge as i32),
+/// removes all pending messages from all subscriptions of a topic
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// returns the number of removed messages if the server reports it.
+pub fn purge_topic(session: &Session, topic: &str) -> Result<Option<i64>, Error> {
+    let mut msg: MapMessage = Default::default();
+    msg.body
+        .insert("dn".to_string(), TypedValue::String(topic.to_string()));
+    msg.body.insert(
+        "dt".to_string(),
+        TypedValue::Integer(DESTINATION_TYPE_TOPIC),
     );
-    header.insert("save".to_string(), TypedValue::Boolean(true));
-    header.insert("arseq".to_string(), TypedValue::Integer(1));
-    msg.header = Some(header);
+    purge(session, AdminCommands::PurgeDestination, msg, topic)
+}
 
-    let admin_queue = Destination::Queue(ADMIN_QUEUE_NAME.to_string());
-    let result = session.send_message(&admin_queue, msg);
-    match result {
-        Ok(_) => {}
-        Err(err) => {
-            error!(
-                "error while creating bridge {:?}->{:?}: {}",
-                bridge.source, bridge.target, err
-            );
-            return Err(err);
-        }
+/// removes all pending messages from a durable subscription
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// returns the number of removed messages if the server reports it.
+pub fn purge_durable(
+    session: &Session,
+    durable_name: &str,
+    client_id: Option<&str>,
+) -> Result<Option<i64>, Error> {
+    let mut msg: MapMessage = Default::default();
+    msg.body.insert(
+        "dur".to_string(),
+        TypedValue::String(durable_name.to_string()),
+    );
+    if let Some(client_id) = client_id {
+        msg.body
+            .insert("cid".to_string(), TypedValue::String(client_id.to_string()));
     }
-    Ok(())
+    purge(session, AdminCommands::PurgeDurable, msg, durable_name)
 }
 
-/// delete a bridge
-pub fn delete_bridge(session: &Session, bridge: &BridgeInfo) -> Result<(), Error> {
-    //create bridge map-message
+/// removes all pending messages from all queues matching a pattern, `>` matches all queues
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// returns the number of removed messages if the server reports it.
+pub fn purge_all_queues(session: &Session, pattern: &str) -> Result<Option<i64>, Error> {
     let mut msg: MapMessage = Default::default();
-    match bridge.source.clone() {
-        Destination::Queue(name) => {
-            msg.body.insert(
-                "st".to_string(),
-                TypedValue::Integer(DESTINATION_TYPE_QUEUE),
-            );
-            msg.body.insert("sn".to_string(), TypedValue::String(name));
-        }
-        Destination::Topic(name) => {
-            msg.body.insert(
-                "st".to_string(),
-                TypedValue::Integer(DESTINATION_TYPE_TOPIC),
-            );
-            msg.body.insert("sn".to_string(), TypedValue::String(name));
-        }
-    }
+    msg.body.insert(
+        "pattern".to_string(),
+        TypedValue::String(pattern.to_string()),
+    );
+    purge(session, AdminCommands::PurgeAllQueues, msg, pattern)
+}
 
-    match bridge.target.clone() {
-        Destination::Queue(name) => {
-            msg.body.insert(
-                "tt".to_string(),
-                TypedValue::Integer(DESTINATION_TYPE_QUEUE),
-            );
-            msg.body.insert("tn".to_string(), TypedValue::String(name));
-        }
-        Destination::Topic(name) => {
-            msg.body.insert(
-                "tt".to_string(),
-                TypedValue::Integer(DESTINATION_TYPE_TOPIC),
-            );
-            msg.body.insert("tn".to_string(), TypedValue::String(name));
-        }
-    }
+fn purge(
+    session: &Session,
+    command: AdminCommands,
+    mut msg: MapMessage,
+    name: &str,
+) -> Result<Option<i64>, Error> {
+    trace!("purging {}", name);
     //header
     let mut header: HashMap<String, TypedValue> = HashMap::new();
     //actual boolean
     header.insert("JMS_TIBCO_MSG_EXT".to_string(), TypedValue::Boolean(true));
-    header.insert(
-        "code".to_string(),
-        TypedValue::Integer(AdminCommands::DeleteBridge as i32),
-    );
+    header.insert("code".to_string(), TypedValue::Integer(command as i32));
     header.insert("save".to_string(), TypedValue::Boolean(true));
     header.insert("arseq".to_string(), TypedValue::Integer(1));
     msg.header = Some(header);
 
-    let admin_queue = Destination::Queue(ADMIN_QUEUE_NAME.to_string());
-    let result = session.send_message(&admin_queue, msg);
-    match result {
-        Ok(_) => {}
+    match execute(session, msg) {
+        Ok(reply) => Ok(match reply.body.get("nm") {
+            Some(TypedValue::Integer(val)) => Some(*val as i64),
+            Some(TypedValue::Long(val)) => Some(*val),
+            Some(TypedValue::String(val)) => val.parse::<i64>().ok(),
+            _ => None,
+        }),
         Err(err) => {
-            error!(
-                "error while deleting bridge {:?}->{:?}: {}",
-                bridge.source, bridge.target, err
-            );
-            return Err(err);
+            error!("error while purging {}: {}", name, err);
+            Err(err)
         }
     }
-    Ok(())
 }
 
 //
-// Server
+// Durables
 //
 
-/// get server state
+/// holds durable subscription information
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct DurableInfo {
+    /// name of the durable subscription
+    pub name: String,
+    /// topic of the durable subscription
+    pub topic: String,
+    /// client id of the connection owning the durable, None for shared durables
+    pub client_id: Option<String>,
+    /// selector
+    pub selector: Option<String>,
+    /// messages pending for the durable
+    pub pending_messages: Option<i64>,
+    /// whether a consumer is connected to the durable
+    pub connected: Option<bool>,
+    /// user of the connected consumer
+    pub consumer_user: Option<String>,
+    /// whether the durable is shared between consumers
+    pub shared: Option<bool>,
+    /// fields reported by the server which are not modelled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub extra: HashMap<String, TypedValue>,
+}
+
+/// lists the durables of the topics matching a pattern, `*` matches one element and `>` all trailing elements
 ///
 /// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
-pub fn get_server_state(session: &Session) -> Result<ServerState, Error> {
-    const TIMEOUT: i64 = 60000;
-    let mut msg: MapMessage = Default::default();
-
-    //header
-    let mut header: HashMap<String, TypedValue> = HashMap::new();
-    //actual boolean
+pub fn list_durables(session: &Session, topic_pattern: &str) -> Result<Vec<DurableInfo>, Error> {
+    let mut msg = command(AdminCommands::ListDurables);
+    msg.body.insert(
+        "pattern".to_string(),
+        TypedValue::String(topic_pattern.to_string()),
+    );
+    let reply = match execute(session, msg) {
+        Ok(reply) => reply,
+        Err(err) => {
+            error!("something went wrong retrieving durables: {}", err);
+            return Err(err);
+        }
+    };
+    let mut durables = Vec::new();
+    for (key, info) in positional_entries(&reply, "durable") {
+        durables.push(decode_durable(key, info)?);
+    }
+    Ok(durables)
+}
+
+/// decodes a durable of a list reply
+fn decode_durable(key: &str, info: &MapMessage) -> Result<DurableInfo, DecodeError> {
+    let mut decoder = Decoder::new(key, info);
+    let Some(name) = decoder.string("dn")? else {
+        return Err(decoder.error("dn", "missing durable name".to_string()));
+    };
+    let Some(topic) = decoder.string("tn")? else {
+        return Err(decoder.error("tn", "missing topic name".to_string()));
+    };
+    Ok(DurableInfo {
+        name,
+        topic,
+        client_id: decoder.string("cid")?.filter(|cid| !cid.is_empty()),
+        selector: decoder.string("sel")?.filter(|sel| !sel.is_empty()),
+        pending_messages: decoder.number("nm")?,
+        connected: decoder.flag("conn")?,
+        consumer_user: decoder.string("un")?.filter(|user| !user.is_empty()),
+        shared: decoder.flag("shared")?,
+        extra: decoder.extra(),
+    })
+}
+
+/// creates a durable subscription on a topic, so messages are kept before the first consumer connects
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn create_durable(session: &Session, durable: &DurableInfo) -> Result<(), Error> {
+    super::selector::validate(durable.selector.as_deref())?;
+    trace!(
+        "creating durable {} on topic {}",
+        durable.name,
+        durable.topic
+    );
+    let mut msg = command(AdminCommands::CreateDurable);
+    durable_fields(
+        &mut msg.body,
+        &durable.topic,
+        &durable.name,
+        durable.client_id.as_deref(),
+    );
+    if let Some(sel) = &durable.selector {
+        msg.body
+            .insert("sel".to_string(), TypedValue::String(sel.clone()));
+    }
+    if let Some(shared) = durable.shared {
+        msg.body
+            .insert("shared".to_string(), TypedValue::Boolean(shared));
+    }
+    if let Err(err) = execute(session, msg) {
+        error!("error while creating durable {}: {}", durable.name, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// deletes a durable subscription together with its pending messages
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn delete_durable(
+    session: &Session,
+    topic: &str,
+    name: &str,
+    client_id: Option<&str>,
+) -> Result<(), Error> {
+    trace!("deleting durable {} on topic {}", name, topic);
+    let mut msg = command(AdminCommands::DeleteDurable);
+    durable_fields(&mut msg.body, topic, name, client_id);
+    if let Err(err) = execute(session, msg) {
+        error!("error while deleting durable {}: {}", name, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+fn durable_fields(
+    body: &mut HashMap<String, TypedValue>,
+    topic: &str,
+    name: &str,
+    client_id: Option<&str>,
+) {
+    body.insert("tn".to_string(), TypedValue::String(topic.to_string()));
+    body.insert("dn".to_string(), TypedValue::String(name.to_string()));
+    if let Some(client_id) = client_id {
+        body.insert("cid".to_string(), TypedValue::String(client_id.to_string()));
+    }
+}
+
+//
+// Connections
+//
+
+/// holds client connection information
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ConnectionInfo {
+    /// id of the connection, used by [`destroy_connection`]
+    pub id: i64,
+    /// user of the connection
+    pub user: Option<String>,
+    /// host the client connected from
+    pub host: Option<String>,
+    /// client id of the connection
+    pub client_id: Option<String>,
+    /// type of the connection, e.g. `jms` or `admin`
+    pub connection_type: Option<String>,
+    /// number of open sessions
+    pub session_count: Option<i64>,
+    /// number of open consumers
+    pub consumer_count: Option<i64>,
+    /// number of open producers
+    pub producer_count: Option<i64>,
+    /// time since the connection was opened
+    pub uptime: Option<Duration>,
+    /// fields reported by the server which are not modelled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub extra: HashMap<String, TypedValue>,
+}
+
+/// selects the connections listed by [`list_connections`], unset fields match all connections
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConnectionFilter {
+    /// only connections of this user
+    pub user: Option<String>,
+    /// only connections from this host
+    pub host: Option<String>,
+    /// only connections with this client id
+    pub client_id: Option<String>,
+}
+
+impl ConnectionFilter {
+    fn matches(&self, connection: &ConnectionInfo) -> bool {
+        let matches = |expected: &Option<String>, actual: &Option<String>| {
+            expected.is_none() || expected == actual
+        };
+        matches(&self.user, &connection.user)
+            && matches(&self.host, &connection.host)
+            && matches(&self.client_id, &connection.client_id)
+    }
+}
+
+/// lists the client connections matching a filter
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_connections(
+    session: &Session,
+    filter: &ConnectionFilter,
+) -> Result<Vec<ConnectionInfo>, Error> {
+    let mut msg = command(AdminCommands::ListConnections);
+    for (field, value) in [
+        ("un", &filter.user),
+        ("host", &filter.host),
+        ("cid", &filter.client_id),
+    ] {
+        if let Some(value) = value {
+            msg.body
+                .insert(field.to_string(), TypedValue::String(value.clone()));
+        }
+    }
+    let reply = match execute(session, msg) {
+        Ok(reply) => reply,
+        Err(err) => {
+            error!("something went wrong retrieving connections: {}", err);
+            return Err(err);
+        }
+    };
+    let mut connections = Vec::new();
+    for (key, info) in positional_entries(&reply, "connection") {
+        connections.push(decode_connection(key, info)?);
+    }
+    // the server may ignore the filter, so apply it again
+    connections.retain(|connection| filter.matches(connection));
+    Ok(connections)
+}
+
+/// decodes a connection of a list reply
+fn decode_connection(key: &str, info: &MapMessage) -> Result<ConnectionInfo, DecodeError> {
+    let mut decoder = Decoder::new(key, info);
+    let Some(id) = decoder.number("id")? else {
+        return Err(decoder.error("id", "missing connection id".to_string()));
+    };
+    Ok(ConnectionInfo {
+        id,
+        user: decoder.string("un")?.filter(|user| !user.is_empty()),
+        host: decoder.string("host")?.filter(|host| !host.is_empty()),
+        client_id: decoder.string("cid")?.filter(|cid| !cid.is_empty()),
+        connection_type: decoder.string("type")?,
+        session_count: decoder.number("ns")?,
+        consumer_count: decoder.number("nc")?,
+        producer_count: decoder.number("np")?,
+        uptime: decoder.number("up")?.map(Duration::from_millis),
+        extra: decoder.extra(),
+    })
+}
+
+/// disconnects a client connection, its sessions are closed by the server
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn destroy_connection(session: &Session, id: i64) -> Result<(), Error> {
+    trace!("destroying connection {}", id);
+    let mut msg = command(AdminCommands::DestroyConnection);
+    msg.body.insert("id".to_string(), TypedValue::Long(id));
+    if let Err(err) = execute(session, msg) {
+        error!("error while destroying connection {}: {}", id, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+//
+// Consumers and producers
+//
+
+/// holds information about a consumer of a destination
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ConsumerInfo {
+    /// id of the consumer
+    pub id: i64,
+    /// destination the consumer receives from
+    pub destination: Destination,
+    /// id of the connection of the consumer, see [`list_connections`]
+    pub connection_id: Option<i64>,
+    /// user of the connection
+    pub user: Option<String>,
+    /// host the client connected from
+    pub host: Option<String>,
+    /// selector
+    pub selector: Option<String>,
+    /// name of the durable subscription
+    pub durable_name: Option<String>,
+    /// messages delivered to the consumer
+    pub delivered_count: Option<i64>,
+    /// delivered messages which are not acknowledged yet
+    pub pending_ack_count: Option<i64>,
+    /// messages per second delivered to the consumer
+    pub message_rate: Option<f64>,
+    /// fields reported by the server which are not modelled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub extra: HashMap<String, TypedValue>,
+}
+
+/// holds information about a producer of a destination
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ProducerInfo {
+    /// id of the producer
+    pub id: i64,
+    /// destination the producer sends to
+    pub destination: Destination,
+    /// id of the connection of the producer, see [`list_connections`]
+    pub connection_id: Option<i64>,
+    /// user of the connection
+    pub user: Option<String>,
+    /// host the client connected from
+    pub host: Option<String>,
+    /// messages sent by the producer
+    pub sent_count: Option<i64>,
+    /// messages per second sent by the producer
+    pub message_rate: Option<f64>,
+    /// fields reported by the server which are not modelled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub extra: HashMap<String, TypedValue>,
+}
+
+/// lists the consumers of a destination
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_consumers(
+    session: &Session,
+    destination: &Destination,
+) -> Result<Vec<ConsumerInfo>, Error> {
+    let reply = list_clients(session, AdminCommands::ListConsumers, destination)?;
+    let mut consumers = Vec::new();
+    for (key, info) in positional_entries(&reply, "consumer") {
+        let mut decoder = Decoder::new(key, info);
+        let (id, destination) = client_identity(&mut decoder)?;
+        consumers.push(ConsumerInfo {
+            id,
+            destination,
+            connection_id: decoder.number("conn")?,
+            user: decoder.string("un")?.filter(|user| !user.is_empty()),
+            host: decoder.string("host")?.filter(|host| !host.is_empty()),
+            selector: decoder.string("sel")?.filter(|sel| !sel.is_empty()),
+            durable_name: decoder.string("dur")?.filter(|dur| !dur.is_empty()),
+            delivered_count: decoder.number("nd")?,
+            pending_ack_count: decoder.number("npa")?,
+            message_rate: decoder.number("rate")?,
+            extra: decoder.extra(),
+        });
+    }
+    Ok(consumers)
+}
+
+/// lists the producers of a destination
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_producers(
+    session: &Session,
+    destination: &Destination,
+) -> Result<Vec<ProducerInfo>, Error> {
+    let reply = list_clients(session, AdminCommands::ListProducers, destination)?;
+    let mut producers = Vec::new();
+    for (key, info) in positional_entries(&reply, "producer") {
+        let mut decoder = Decoder::new(key, info);
+        let (id, destination) = client_identity(&mut decoder)?;
+        producers.push(ProducerInfo {
+            id,
+            destination,
+            connection_id: decoder.number("conn")?,
+            user: decoder.string("un")?.filter(|user| !user.is_empty()),
+            host: decoder.string("host")?.filter(|host| !host.is_empty()),
+            sent_count: decoder.number("ns")?,
+            message_rate: decoder.number("rate")?,
+            extra: decoder.extra(),
+        });
+    }
+    Ok(producers)
+}
+
+fn list_clients(
+    session: &Session,
+    code: AdminCommands,
+    destination: &Destination,
+) -> Result<MapMessage, Error> {
+    let mut msg = command(code);
+    destination_fields(&mut msg.body, "dt", "dn", destination);
+    match execute(session, msg) {
+        Ok(reply) => Ok(reply),
+        Err(err) => {
+            error!(
+                "something went wrong retrieving clients of {:?}: {}",
+                destination, err
+            );
+            Err(err)
+        }
+    }
+}
+
+/// id and destination of a consumer or producer, both fields are required
+fn client_identity(decoder: &mut Decoder) -> Result<(i64, Destination), DecodeError> {
+    let Some(id) = decoder.number("id")? else {
+        return Err(decoder.error("id", "missing id".to_string()));
+    };
+    Ok((id, decoder.destination("dt", "dn")?))
+}
+
+//
+// Routes
+//
+
+/// holds route information
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct RouteInfo {
+    /// name of the route, which is the name of the server it connects to
+    pub name: String,
+    /// url of the server the route connects to
+    pub url: String,
+    /// zone of the route, servers use the default zone if none is given
+    pub zone_name: Option<String>,
+    /// type of the zone
+    pub zone_type: Option<ZoneType>,
+    /// state of the route, reported by the server
+    pub state: Option<RouteState>,
+    /// fields reported by the server which are not modelled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub extra: HashMap<String, TypedValue>,
+}
+
+/// how messages are forwarded within a zone
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ZoneType {
+    /// messages travel at most one route
+    OneHop,
+    /// messages are forwarded across several routes
+    MultiHop,
+}
+
+impl ZoneType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ZoneType::OneHop => "1hop",
+            ZoneType::MultiHop => "mhop",
+        }
+    }
+
+    fn parse(val: &str) -> Option<ZoneType> {
+        match val {
+            "1hop" | "one_hop" => Some(ZoneType::OneHop),
+            "mhop" | "multi_hop" => Some(ZoneType::MultiHop),
+            _ => None,
+        }
+    }
+}
+
+/// connection state of a route
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RouteState {
+    /// the route is connected to the other server
+    Connected,
+    /// the route is not connected
+    Disconnected,
+}
+
+/// lists all routes of the EMS
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_routes(session: &Session) -> Result<Vec<RouteInfo>, Error> {
+    let reply = match execute(session, command(AdminCommands::ListRoutes)) {
+        Ok(reply) => reply,
+        Err(err) => {
+            error!("something went wrong retrieving routes: {}", err);
+            return Err(err);
+        }
+    };
+    let mut routes = Vec::new();
+    for (name, info) in named_entries(&reply, "route") {
+        routes.push(decode_route(name, info)?);
+    }
+    Ok(routes)
+}
+
+/// decodes a route of a list reply
+fn decode_route(name: &str, info: &MapMessage) -> Result<RouteInfo, DecodeError> {
+    let mut decoder = Decoder::new(name, info);
+    let zone_type = match decoder.string("zt")? {
+        None => None,
+        Some(val) => match ZoneType::parse(&val) {
+            Some(zone_type) => Some(zone_type),
+            None => return Err(decoder.error("zt", format!("invalid zone type '{val}'"))),
+        },
+    };
+    Ok(RouteInfo {
+        name: name.to_string(),
+        url: decoder.string("url")?.unwrap_or_default(),
+        zone_name: decoder.string("zn")?.filter(|zone| !zone.is_empty()),
+        zone_type,
+        state: decoder.flag("conn")?.map(|connected| match connected {
+            true => RouteState::Connected,
+            false => RouteState::Disconnected,
+        }),
+        extra: decoder.extra(),
+    })
+}
+
+/// creates a route to another server
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn create_route(session: &Session, route: &RouteInfo) -> Result<(), Error> {
+    trace!("creating route {} to {}", route.name, route.url);
+    let mut msg = command(AdminCommands::CreateRoute);
+    msg.body
+        .insert("rn".to_string(), TypedValue::String(route.name.clone()));
+    msg.body
+        .insert("url".to_string(), TypedValue::String(route.url.clone()));
+    if let Some(zone_name) = &route.zone_name {
+        msg.body
+            .insert("zn".to_string(), TypedValue::String(zone_name.clone()));
+    }
+    if let Some(zone_type) = &route.zone_type {
+        msg.body.insert(
+            "zt".to_string(),
+            TypedValue::String(zone_type.as_str().to_string()),
+        );
+    }
+    if let Err(err) = execute(session, msg) {
+        error!("error while creating route {}: {}", route.name, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// deletes a route
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn delete_route(session: &Session, name: &str) -> Result<(), Error> {
+    trace!("deleting route {}", name);
+    let mut msg = command(AdminCommands::DeleteRoute);
+    msg.body
+        .insert("rn".to_string(), TypedValue::String(name.to_string()));
+    if let Err(err) = execute(session, msg) {
+        error!("error while deleting route {}: {}", name, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+//
+// Factories
+//
+
+/// holds connection factory information
+///
+/// factories are stored by the server under their JNDI name, clients look them up
+/// with [`crate::lookup_factory`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct FactoryInfo {
+    /// JNDI name of the factory
+    pub name: String,
+    /// kind of connections created by the factory
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub factory_type: FactoryType,
+    /// server url, fault tolerant servers are given as comma separated list
+    pub url: String,
+    /// client id of the connections created by the factory
+    pub client_id: Option<String>,
+    /// number of attempts to connect
+    pub connect_attempt_count: Option<i32>,
+    /// time between two attempts to connect
+    pub connect_attempt_delay: Option<Duration>,
+    /// number of attempts to reconnect after a fault tolerant failover
+    pub reconnect_attempt_count: Option<i32>,
+    /// time between two attempts to reconnect
+    pub reconnect_attempt_delay: Option<Duration>,
+    /// fields reported by the server which are not modelled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub extra: HashMap<String, TypedValue>,
+}
+
+/// kind of connections created by a factory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FactoryType {
+    /// connections for queues and topics
+    #[default]
+    Generic,
+    /// queue connections
+    Queue,
+    /// topic connections
+    Topic,
+}
+
+impl FactoryType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FactoryType::Generic => "generic",
+            FactoryType::Queue => "queue",
+            FactoryType::Topic => "topic",
+        }
+    }
+
+    fn parse(val: &str) -> Option<FactoryType> {
+        match val {
+            "generic" => Some(FactoryType::Generic),
+            "queue" => Some(FactoryType::Queue),
+            "topic" => Some(FactoryType::Topic),
+            _ => None,
+        }
+    }
+}
+
+/// lists all connection factories of the EMS
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_factories(session: &Session) -> Result<Vec<FactoryInfo>, Error> {
+    let reply = match execute(session, command(AdminCommands::ListFactories)) {
+        Ok(reply) => reply,
+        Err(err) => {
+            error!("something went wrong retrieving factories: {}", err);
+            return Err(err);
+        }
+    };
+    let mut factories = Vec::new();
+    for (name, info) in named_entries(&reply, "factory") {
+        factories.push(decode_factory(name, info)?);
+    }
+    Ok(factories)
+}
+
+/// decodes a connection factory of a list reply
+fn decode_factory(name: &str, info: &MapMessage) -> Result<FactoryInfo, DecodeError> {
+    let mut decoder = Decoder::new(name, info);
+    let factory_type = match decoder.string("ft")? {
+        None => FactoryType::default(),
+        Some(val) => match FactoryType::parse(&val) {
+            Some(factory_type) => factory_type,
+            None => return Err(decoder.error("ft", format!("invalid factory type '{val}'"))),
+        },
+    };
+    Ok(FactoryInfo {
+        name: name.to_string(),
+        factory_type,
+        url: decoder.string("url")?.unwrap_or_default(),
+        client_id: decoder.string("cid")?.filter(|cid| !cid.is_empty()),
+        connect_attempt_count: decoder.number("cac")?,
+        connect_attempt_delay: decoder.number("cad")?.map(Duration::from_millis),
+        reconnect_attempt_count: decoder.number("rac")?,
+        reconnect_attempt_delay: decoder.number("rad")?.map(Duration::from_millis),
+        extra: decoder.extra(),
+    })
+}
+
+/// creates a connection factory
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn create_factory(session: &Session, factory: &FactoryInfo) -> Result<(), Error> {
+    trace!("creating factory {} for {}", factory.name, factory.url);
+    let mut msg = command(AdminCommands::CreateFactory);
+    msg.body
+        .insert("jn".to_string(), TypedValue::String(factory.name.clone()));
+    msg.body.insert(
+        "ft".to_string(),
+        TypedValue::String(factory.factory_type.as_str().to_string()),
+    );
+    msg.body
+        .insert("url".to_string(), TypedValue::String(factory.url.clone()));
+    if let Some(client_id) = &factory.client_id {
+        msg.body
+            .insert("cid".to_string(), TypedValue::String(client_id.clone()));
+    }
+    let counts = [
+        ("cac", factory.connect_attempt_count),
+        ("rac", factory.reconnect_attempt_count),
+    ];
+    for (field, count) in counts {
+        if let Some(count) = count {
+            msg.body
+                .insert(field.to_string(), TypedValue::Integer(count));
+        }
+    }
+    let delays = [
+        ("cad", factory.connect_attempt_delay),
+        ("rad", factory.reconnect_attempt_delay),
+    ];
+    for (field, delay) in delays {
+        if let Some(delay) = delay {
+            msg.body.insert(
+                field.to_string(),
+                TypedValue::Long(delay.as_millis() as i64),
+            );
+        }
+    }
+    if let Err(err) = execute(session, msg) {
+        error!("error while creating factory {}: {}", factory.name, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// deletes a connection factory
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn delete_factory(session: &Session, name: &str) -> Result<(), Error> {
+    trace!("deleting factory {}", name);
+    let mut msg = command(AdminCommands::DeleteFactory);
+    msg.body
+        .insert("jn".to_string(), TypedValue::String(name.to_string()));
+    if let Err(err) = execute(session, msg) {
+        error!("error while deleting factory {}: {}", name, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+//
+// Bridges
+//
+
+/// create a bridge
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn create_bridge(session: &Session, bridge: &BridgeInfo) -> Result<(), Error> {
+    super::selector::validate(bridge.selector.as_deref())?;
+    //create bridge map-message
+    let mut msg: MapMessage = Default::default();
+    destination_fields(&mut msg.body, "st", "sn", &bridge.source);
+    destination_fields(&mut msg.body, "tt", "tn", &bridge.target);
+    if let Some(sel) = bridge.selector.clone() {
+        msg.body.insert("sel".to_string(), TypedValue::String(sel));
+    }
+    //header
+    let mut header: HashMap<String, TypedValue> = HashMap::new();
+    //actual boolean
+    header.insert("JMS_TIBCO_MSG_EXT".to_string(), TypedValue::Boolean(true));
+    header.insert(
+        "code".to_string(),
+        TypedValue::Integer(AdminCommands::CreateBridge as i32),
+    );
+    header.insert("save".to_string(), TypedValue::Boolean(true));
+    header.insert("arseq".to_string(), TypedValue::Integer(1));
+    msg.header = Some(header);
+
+    let result = execute(session, msg);
+    match result {
+        Ok(_) => {}
+        Err(err) => {
+            error!(
+                "error while creating bridge {:?}->{:?}: {}",
+                bridge.source, bridge.target, err
+            );
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// delete a bridge
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn delete_bridge(session: &Session, bridge: &BridgeInfo) -> Result<(), Error> {
+    //create bridge map-message
+    let mut msg: MapMessage = Default::default();
+    destination_fields(&mut msg.body, "st", "sn", &bridge.source);
+    destination_fields(&mut msg.body, "tt", "tn", &bridge.target);
+    //header
+    let mut header: HashMap<String, TypedValue> = HashMap::new();
+    //actual boolean
+    header.insert("JMS_TIBCO_MSG_EXT".to_string(), TypedValue::Boolean(true));
+    header.insert(
+        "code".to_string(),
+        TypedValue::Integer(AdminCommands::DeleteBridge as i32),
+    );
+    header.insert("save".to_string(), TypedValue::Boolean(true));
+    header.insert("arseq".to_string(), TypedValue::Integer(1));
+    msg.header = Some(header);
+
+    let result = execute(session, msg);
+    match result {
+        Ok(_) => {}
+        Err(err) => {
+            error!(
+                "error while deleting bridge {:?}->{:?}: {}",
+                bridge.source, bridge.target, err
+            );
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// lists all bridges present on the EMS
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_bridges(session: &Session) -> Result<Vec<BridgeInfo>, Error> {
+    query_bridges(session, None)
+}
+
+/// lists the bridges with the given destination as source
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn get_bridges_for(session: &Session, source: &Destination) -> Result<Vec<BridgeInfo>, Error> {
+    let mut bridges = query_bridges(session, Some(source))?;
+    // the server may ignore the filter, so apply it again
+    bridges.retain(|bridge| bridge.source == *source);
+    Ok(bridges)
+}
+
+fn query_bridges(
+    session: &Session,
+    source: Option<&Destination>,
+) -> Result<Vec<BridgeInfo>, Error> {
+    let mut msg: MapMessage = Default::default();
+    if let Some(source) = source {
+        destination_fields(&mut msg.body, "st", "sn", source);
+    }
+    //header
+    let mut header: HashMap<String, TypedValue> = HashMap::new();
     header.insert(
         "code".to_string(),
-        TypedValue::Integer(AdminCommands::GetStateInfo as i32),
+        TypedValue::Integer(AdminCommands::ListBridges as i32),
+    );
+    header.insert("save".to_string(), TypedValue::Boolean(true));
+    header.insert("arseq".to_string(), TypedValue::Integer(1));
+    msg.header = Some(header);
+
+    let reply = match execute(session, msg) {
+        Ok(reply) => reply,
+        Err(err) => {
+            error!("something went wrong retrieving bridges: {}", err);
+            return Err(err);
+        }
+    };
+    let mut bridges = Vec::new();
+    for (key, info) in positional_entries(&reply, "bridge") {
+        bridges.push(decode_bridge(key, info)?);
+    }
+    Ok(bridges)
+}
+
+/// decodes a bridge of a list reply
+fn decode_bridge(key: &str, info: &MapMessage) -> Result<BridgeInfo, DecodeError> {
+    let mut decoder = Decoder::new(key, info);
+    Ok(BridgeInfo {
+        source: decoder.destination("st", "sn")?,
+        target: decoder.destination("tt", "tn")?,
+        selector: decoder.string("sel")?.filter(|sel| !sel.is_empty()),
+    })
+}
+
+/// writes the type and name of a destination to an admin message body
+fn destination_fields(
+    body: &mut HashMap<String, TypedValue>,
+    type_field: &str,
+    name_field: &str,
+    destination: &Destination,
+) {
+    let (destination_type, name) = match destination {
+        Destination::Queue(name) => (DESTINATION_TYPE_QUEUE, name),
+        Destination::Topic(name) => (DESTINATION_TYPE_TOPIC, name),
+    };
+    body.insert(
+        type_field.to_string(),
+        TypedValue::Integer(destination_type),
+    );
+    body.insert(name_field.to_string(), TypedValue::String(name.clone()));
+}
+
+//
+// Users and groups
+//
+
+/// holds user information
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct UserInfo {
+    /// name of the user
+    pub name: String,
+    /// description of the user
+    pub description: Option<String>,
+}
+
+/// holds group information
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct GroupInfo {
+    /// name of the group
+    pub name: String,
+    /// description of the group
+    pub description: Option<String>,
+    /// names of the users in the group
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub members: Vec<String>,
+}
+
+/// admin message for a command without properties
+fn command(code: AdminCommands) -> MapMessage {
+    let mut msg: MapMessage = Default::default();
+    let mut header: HashMap<String, TypedValue> = HashMap::new();
+    //actual boolean
+    header.insert("JMS_TIBCO_MSG_EXT".to_string(), TypedValue::Boolean(true));
+    header.insert("code".to_string(), TypedValue::Integer(code as i32));
+    header.insert("save".to_string(), TypedValue::Boolean(true));
+    header.insert("arseq".to_string(), TypedValue::Integer(1));
+    msg.header = Some(header);
+    msg
+}
+
+/// entries of a list reply in name order
+fn named_entries<'a>(reply: &'a MapMessage, what: &str) -> Vec<(&'a String, &'a MapMessage)> {
+    let mut entries: Vec<(&String, &MapMessage)> = Vec::new();
+    for (name, val) in &reply.body {
+        match val {
+            TypedValue::Map(info) => entries.push((name, info)),
+            _ => warn!("unknown entry {} in {} list", name, what),
+        }
+    }
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// entries of a list reply which are keyed by their position in the list
+fn positional_entries<'a>(reply: &'a MapMessage, what: &str) -> Vec<(&'a String, &'a MapMessage)> {
+    let mut entries = named_entries(reply, what);
+    entries.sort_by_key(|(key, _)| key.parse::<usize>().unwrap_or(usize::MAX));
+    entries
+}
+
+/// lists all users present on the EMS
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_users(session: &Session) -> Result<Vec<UserInfo>, Error> {
+    let reply = match execute(session, command(AdminCommands::ListUsers)) {
+        Ok(reply) => reply,
+        Err(err) => {
+            error!("something went wrong retrieving users: {}", err);
+            return Err(err);
+        }
+    };
+    let mut users = Vec::new();
+    for (name, info) in named_entries(&reply, "user") {
+        let mut decoder = Decoder::new(name, info);
+        users.push(UserInfo {
+            name: name.to_string(),
+            description: decoder.string("desc")?,
+        });
+    }
+    Ok(users)
+}
+
+/// creates a user on the EMS, without a password the user can not log in
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn create_user(
+    session: &Session,
+    user: &UserInfo,
+    password: Option<&str>,
+) -> Result<(), Error> {
+    trace!("creating user {}", user.name);
+    let mut msg = command(AdminCommands::CreateUser);
+    msg.body
+        .insert("un".to_string(), TypedValue::String(user.name.clone()));
+    if let Some(description) = &user.description {
+        msg.body
+            .insert("desc".to_string(), TypedValue::String(description.clone()));
+    }
+    if let Some(password) = password {
+        msg.body
+            .insert("pw".to_string(), TypedValue::String(password.to_string()));
+    }
+    if let Err(err) = execute(session, msg) {
+        error!("error while creating user {}: {}", user.name, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// deletes a user from the EMS, the user is removed from all groups
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn delete_user(session: &Session, user: &str) -> Result<(), Error> {
+    trace!("deleting user {}", user);
+    let mut msg = command(AdminCommands::DeleteUser);
+    msg.body
+        .insert("un".to_string(), TypedValue::String(user.to_string()));
+    if let Err(err) = execute(session, msg) {
+        error!("error while deleting user {}: {}", user, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// sets the password of a user, None removes the password
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn set_password(session: &Session, user: &str, password: Option<&str>) -> Result<(), Error> {
+    trace!("setting password of user {}", user);
+    let mut msg = command(AdminCommands::SetPassword);
+    msg.body
+        .insert("un".to_string(), TypedValue::String(user.to_string()));
+    msg.body.insert(
+        "pw".to_string(),
+        TypedValue::String(password.unwrap_or_default().to_string()),
     );
-    header.insert("save".to_string(), TypedValue::Boolean(true));
-    header.insert("arseq".to_string(), TypedValue::Integer(1));
-    msg.header = Some(header);
+    if let Err(err) = execute(session, msg) {
+        error!("error while setting password of user {}: {}", user, err);
+        return Err(err);
+    }
+    Ok(())
+}
 
-    let admin_queue = Destination::Queue(ADMIN_QUEUE_NAME.to_string());
-    let query_result = session.request_reply(&admin_queue, msg, TIMEOUT);
-    match query_result {
-        Ok(response) => {
-            if let Some(resp) = response {
-                match &resp {
-                    Message::MapMessage(map_message) => {
-                        //got response message
-                        let state_str = extract!(
-                            TypedValue::String(_),
-                            map_message.body.get("state").unwrap()
-                        )
-                        .expect("extract server status");
-                        if state_str == "3" {
-                            return Ok(ServerState::Standby);
-                        } else {
-                            return Ok(ServerState::Active);
-                        }
-                    }
-                    _ => {
-                        warn!("unkown response from queue information request")
-                    }
-                }
+/// lists all groups present on the EMS
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_groups(session: &Session) -> Result<Vec<GroupInfo>, Error> {
+    let reply = match execute(session, command(AdminCommands::ListGroups)) {
+        Ok(reply) => reply,
+        Err(err) => {
+            error!("something went wrong retrieving groups: {}", err);
+            return Err(err);
+        }
+    };
+    let mut groups = Vec::new();
+    for (name, info) in named_entries(&reply, "group") {
+        let mut decoder = Decoder::new(name, info);
+        groups.push(GroupInfo {
+            name: name.to_string(),
+            description: decoder.string("desc")?,
+            members: decoder
+                .string("mbrs")?
+                .map(|val| name_list(&val))
+                .unwrap_or_default(),
+        });
+    }
+    Ok(groups)
+}
+
+/// creates a group on the EMS together with its members
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn create_group(session: &Session, group: &GroupInfo) -> Result<(), Error> {
+    trace!("creating group {}", group.name);
+    let mut msg = command(AdminCommands::CreateGroup);
+    msg.body
+        .insert("gn".to_string(), TypedValue::String(group.name.clone()));
+    if let Some(description) = &group.description {
+        msg.body
+            .insert("desc".to_string(), TypedValue::String(description.clone()));
+    }
+    if !group.members.is_empty() {
+        msg.body.insert(
+            "mbrs".to_string(),
+            TypedValue::String(group.members.join(",")),
+        );
+    }
+    if let Err(err) = execute(session, msg) {
+        error!("error while creating group {}: {}", group.name, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// deletes a group from the EMS, its members are kept
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn delete_group(session: &Session, group: &str) -> Result<(), Error> {
+    trace!("deleting group {}", group);
+    let mut msg = command(AdminCommands::DeleteGroup);
+    msg.body
+        .insert("gn".to_string(), TypedValue::String(group.to_string()));
+    if let Err(err) = execute(session, msg) {
+        error!("error while deleting group {}: {}", group, err);
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// adds a user to a group
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn add_user_to_group(session: &Session, user: &str, group: &str) -> Result<(), Error> {
+    membership(session, AdminCommands::AddUserToGroup, user, group)
+}
+
+/// removes a user from a group
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn remove_user_from_group(session: &Session, user: &str, group: &str) -> Result<(), Error> {
+    membership(session, AdminCommands::RemoveUserFromGroup, user, group)
+}
+
+fn membership(
+    session: &Session,
+    code: AdminCommands,
+    user: &str,
+    group: &str,
+) -> Result<(), Error> {
+    trace!("changing membership of user {} in group {}", user, group);
+    let mut msg = command(code);
+    msg.body
+        .insert("un".to_string(), TypedValue::String(user.to_string()));
+    msg.body
+        .insert("gn".to_string(), TypedValue::String(group.to_string()));
+    if let Err(err) = execute(session, msg) {
+        error!(
+            "error while changing membership of user {} in group {}: {}",
+            user, group, err
+        );
+        return Err(err);
+    }
+    Ok(())
+}
+
+//
+// Permissions
+//
+
+/// permission on a destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Permission {
+    /// receive messages from a queue
+    Receive,
+    /// send messages to a queue
+    Send,
+    /// browse the messages of a queue
+    Browse,
+    /// publish messages to a topic
+    Publish,
+    /// subscribe to a topic
+    Subscribe,
+    /// create durable subscriptions on a topic
+    Durable,
+    /// use existing durable subscriptions on a topic
+    UseDurable,
+    /// admin permission to create the destination
+    Create,
+    /// admin permission to delete the destination
+    Delete,
+    /// admin permission to change the properties of the destination
+    Modify,
+    /// admin permission to purge the destination
+    Purge,
+}
+
+impl Permission {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Permission::Receive => "receive",
+            Permission::Send => "send",
+            Permission::Browse => "browse",
+            Permission::Publish => "publish",
+            Permission::Subscribe => "subscribe",
+            Permission::Durable => "durable",
+            Permission::UseDurable => "use_durable",
+            Permission::Create => "create",
+            Permission::Delete => "delete",
+            Permission::Modify => "modify",
+            Permission::Purge => "purge",
+        }
+    }
+
+    fn parse(val: &str) -> Option<Permission> {
+        match val {
+            "receive" => Some(Permission::Receive),
+            "send" => Some(Permission::Send),
+            "browse" => Some(Permission::Browse),
+            "publish" => Some(Permission::Publish),
+            "subscribe" => Some(Permission::Subscribe),
+            "durable" => Some(Permission::Durable),
+            "use_durable" => Some(Permission::UseDurable),
+            "create" => Some(Permission::Create),
+            "delete" => Some(Permission::Delete),
+            "modify" => Some(Permission::Modify),
+            "purge" => Some(Permission::Purge),
+            _ => None,
+        }
+    }
+}
+
+/// user or group permissions are granted to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub enum Principal {
+    /// a single user
+    User(String),
+    /// all members of a group
+    Group(String),
+}
+
+/// permissions of a principal on a destination
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AclEntry {
+    /// destination, may contain wildcards
+    pub destination: Destination,
+    /// user or group
+    pub principal: Principal,
+    /// granted permissions
+    pub permissions: Vec<Permission>,
+}
+
+/// selects the entries listed by [`list_acls`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AclFilter {
+    /// entries of a destination
+    Destination(Destination),
+    /// entries of a user or group
+    Principal(Principal),
+    /// every entry of the server
+    All,
+}
+
+impl From<Destination> for AclFilter {
+    fn from(destination: Destination) -> Self {
+        AclFilter::Destination(destination)
+    }
+}
+
+impl From<Principal> for AclFilter {
+    fn from(principal: Principal) -> Self {
+        AclFilter::Principal(principal)
+    }
+}
+
+/// grants permissions on a destination to a user or group
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn grant(
+    session: &Session,
+    destination: &Destination,
+    principal: &Principal,
+    permissions: &[Permission],
+) -> Result<(), Error> {
+    change_permissions(
+        session,
+        AdminCommands::GrantPermissions,
+        destination,
+        principal,
+        permissions,
+    )
+}
+
+/// revokes permissions on a destination from a user or group
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// waits for the server to confirm the command, a rejected command fails with an [`AdminError`].
+pub fn revoke(
+    session: &Session,
+    destination: &Destination,
+    principal: &Principal,
+    permissions: &[Permission],
+) -> Result<(), Error> {
+    change_permissions(
+        session,
+        AdminCommands::RevokePermissions,
+        destination,
+        principal,
+        permissions,
+    )
+}
+
+fn change_permissions(
+    session: &Session,
+    code: AdminCommands,
+    destination: &Destination,
+    principal: &Principal,
+    permissions: &[Permission],
+) -> Result<(), Error> {
+    trace!(
+        "changing permissions of {:?} on {:?}",
+        principal,
+        destination
+    );
+    let mut msg = command(code);
+    destination_fields(&mut msg.body, "dt", "dn", destination);
+    principal_fields(&mut msg.body, principal);
+    let names: Vec<&str> = permissions.iter().map(Permission::as_str).collect();
+    msg.body
+        .insert("perm".to_string(), TypedValue::String(names.join(",")));
+    if let Err(err) = execute(session, msg) {
+        error!(
+            "error while changing permissions of {:?} on {:?}: {}",
+            principal, destination, err
+        );
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// lists the permissions of a destination or of a user or group
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn list_acls(session: &Session, filter: impl Into<AclFilter>) -> Result<Vec<AclEntry>, Error> {
+    let mut msg = command(AdminCommands::ListAcls);
+    match filter.into() {
+        AclFilter::Destination(destination) => {
+            destination_fields(&mut msg.body, "dt", "dn", &destination)
+        }
+        AclFilter::Principal(principal) => principal_fields(&mut msg.body, &principal),
+        AclFilter::All => {}
+    }
+    let reply = match execute(session, msg) {
+        Ok(reply) => reply,
+        Err(err) => {
+            error!("something went wrong retrieving permissions: {}", err);
+            return Err(err);
+        }
+    };
+    let mut acls = Vec::new();
+    for (key, info) in positional_entries(&reply, "permission") {
+        acls.push(decode_acl(key, info)?);
+    }
+    Ok(acls)
+}
+
+/// decodes a permission entry of a list reply
+fn decode_acl(key: &str, info: &MapMessage) -> Result<AclEntry, DecodeError> {
+    let mut decoder = Decoder::new(key, info);
+    let destination = decoder.destination("dt", "dn")?;
+    let principal = match (decoder.string("un")?, decoder.string("gn")?) {
+        (Some(user), _) => Principal::User(user),
+        (None, Some(group)) => Principal::Group(group),
+        (None, None) => return Err(decoder.error("un", "missing user or group".to_string())),
+    };
+    let mut permissions = Vec::new();
+    for name in name_list(&decoder.string("perm")?.unwrap_or_default()) {
+        match Permission::parse(&name) {
+            Some(permission) => permissions.push(permission),
+            // newer servers may know more permissions
+            None => warn!("unknown permission {} in permission list", name),
+        }
+    }
+    permissions.sort();
+    permissions.dedup();
+    Ok(AclEntry {
+        destination,
+        principal,
+        permissions,
+    })
+}
+
+/// writes the user or group name to an admin message body
+fn principal_fields(body: &mut HashMap<String, TypedValue>, principal: &Principal) {
+    match principal {
+        Principal::User(name) => body.insert("un".to_string(), TypedValue::String(name.clone())),
+        Principal::Group(name) => body.insert("gn".to_string(), TypedValue::String(name.clone())),
+    };
+}
+
+//
+// Server
+//
+
+/// get server state
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// fails with an [`AdminError`] of kind [`AdminErrorKind::NoReply`] if the server does not reply.
+pub fn get_server_state(session: &Session) -> Result<ServerState, Error> {
+    let reply = match execute(session, command(AdminCommands::GetStateInfo)) {
+        Ok(reply) => reply,
+        Err(err) => {
+            error!("something went wrong retrieving the server state: {}", err);
+            return Err(err);
+        }
+    };
+    let mut decoder = Decoder::new("server", &reply);
+    match decoder.number::<i32>("state")? {
+        Some(code) => Ok(ServerState::from_code(code)),
+        None => Err(decoder
+            .error("state", "missing server state".to_string())
+            .into()),
+    }
+}
+
+/// fault tolerance state of a member of a server pair
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct FtMember {
+    /// url of the server
+    pub url: String,
+    /// reported state, None if the server could not be queried
+    pub state: Option<ServerState>,
+    /// error querying the server
+    pub error: Option<String>,
+}
+
+/// health of a fault tolerant server pair
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FtPairHealth {
+    /// one server is active, the other one standby
+    Healthy,
+    /// one server is active, the other one is not standby or could not be queried
+    Degraded,
+    /// both servers are active
+    SplitBrain,
+    /// both servers are standby, no server accepts clients
+    BothStandby,
+    /// no server is active
+    NoActive,
+}
+
+impl FtPairHealth {
+    /// health of a pair with the given member states
+    pub fn of(a: Option<&ServerState>, b: Option<&ServerState>) -> FtPairHealth {
+        use ServerState::{Active, Standby};
+        match (a, b) {
+            (Some(Active), Some(Active)) => FtPairHealth::SplitBrain,
+            (Some(Active), Some(Standby)) | (Some(Standby), Some(Active)) => FtPairHealth::Healthy,
+            (Some(Active), _) | (_, Some(Active)) => FtPairHealth::Degraded,
+            (Some(Standby), Some(Standby)) => FtPairHealth::BothStandby,
+            _ => FtPairHealth::NoActive,
+        }
+    }
+}
+
+/// state of a fault tolerant server pair
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct FtPairStatus {
+    /// members of the pair in the order of the given urls
+    pub members: [FtMember; 2],
+    /// health of the pair
+    pub health: FtPairHealth,
+}
+
+impl FtPairStatus {
+    /// the active member, None unless exactly one member is active
+    pub fn active(&self) -> Option<&FtMember> {
+        let mut active = self
+            .members
+            .iter()
+            .filter(|member| member.state == Some(ServerState::Active));
+        match (active.next(), active.next()) {
+            (Some(member), None) => Some(member),
+            _ => None,
+        }
+    }
+}
+
+/// queries the state of both members of a fault tolerant server pair
+///
+/// each url is queried directly. a single member which can not be queried is
+/// reported with its error and without a state, so the pair is `Degraded` or
+/// `NoActive`. if neither member can be queried the check fails with the error of
+/// the first url.
+pub fn check_ft_pair(
+    url_a: &str,
+    url_b: &str,
+    user: &str,
+    password: &str,
+) -> Result<FtPairStatus, Error> {
+    #[cfg(feature = "ems-sys")]
+    let backend = |_: &str| super::native::NativeBackend;
+    #[cfg(not(feature = "ems-sys"))]
+    let backend = super::mock::MockBroker::current;
+    check_ft_pair_with_backend(url_a, url_b, user, password, backend)
+}
+
+/// queries the state of both members of a fault tolerant server pair through the
+/// backend returned for each url, see [`check_ft_pair`]
+pub fn check_ft_pair_with_backend<B: Backend + 'static>(
+    url_a: &str,
+    url_b: &str,
+    user: &str,
+    password: &str,
+    backend: impl Fn(&str) -> B,
+) -> Result<FtPairStatus, Error> {
+    let query = |url: &str| {
+        let admin_url = format!("<$admin>:{url}");
+        super::connect_with_backend(backend(url), &admin_url, user, password)
+            .and_then(|conn| get_server_state(&conn.session()?))
+    };
+    let (state_a, state_b) = match (query(url_a), query(url_b)) {
+        (Err(err_a), Err(err_b)) => {
+            error!("could not query server state of {url_a}: {err_a}, nor of {url_b}: {err_b}");
+            return Err(Error::new(
+                err_a.kind(),
+                format!(
+                    "fault tolerant pair can not be queried, {url_a}: {err_a}, {url_b}: {err_b}"
+                ),
+            ));
+        }
+        states => states,
+    };
+    let member = |url: &str, state: Result<ServerState, Error>| match state {
+        Ok(state) => FtMember {
+            url: url.to_string(),
+            state: Some(state),
+            error: None,
+        },
+        Err(err) => {
+            warn!("could not query server state of {}: {}", url, err);
+            FtMember {
+                url: url.to_string(),
+                state: None,
+                error: Some(err.to_string()),
             }
         }
+    };
+    let members = [member(url_a, state_a), member(url_b, state_b)];
+    let health = FtPairHealth::of(members[0].state.as_ref(), members[1].state.as_ref());
+    Ok(FtPairStatus { members, health })
+}
+
+/// holds server information and statistics
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ServerInfo {
+    /// name of the server
+    pub server_name: Option<String>,
+    /// version of the server, e.g. `10.2.1`
+    pub version: Option<String>,
+    /// fault tolerance state of the server
+    pub state: Option<ServerState>,
+    /// time since the server was started
+    pub uptime: Option<Duration>,
+    /// number of client connections
+    pub connection_count: Option<i64>,
+    /// maximum number of client connections, 0 if unlimited
+    pub max_connections: Option<i64>,
+    /// number of sessions
+    pub session_count: Option<i64>,
+    /// number of pending messages
+    pub pending_message_count: Option<i64>,
+    /// memory used by pending messages in bytes
+    pub message_memory: Option<i64>,
+    /// maximum memory for pending messages in bytes, 0 if unlimited
+    pub max_message_memory: Option<i64>,
+    /// size of the synchronous store in bytes
+    pub sync_store_size: Option<i64>,
+    /// size of the asynchronous store in bytes
+    pub async_store_size: Option<i64>,
+    /// inbound messages per second
+    pub inbound_message_rate: Option<f64>,
+    /// inbound bytes per second
+    pub inbound_byte_rate: Option<f64>,
+    /// outbound messages per second
+    pub outbound_message_rate: Option<f64>,
+    /// outbound bytes per second
+    pub outbound_byte_rate: Option<f64>,
+    /// fields reported by the server which are not modelled
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "HashMap::is_empty")
+    )]
+    pub extra: HashMap<String, TypedValue>,
+}
+
+impl ServerInfo {
+    /// whether the server reports at least the given version
+    ///
+    /// missing version elements count as 0, a server without a version never matches.
+    pub fn version_at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        let Some(version) = &self.version else {
+            return false;
+        };
+        let mut elements = version.split(['.', ' ', '-']).map(|element| {
+            element
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect::<String>()
+                .parse::<u32>()
+                .unwrap_or(0)
+        });
+        let reported = [
+            elements.next().unwrap_or(0),
+            elements.next().unwrap_or(0),
+            elements.next().unwrap_or(0),
+        ];
+        reported >= [major, minor, patch]
+    }
+}
+
+/// get server information and statistics
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn get_server_info(session: &Session) -> Result<ServerInfo, Error> {
+    let reply = match execute(session, command(AdminCommands::GetServerInfo)) {
+        Ok(reply) => reply,
         Err(err) => {
             error!(
-                "something went wronge retrieving queue information: {}",
+                "something went wrong retrieving server information: {}",
                 err
             );
             return Err(err);
         }
+    };
+    Ok(decode_server_info(&reply)?)
+}
+
+/// decodes the reply of a server info request
+fn decode_server_info(reply: &MapMessage) -> Result<ServerInfo, DecodeError> {
+    let mut decoder = Decoder::new("server", reply);
+    Ok(ServerInfo {
+        server_name: decoder.string("sn")?,
+        version: decoder.string("ver")?,
+        state: decoder.number::<i32>("state")?.map(ServerState::from_code),
+        uptime: decoder.number("up")?.map(Duration::from_millis),
+        connection_count: decoder.number("cc")?,
+        max_connections: decoder.number("mc")?,
+        session_count: decoder.number("sc")?,
+        pending_message_count: decoder.number("nm")?,
+        message_memory: decoder.number("mmu")?,
+        max_message_memory: decoder.number("mmm")?,
+        sync_store_size: decoder.number("syss")?,
+        async_store_size: decoder.number("asys")?,
+        inbound_message_rate: decoder.number("imr")?,
+        inbound_byte_rate: decoder.number("ibr")?,
+        outbound_message_rate: decoder.number("omr")?,
+        outbound_byte_rate: decoder.number("obr")?,
+        extra: decoder.extra(),
+    })
+}
+
+//
+// Configuration
+//
+
+/// configuration of a server, as collected by [`export_config`]
+///
+/// statistics like pending messages or consumer counts are not part of the configuration,
+/// passwords are never reported by the server and so are not exported either.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct ServerConfig {
+    /// name of the exported server
+    pub server_name: Option<String>,
+    /// version of the exported server
+    pub version: Option<String>,
+    /// queues, without the temporary and system queues starting with `$`
+    pub queues: Vec<QueueInfo>,
+    /// topics, without the temporary and system topics starting with `$`
+    pub topics: Vec<TopicInfo>,
+    /// bridges between destinations
+    pub bridges: Vec<BridgeInfo>,
+    /// durable subscriptions
+    pub durables: Vec<DurableInfo>,
+    /// users
+    pub users: Vec<UserInfo>,
+    /// groups with their members
+    pub groups: Vec<GroupInfo>,
+    /// permissions on all destinations, including the system destinations
+    pub acls: Vec<AclEntry>,
+    /// routes to other servers
+    pub routes: Vec<RouteInfo>,
+    /// connection factories
+    pub factories: Vec<FactoryInfo>,
+}
+
+/// collects the configuration of a server into a single document
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn export_config(session: &Session) -> Result<ServerConfig, Error> {
+    let server = get_server_info(session)?;
+    let all_queues = list_all_queues(session)?;
+    let all_topics = list_all_topics(session)?;
+    // listed without filter to include principals which only exist in an external directory
+    let mut seen = HashSet::new();
+    let acls = list_acls(session, AclFilter::All)?
+        .into_iter()
+        .filter(|acl| seen.insert(acl.clone()))
+        .collect();
+    let queues = all_queues
+        .into_iter()
+        .filter(|queue| !queue.name.starts_with('$'))
+        .map(|queue| QueueInfo {
+            pending_messages: None,
+            consumer_count: None,
+            incoming_total_count: None,
+            outgoing_total_count: None,
+            ..queue
+        })
+        .collect();
+    let topics = all_topics
+        .into_iter()
+        .filter(|topic| !topic.name.starts_with('$'))
+        .map(|topic| TopicInfo {
+            durable_count: None,
+            subscriber_count: None,
+            pending_messages: None,
+            incoming_total_count: None,
+            outgoing_total_count: None,
+            ..topic
+        })
+        .collect();
+    let durables = list_durables(session, ">")?
+        .into_iter()
+        .filter(|durable| !durable.topic.starts_with('$'))
+        .map(|durable| DurableInfo {
+            pending_messages: None,
+            connected: None,
+            consumer_user: None,
+            ..durable
+        })
+        .collect();
+    let users = list_users(session)?;
+    let groups = list_groups(session)?;
+    let routes = list_routes(session)?
+        .into_iter()
+        .map(|route| RouteInfo {
+            state: None,
+            ..route
+        })
+        .collect();
+    Ok(ServerConfig {
+        server_name: server.server_name,
+        version: server.version,
+        queues,
+        topics,
+        bridges: list_bridges(session)?,
+        durables,
+        users,
+        groups,
+        acls,
+        routes,
+        factories: list_factories(session)?,
+    })
+}
+
+/// recreates an exported configuration on an empty server
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// existing objects are looked up in the listings of the server before anything is created,
+/// so the objects every server starts with and the objects of an import which failed part
+/// way do not stop the import: existing destinations, like the default `>` queue and topic,
+/// get the exported properties, existing groups get the missing members and all other
+/// existing objects are kept as they are. Users are created without password. Stops at the
+/// first rejected command and returns its [`AdminError`].
+pub fn import_config(session: &Session, config: &ServerConfig) -> Result<(), Error> {
+    if !config.users.is_empty() {
+        let existing: HashSet<String> = list_users(session)?
+            .into_iter()
+            .map(|user| user.name)
+            .collect();
+        for user in &config.users {
+            if !existing.contains(&user.name) {
+                create_user(session, user, None)?;
+            }
+        }
+    }
+    if !config.groups.is_empty() {
+        let existing: HashMap<String, Vec<String>> = list_groups(session)?
+            .into_iter()
+            .map(|group| (group.name, group.members))
+            .collect();
+        for group in &config.groups {
+            match existing.get(&group.name) {
+                None => create_group(session, group)?,
+                Some(members) => {
+                    for member in group.members.iter().filter(|m| !members.contains(m)) {
+                        add_user_to_group(session, member, &group.name)?;
+                    }
+                }
+            }
+        }
+    }
+    if !config.queues.is_empty() {
+        let existing: HashSet<String> = list_all_queues(session)?
+            .into_iter()
+            .map(|queue| queue.name)
+            .collect();
+        for queue in &config.queues {
+            match existing.contains(&queue.name) {
+                true => update_queue(session, queue)?,
+                false => create_queue(session, queue)?,
+            }
+        }
+    }
+    if !config.topics.is_empty() {
+        let existing: HashSet<String> = list_all_topics(session)?
+            .into_iter()
+            .map(|topic| topic.name)
+            .collect();
+        for topic in &config.topics {
+            match existing.contains(&topic.name) {
+                true => update_topic(session, topic)?,
+                false => create_topic(session, topic)?,
+            }
+        }
+    }
+    if !config.bridges.is_empty() {
+        let existing = list_bridges(session)?;
+        for bridge in &config.bridges {
+            if !existing.contains(bridge) {
+                create_bridge(session, bridge)?;
+            }
+        }
+    }
+    if !config.durables.is_empty() {
+        let existing: HashSet<(String, String)> = list_durables(session, ">")?
+            .into_iter()
+            .map(|durable| (durable.topic, durable.name))
+            .collect();
+        for durable in &config.durables {
+            if !existing.contains(&(durable.topic.clone(), durable.name.clone())) {
+                create_durable(session, durable)?;
+            }
+        }
+    }
+    for acl in &config.acls {
+        grant(session, &acl.destination, &acl.principal, &acl.permissions)?;
+    }
+    if !config.routes.is_empty() {
+        let existing: HashSet<String> = list_routes(session)?
+            .into_iter()
+            .map(|route| route.name)
+            .collect();
+        for route in &config.routes {
+            if !existing.contains(&route.name) {
+                create_route(session, route)?;
+            }
+        }
+    }
+    if !config.factories.is_empty() {
+        let existing: HashSet<String> = list_factories(session)?
+            .into_iter()
+            .map(|factory| factory.name)
+            .collect();
+        for factory in &config.factories {
+            if !existing.contains(&factory.name) {
+                create_factory(session, factory)?;
+            }
+        }
     }
-    Ok(ServerState::Active)
+    Ok(())
 }
 
 /// holds static bridge information
@@ -764,15 +3392,104 @@ pub enum OverflowPolicy {
     RejectIncoming = 2,
 }
 
-/// admin command codes used on the admin queue
+/// message tracing of a destination
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TraceMode {
+    /// trace message headers
+    Default,
+    /// trace message headers and body
+    Body,
+}
+
+impl TraceMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TraceMode::Default => "default",
+            TraceMode::Body => "body",
+        }
+    }
+
+    fn parse(val: &str) -> Option<TraceMode> {
+        match val {
+            "default" | "1" => Some(TraceMode::Default),
+            "body" => Some(TraceMode::Body),
+            _ => None,
+        }
+    }
+}
+
+/// admin command codes used on the admin queue
+///
+/// only the [verified](AdminCommands::is_verified) commands are sent to Tibco EMS
+/// servers, the others are implemented by the admin queue emulated in
+/// [`crate::mock`] only.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AdminCommands {
     /// delete a destination
     DeleteDestination = 16,
+    /// alter the properties of a destination
+    AlterDestination = 17,
     /// create a destination
     CreateDestination = 18,
     /// list destinations
     ListDestination = 19,
+    /// purge the messages of a destination
+    PurgeDestination = 21,
+    /// purge the messages of a durable subscription
+    PurgeDurable = 22,
+    /// purge the messages of all queues matching a pattern
+    PurgeAllQueues = 23,
+    /// list durable subscriptions
+    ListDurables = 25,
+    /// create a durable subscription
+    CreateDurable = 26,
+    /// delete a durable subscription
+    DeleteDurable = 27,
+    /// create a user
+    CreateUser = 30,
+    /// delete a user
+    DeleteUser = 31,
+    /// set the password of a user
+    SetPassword = 32,
+    /// list users
+    ListUsers = 33,
+    /// create a group
+    CreateGroup = 34,
+    /// delete a group
+    DeleteGroup = 35,
+    /// list groups
+    ListGroups = 36,
+    /// add a user to a group
+    AddUserToGroup = 37,
+    /// remove a user from a group
+    RemoveUserFromGroup = 38,
+    /// grant permissions on a destination
+    GrantPermissions = 40,
+    /// revoke permissions on a destination
+    RevokePermissions = 41,
+    /// list permissions
+    ListAcls = 42,
+    /// list client connections
+    ListConnections = 50,
+    /// destroy a client connection
+    DestroyConnection = 51,
+    /// list the consumers of a destination
+    ListConsumers = 52,
+    /// list the producers of a destination
+    ListProducers = 53,
+    /// list routes
+    ListRoutes = 60,
+    /// create a route
+    CreateRoute = 61,
+    /// delete a route
+    DeleteRoute = 62,
+    /// list connection factories
+    ListFactories = 70,
+    /// create a connection factory
+    CreateFactory = 71,
+    /// delete a connection factory
+    DeleteFactory = 72,
     /// get server info
     GetServerInfo = 120,
     /// get state info
@@ -781,13 +3498,140 @@ pub enum AdminCommands {
     CreateBridge = 220,
     /// delete a bridge
     DeleteBridge = 221,
+    /// list bridges
+    ListBridges = 222,
 }
 
-/// server states
-#[derive(Debug, Clone, PartialEq)]
+impl AdminCommands {
+    /// command of an admin request
+    fn of(msg: &MapMessage) -> Option<AdminCommands> {
+        use AdminCommands::*;
+        let code = match msg.header.as_ref()?.get("code")? {
+            TypedValue::Integer(code) => *code,
+            _ => return None,
+        };
+        [
+            DeleteDestination,
+            AlterDestination,
+            CreateDestination,
+            ListDestination,
+            PurgeDestination,
+            PurgeDurable,
+            PurgeAllQueues,
+            ListDurables,
+            CreateDurable,
+            DeleteDurable,
+            CreateUser,
+            DeleteUser,
+            SetPassword,
+            ListUsers,
+            CreateGroup,
+            DeleteGroup,
+            ListGroups,
+            AddUserToGroup,
+            RemoveUserFromGroup,
+            GrantPermissions,
+            RevokePermissions,
+            ListAcls,
+            ListConnections,
+            DestroyConnection,
+            ListConsumers,
+            ListProducers,
+            ListRoutes,
+            CreateRoute,
+            DeleteRoute,
+            ListFactories,
+            CreateFactory,
+            DeleteFactory,
+            GetServerInfo,
+            GetStateInfo,
+            CreateBridge,
+            DeleteBridge,
+            ListBridges,
+        ]
+        .into_iter()
+        .find(|command| *command as i32 == code)
+    }
+
+    /// whether the command is known to be understood by Tibco EMS servers
+    ///
+    /// Tibco does not document the admin queue. The destination, bridge and server
+    /// info commands are those the admin functions of this crate used before the
+    /// mock broker existed, they are exercised by the `test_with_ems` tests. The
+    /// codes and body fields of all other commands are implemented by the emulated
+    /// admin queue only and have not been checked against a server.
+    pub fn is_verified(&self) -> bool {
+        use AdminCommands::*;
+        matches!(
+            self,
+            DeleteDestination
+                | CreateDestination
+                | ListDestination
+                | GetServerInfo
+                | GetStateInfo
+                | CreateBridge
+                | DeleteBridge
+        )
+    }
+
+    /// whether the command only queries the server, its reply is the result
+    pub fn is_query(&self) -> bool {
+        use AdminCommands::*;
+        matches!(
+            self,
+            ListDestination
+                | ListDurables
+                | ListUsers
+                | ListGroups
+                | ListAcls
+                | ListConnections
+                | ListConsumers
+                | ListProducers
+                | ListRoutes
+                | ListFactories
+                | GetServerInfo
+                | GetStateInfo
+                | ListBridges
+        )
+    }
+}
+
+/// fault tolerance states of a server
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ServerState {
+    /// server is activating after its peer failed
+    Activating,
     /// server is standby
-    Standby = 3,
+    Standby,
     /// server is active
-    Active = 4,
+    Active,
+    /// server is recovering its stores
+    Recovering,
+    /// state code not known to this crate
+    Unknown(i32),
+}
+
+impl ServerState {
+    /// state code used by the server
+    pub fn code(&self) -> i32 {
+        match self {
+            ServerState::Activating => 2,
+            ServerState::Standby => 3,
+            ServerState::Active => 4,
+            ServerState::Recovering => 5,
+            ServerState::Unknown(code) => *code,
+        }
+    }
+
+    /// state for a code reported by the server
+    pub fn from_code(code: i32) -> ServerState {
+        match code {
+            2 => ServerState::Activating,
+            3 => ServerState::Standby,
+            4 => ServerState::Active,
+            5 => ServerState::Recovering,
+            code => ServerState::Unknown(code),
+        }
+    }
 }
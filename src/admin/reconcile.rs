@@ -0,0 +1,662 @@
+//! Declarative reconciliation of the server configuration.
+//!
+//! A [`DesiredState`] lists the queues, topics, bridges and permissions a server should
+//! have. With the `serde` feature it can be loaded from any serde format, e.g. a JSON or
+//! YAML file kept in version control. [`plan`] compares it with the server and returns
+//! the [`Action`]s needed to get there, [`apply`] executes them and [`reconcile`] does
+//! both, unless [`ReconcileOptions::dry_run`] is set.
+//!
+//! Properties missing from the desired state are left as they are. Queues, topics,
+//! bridges and permissions missing from the desired state are only deleted with
+//! [`ReconcileOptions::prune`]. System destinations starting with `$` and the default
+//! destinations `>` are never deleted, their permissions are only revoked if the
+//! desired state names them.
+//!
+//! Bridges and permissions are only read from the server if the desired state lists
+//! any or pruning is requested, as their admin commands are not supported by every
+//! server.
+
+use super::{
+    create_bridge, create_queue, create_topic, delete_bridge, delete_queue, delete_topic, grant,
+    list_acls, list_all_queues, list_all_topics, list_bridges, patch_queue, patch_topic, revoke,
+    AclEntry, AclFilter, BridgeInfo, OverflowPolicy, Patch, Permission, Principal, QueueInfo,
+    QueueInfoPatch, TopicInfo, TopicInfoPatch, TraceMode,
+};
+use crate::{Destination, Session};
+use log::trace;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::io::{Error, ErrorKind};
+
+/// configuration a server should have
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct DesiredState {
+    /// queues with the properties to set
+    pub queues: Vec<QueueInfo>,
+    /// topics with the properties to set
+    pub topics: Vec<TopicInfo>,
+    /// bridges between destinations
+    pub bridges: Vec<BridgeInfo>,
+    /// permissions of users and groups on destinations
+    pub acls: Vec<AclEntry>,
+}
+
+/// controls how the desired state is reconciled
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReconcileOptions {
+    /// only plan the changes, the server is left untouched
+    pub dry_run: bool,
+    /// delete queues, topics, bridges and permissions missing from the desired state
+    pub prune: bool,
+}
+
+/// change of a single property of a destination
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// name of the property
+    pub property: &'static str,
+    /// value on the server, None if the property is not set
+    pub current: Option<String>,
+    /// value of the desired state
+    pub desired: String,
+}
+
+/// step of a plan
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// create a missing queue
+    CreateQueue(QueueInfo),
+    /// change properties of a queue
+    UpdateQueue {
+        /// properties to set
+        patch: QueueInfoPatch,
+        /// changed properties with their old and new values
+        changes: Vec<Change>,
+    },
+    /// delete a queue
+    DeleteQueue(String),
+    /// create a missing topic
+    CreateTopic(TopicInfo),
+    /// change properties of a topic
+    UpdateTopic {
+        /// properties to set
+        patch: TopicInfoPatch,
+        /// changed properties with their old and new values
+        changes: Vec<Change>,
+    },
+    /// delete a topic
+    DeleteTopic(String),
+    /// create a missing bridge
+    CreateBridge(BridgeInfo),
+    /// delete a bridge
+    DeleteBridge(BridgeInfo),
+    /// grant missing permissions
+    Grant(AclEntry),
+    /// revoke permissions
+    Revoke(AclEntry),
+}
+
+/// actions turning the server configuration into the desired state
+///
+/// creations and grants come first, so nothing is deleted before the new configuration is in place.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Plan {
+    /// actions in the order they are applied
+    pub actions: Vec<Action>,
+}
+
+impl Plan {
+    /// true if the server already has the desired state
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.actions.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        for action in &self.actions {
+            writeln!(f, "{action}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Action::CreateQueue(queue) => write!(f, "+ create queue {}", queue.name),
+            Action::UpdateQueue { patch, changes } => {
+                write!(f, "~ update queue {}", patch.name)?;
+                write_changes(f, changes)
+            }
+            Action::DeleteQueue(name) => write!(f, "- delete queue {name}"),
+            Action::CreateTopic(topic) => write!(f, "+ create topic {}", topic.name),
+            Action::UpdateTopic { patch, changes } => {
+                write!(f, "~ update topic {}", patch.name)?;
+                write_changes(f, changes)
+            }
+            Action::DeleteTopic(name) => write!(f, "- delete topic {name}"),
+            Action::CreateBridge(bridge) => {
+                write!(f, "+ create bridge ")?;
+                write_bridge(f, bridge)
+            }
+            Action::DeleteBridge(bridge) => {
+                write!(f, "- delete bridge ")?;
+                write_bridge(f, bridge)
+            }
+            Action::Grant(acl) => write!(
+                f,
+                "+ grant {} on {} to {}",
+                permission_names(&acl.permissions),
+                describe_destination(&acl.destination),
+                describe_principal(&acl.principal)
+            ),
+            Action::Revoke(acl) => write!(
+                f,
+                "- revoke {} on {} from {}",
+                permission_names(&acl.permissions),
+                describe_destination(&acl.destination),
+                describe_principal(&acl.principal)
+            ),
+        }
+    }
+}
+
+fn write_changes(f: &mut fmt::Formatter, changes: &[Change]) -> fmt::Result {
+    for (index, change) in changes.iter().enumerate() {
+        let separator = if index == 0 { ": " } else { ", " };
+        let current = change.current.as_deref().unwrap_or("unset");
+        write!(
+            f,
+            "{separator}{} {current} -> {}",
+            change.property, change.desired
+        )?;
+    }
+    Ok(())
+}
+
+fn write_bridge(f: &mut fmt::Formatter, bridge: &BridgeInfo) -> fmt::Result {
+    write!(
+        f,
+        "{} -> {}",
+        describe_destination(&bridge.source),
+        describe_destination(&bridge.target)
+    )?;
+    if let Some(selector) = &bridge.selector {
+        write!(f, " with selector {selector}")?;
+    }
+    Ok(())
+}
+
+fn describe_destination(destination: &Destination) -> String {
+    match destination {
+        Destination::Queue(name) => format!("queue {name}"),
+        Destination::Topic(name) => format!("topic {name}"),
+    }
+}
+
+/// property value as shown in a plan
+trait Describe {
+    fn describe(&self) -> String;
+}
+
+impl Describe for i32 {
+    fn describe(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Describe for i64 {
+    fn describe(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Describe for bool {
+    fn describe(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Describe for String {
+    fn describe(&self) -> String {
+        self.clone()
+    }
+}
+
+impl Describe for Vec<String> {
+    fn describe(&self) -> String {
+        match self.is_empty() {
+            true => "none".to_string(),
+            false => self.join(","),
+        }
+    }
+}
+
+impl Describe for OverflowPolicy {
+    fn describe(&self) -> String {
+        match self {
+            OverflowPolicy::Default => "default",
+            OverflowPolicy::DiscardOld => "discardOld",
+            OverflowPolicy::RejectIncoming => "rejectIncoming",
+        }
+        .to_string()
+    }
+}
+
+impl Describe for TraceMode {
+    fn describe(&self) -> String {
+        self.as_str().to_string()
+    }
+}
+
+fn describe_principal(principal: &Principal) -> String {
+    match principal {
+        Principal::User(name) => format!("user {name}"),
+        Principal::Group(name) => format!("group {name}"),
+    }
+}
+
+fn permission_names(permissions: &[Permission]) -> String {
+    let names: Vec<&str> = permissions.iter().map(Permission::as_str).collect();
+    names.join(", ")
+}
+
+fn destination_name(destination: &Destination) -> &str {
+    match destination {
+        Destination::Queue(name) | Destination::Topic(name) => name,
+    }
+}
+
+/// whether a destination is subject to pruning
+fn prunable(name: &str) -> bool {
+    !name.starts_with('$') && name != ">"
+}
+
+/// compares the desired state with the server and returns the actions needed to reach it
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// fails with `ErrorKind::InvalidInput` if the desired state lists a queue or topic twice.
+pub fn plan(
+    session: &Session,
+    desired: &DesiredState,
+    options: &ReconcileOptions,
+) -> Result<Plan, Error> {
+    check_unique(desired.queues.iter().map(|queue| &queue.name), "queue")?;
+    check_unique(desired.topics.iter().map(|topic| &topic.name), "topic")?;
+    let mut actions = Vec::new();
+    let mut deleted_destinations = Vec::new();
+    let mut deleted_bridges = Vec::new();
+
+    let queues = list_all_queues(session)?;
+    for queue in &desired.queues {
+        match queues.iter().find(|current| current.name == queue.name) {
+            None => actions.push(Action::CreateQueue(queue.clone())),
+            Some(current) => {
+                let (patch, changes) = queue_patch(queue, current);
+                if !changes.is_empty() {
+                    actions.push(Action::UpdateQueue { patch, changes });
+                }
+            }
+        }
+    }
+    if options.prune {
+        for queue in &queues {
+            if prunable(&queue.name) && !desired.queues.iter().any(|q| q.name == queue.name) {
+                deleted_destinations.push(Action::DeleteQueue(queue.name.clone()));
+            }
+        }
+    }
+
+    let topics = list_all_topics(session)?;
+    for topic in &desired.topics {
+        match topics.iter().find(|current| current.name == topic.name) {
+            None => actions.push(Action::CreateTopic(topic.clone())),
+            Some(current) => {
+                let (patch, changes) = topic_patch(topic, current);
+                if !changes.is_empty() {
+                    actions.push(Action::UpdateTopic { patch, changes });
+                }
+            }
+        }
+    }
+    if options.prune {
+        for topic in &topics {
+            if prunable(&topic.name) && !desired.topics.iter().any(|t| t.name == topic.name) {
+                deleted_destinations.push(Action::DeleteTopic(topic.name.clone()));
+            }
+        }
+    }
+
+    let bridges = match desired.bridges.is_empty() && !options.prune {
+        true => Vec::new(),
+        false => list_bridges(session)?,
+    };
+    for bridge in &desired.bridges {
+        if !bridges.contains(bridge) {
+            actions.push(Action::CreateBridge(bridge.clone()));
+        }
+    }
+    if options.prune {
+        for bridge in &bridges {
+            if !desired.bridges.contains(bridge) {
+                deleted_bridges.push(Action::DeleteBridge(bridge.clone()));
+            }
+        }
+    }
+
+    let (grants, revokes) = acl_actions(session, desired, options)?;
+    actions.extend(grants);
+    // permissions and bridges are removed before their destinations
+    actions.extend(revokes);
+    actions.extend(deleted_bridges);
+    actions.extend(deleted_destinations);
+    Ok(Plan { actions })
+}
+
+fn check_unique<'a>(names: impl Iterator<Item = &'a String>, kind: &str) -> Result<(), Error> {
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("{kind} {name} is listed more than once"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// patch setting a property if the desired value differs from the current one
+fn diff<T: Clone + PartialEq + Describe>(
+    property: &'static str,
+    desired: &Option<T>,
+    current: &Option<T>,
+    changes: &mut Vec<Change>,
+) -> Patch<T> {
+    match desired {
+        Some(val) if current.as_ref() != Some(val) => {
+            changes.push(Change {
+                property,
+                current: current.as_ref().map(Describe::describe),
+                desired: val.describe(),
+            });
+            Patch::Set(val.clone())
+        }
+        _ => Patch::Keep,
+    }
+}
+
+fn queue_patch(desired: &QueueInfo, current: &QueueInfo) -> (QueueInfoPatch, Vec<Change>) {
+    let mut changes = Vec::new();
+    let c = &mut changes;
+    let patch = QueueInfoPatch {
+        name: desired.name.clone(),
+        max_bytes: diff("max_bytes", &desired.max_bytes, &current.max_bytes, c),
+        max_messages: diff(
+            "max_messages",
+            &desired.max_messages,
+            &current.max_messages,
+            c,
+        ),
+        overflow_policy: diff(
+            "overflow_policy",
+            &desired.overflow_policy,
+            &current.overflow_policy,
+            c,
+        ),
+        failsafe: diff("failsafe", &desired.failsafe, &current.failsafe, c),
+        secure: diff("secure", &desired.secure, &current.secure, c),
+        global: diff("global", &desired.global, &current.global, c),
+        sender_name: diff("sender_name", &desired.sender_name, &current.sender_name, c),
+        sender_name_enforced: diff(
+            "sender_name_enforced",
+            &desired.sender_name_enforced,
+            &current.sender_name_enforced,
+            c,
+        ),
+        prefetch: diff("prefetch", &desired.prefetch, &current.prefetch, c),
+        expiry_override: diff(
+            "expiry_override",
+            &desired.expiry_override,
+            &current.expiry_override,
+            c,
+        ),
+        redelivery_delay: diff(
+            "redelivery_delay",
+            &desired.redelivery_delay,
+            &current.redelivery_delay,
+            c,
+        ),
+        max_redelivery: diff(
+            "max_redelivery",
+            &desired.max_redelivery,
+            &current.max_redelivery,
+            c,
+        ),
+        exclusive: diff("exclusive", &desired.exclusive, &current.exclusive, c),
+        flow_control: diff(
+            "flow_control",
+            &desired.flow_control,
+            &current.flow_control,
+            c,
+        ),
+        store: diff("store", &desired.store, &current.store, c),
+        trace: diff("trace", &desired.trace, &current.trace, c),
+        import_channels: diff(
+            "import_channels",
+            &desired.import_channels,
+            &current.import_channels,
+            c,
+        ),
+    };
+    (patch, changes)
+}
+
+fn topic_patch(desired: &TopicInfo, current: &TopicInfo) -> (TopicInfoPatch, Vec<Change>) {
+    let mut changes = Vec::new();
+    let c = &mut changes;
+    let patch = TopicInfoPatch {
+        name: desired.name.clone(),
+        expiry_override: diff(
+            "expiry_override",
+            &desired.expiry_override,
+            &current.expiry_override,
+            c,
+        ),
+        global: diff("global", &desired.global, &current.global, c),
+        max_bytes: diff("max_bytes", &desired.max_bytes, &current.max_bytes, c),
+        max_messages: diff(
+            "max_messages",
+            &desired.max_messages,
+            &current.max_messages,
+            c,
+        ),
+        overflow_policy: diff(
+            "overflow_policy",
+            &desired.overflow_policy,
+            &current.overflow_policy,
+            c,
+        ),
+        prefetch: diff("prefetch", &desired.prefetch, &current.prefetch, c),
+        failsafe: diff("failsafe", &desired.failsafe, &current.failsafe, c),
+        secure: diff("secure", &desired.secure, &current.secure, c),
+        sender_name: diff("sender_name", &desired.sender_name, &current.sender_name, c),
+        sender_name_enforced: diff(
+            "sender_name_enforced",
+            &desired.sender_name_enforced,
+            &current.sender_name_enforced,
+            c,
+        ),
+        flow_control: diff(
+            "flow_control",
+            &desired.flow_control,
+            &current.flow_control,
+            c,
+        ),
+        store: diff("store", &desired.store, &current.store, c),
+        trace: diff("trace", &desired.trace, &current.trace, c),
+        import_channels: diff(
+            "import_channels",
+            &desired.import_channels,
+            &current.import_channels,
+            c,
+        ),
+        export_channels: diff(
+            "export_channels",
+            &desired.export_channels,
+            &current.export_channels,
+            c,
+        ),
+    };
+    (patch, changes)
+}
+
+/// grants and revokes, permissions are reconciled on every destination named in the
+/// desired state and, when pruning, on every prunable destination with permissions
+fn acl_actions(
+    session: &Session,
+    desired: &DesiredState,
+    options: &ReconcileOptions,
+) -> Result<(Vec<Action>, Vec<Action>), Error> {
+    let mut grants = Vec::new();
+    let mut revokes = Vec::new();
+    if desired.acls.is_empty() && !options.prune {
+        return Ok((grants, revokes));
+    }
+    let acls = list_acls(session, AclFilter::All)?;
+    let mut destinations: Vec<Destination> = Vec::new();
+    let mut seen = HashSet::new();
+    let named = desired
+        .queues
+        .iter()
+        .map(|queue| Destination::Queue(queue.name.clone()))
+        .chain(
+            desired
+                .topics
+                .iter()
+                .map(|topic| Destination::Topic(topic.name.clone())),
+        )
+        .chain(desired.acls.iter().map(|acl| acl.destination.clone()));
+    let pruned = acls
+        .iter()
+        .map(|acl| acl.destination.clone())
+        .filter(|destination| options.prune && prunable(destination_name(destination)));
+    for destination in named.chain(pruned) {
+        if seen.insert(destination.clone()) {
+            destinations.push(destination);
+        }
+    }
+    for destination in destinations {
+        let wanted = permissions_by_principal(
+            desired
+                .acls
+                .iter()
+                .filter(|acl| acl.destination == destination),
+        );
+        let current =
+            permissions_by_principal(acls.iter().filter(|acl| acl.destination == destination));
+        for (principal, permissions) in &wanted {
+            let granted = current
+                .iter()
+                .find(|(p, _)| p == principal)
+                .map(|(_, permissions)| permissions.clone())
+                .unwrap_or_default();
+            let missing: Vec<Permission> = permissions.difference(&granted).copied().collect();
+            if !missing.is_empty() {
+                grants.push(Action::Grant(AclEntry {
+                    destination: destination.clone(),
+                    principal: principal.clone(),
+                    permissions: missing,
+                }));
+            }
+        }
+        if !options.prune {
+            continue;
+        }
+        for (principal, permissions) in &current {
+            let kept = wanted
+                .iter()
+                .find(|(p, _)| p == principal)
+                .map(|(_, permissions)| permissions.clone())
+                .unwrap_or_default();
+            let revoked: Vec<Permission> = permissions.difference(&kept).copied().collect();
+            if !revoked.is_empty() {
+                revokes.push(Action::Revoke(AclEntry {
+                    destination: destination.clone(),
+                    principal: principal.clone(),
+                    permissions: revoked,
+                }));
+            }
+        }
+    }
+    Ok((grants, revokes))
+}
+
+/// merges the entries of a destination by principal, keeping the order of the entries
+fn permissions_by_principal<'a>(
+    acls: impl Iterator<Item = &'a AclEntry>,
+) -> Vec<(Principal, BTreeSet<Permission>)> {
+    let mut merged: Vec<(Principal, BTreeSet<Permission>)> = Vec::new();
+    for acl in acls {
+        match merged.iter_mut().find(|(p, _)| *p == acl.principal) {
+            Some((_, permissions)) => permissions.extend(acl.permissions.iter().copied()),
+            None => merged.push((
+                acl.principal.clone(),
+                acl.permissions.iter().copied().collect(),
+            )),
+        }
+    }
+    merged
+}
+
+/// executes the actions of a plan in order, stopping at the first failing action
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+pub fn apply(session: &Session, plan: &Plan) -> Result<(), Error> {
+    for action in &plan.actions {
+        trace!("applying {}", action);
+        match action {
+            Action::CreateQueue(queue) => create_queue(session, queue)?,
+            Action::UpdateQueue { patch, .. } => patch_queue(session, patch)?,
+            Action::DeleteQueue(name) => delete_queue(session, name)?,
+            Action::CreateTopic(topic) => create_topic(session, topic)?,
+            Action::UpdateTopic { patch, .. } => patch_topic(session, patch)?,
+            Action::DeleteTopic(name) => delete_topic(session, name)?,
+            Action::CreateBridge(bridge) => create_bridge(session, bridge)?,
+            Action::DeleteBridge(bridge) => delete_bridge(session, bridge)?,
+            Action::Grant(acl) => {
+                grant(session, &acl.destination, &acl.principal, &acl.permissions)?
+            }
+            Action::Revoke(acl) => {
+                revoke(session, &acl.destination, &acl.principal, &acl.permissions)?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// plans the changes towards the desired state and applies them unless `dry_run` is set
+///
+/// the underlying connection must be an admin connection created through the tibco_ems::admin::connect() function.
+///
+/// returns the plan, which was applied completely if no error is returned.
+pub fn reconcile(
+    session: &Session,
+    desired: &DesiredState,
+    options: &ReconcileOptions,
+) -> Result<Plan, Error> {
+    let plan = plan(session, desired, options)?;
+    if !options.dry_run {
+        apply(session, &plan)?;
+    }
+    Ok(plan)
+}
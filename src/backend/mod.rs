@@ -0,0 +1,174 @@
+//! Backends implementing the messaging operations.
+//!
+//! Connections, sessions, consumers and messages only hold opaque handles. Every
+//! handle is registered with the backend which issued it, so calls on these objects
+//! are routed to the right backend at runtime. This allows native and mocked
+//! connections to be used side by side within the same process.
+
+use super::{Destination, Message};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Error;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+/// acknowledge mode of a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcknowledgeMode {
+    /// messages are acknowledged on receive
+    Auto,
+    /// every message needs to be confirmed or rolled back on its own
+    ExplicitClient,
+}
+
+/// kind of consumer to open on a destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsumerKind<'a> {
+    /// plain consumer, on topics this is a non-shared subscriber
+    Plain,
+    /// shared subscription on a topic with the given name
+    Shared(&'a str),
+    /// shared durable subscription on a topic with the given name
+    Durable(&'a str),
+}
+
+/// a messaging backend
+///
+/// all objects are represented by handles issued by the backend itself,
+/// a handle of 0 represents a missing object.
+pub trait Backend: fmt::Debug + Send + Sync {
+    /// open a connection, returns the connection handle
+    fn connect(&self, url: &str, user: &str, password: &str) -> Result<usize, Error>;
+
+    /// open a context on the naming service of a server, returns the context handle
+    fn lookup_context(&self, url: &str, user: &str, password: &str) -> Result<usize, Error>;
+
+    /// look up a destination by its JNDI name
+    fn lookup_destination(&self, context: usize, name: &str) -> Result<Destination, Error>;
+
+    /// look up a connection factory by its JNDI name, returns the factory handle
+    fn lookup_factory(&self, context: usize, name: &str) -> Result<usize, Error>;
+
+    /// close a lookup context
+    fn close_lookup_context(&self, context: usize);
+
+    /// open a connection through a looked up factory, returns the connection handle
+    fn connect_factory(&self, factory: usize, user: &str, password: &str) -> Result<usize, Error>;
+
+    /// destroy a looked up factory
+    fn close_factory(&self, factory: usize);
+
+    /// close a connection together with its sessions
+    fn close_connection(&self, connection: usize);
+
+    /// get the active url of a connection
+    fn active_url(&self, connection: usize) -> Result<String, Error>;
+
+    /// open a session, returns the session and producer handle
+    fn session(&self, connection: usize, mode: AcknowledgeMode) -> Result<(usize, usize), Error>;
+
+    /// close a session together with its producer
+    fn close_session(&self, session: usize, producer: usize);
+
+    /// open a consumer, returns the consumer handle
+    fn consumer(
+        &self,
+        session: usize,
+        destination: &Destination,
+        kind: ConsumerKind,
+        selector: Option<&str>,
+    ) -> Result<usize, Error>;
+
+    /// send a message to a destination
+    fn send(
+        &self,
+        session: usize,
+        producer: usize,
+        destination: &Destination,
+        message: &Message,
+    ) -> Result<(), Error>;
+
+    /// receive a message, a wait time of None blocks until a message is available
+    ///
+    /// the pointer of the returned message is a message handle of this backend
+    fn receive(&self, consumer: usize, wait_time_ms: Option<i64>)
+        -> Result<Option<Message>, Error>;
+
+    /// send a message and wait for the reply, this is used for admin requests
+    fn request_reply(
+        &self,
+        session: usize,
+        destination: &Destination,
+        message: &Message,
+        timeout: i64,
+    ) -> Result<Option<Message>, Error>;
+
+    /// acknowledge a received message
+    fn acknowledge(&self, message: usize);
+
+    /// recover a received message, so it gets redelivered
+    fn recover(&self, message: usize);
+
+    /// release a received message
+    fn destroy(&self, message: usize);
+
+    /// whether the admin queue is emulated by the backend instead of a real server
+    fn emulates_admin(&self) -> bool {
+        false
+    }
+}
+
+struct Handle {
+    backend: Arc<dyn Backend>,
+    parent: Option<usize>,
+}
+
+fn handles() -> MutexGuard<'static, HashMap<usize, Handle>> {
+    static HANDLES: OnceLock<Mutex<HashMap<usize, Handle>>> = OnceLock::new();
+    HANDLES
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+/// registers a handle with the backend that issued it
+///
+/// handles with a parent are released together with the parent
+pub(crate) fn register(handle: usize, backend: &Arc<dyn Backend>, parent: Option<usize>) {
+    if handle != 0 {
+        handles().insert(
+            handle,
+            Handle {
+                backend: backend.clone(),
+                parent,
+            },
+        );
+    }
+}
+
+/// looks up the backend of a handle
+pub(crate) fn lookup(handle: usize) -> Option<Arc<dyn Backend>> {
+    handles().get(&handle).map(|h| h.backend.clone())
+}
+
+/// looks up the backend of a handle, failing for unknown handles
+pub(crate) fn backend_of(handle: usize, kind: &str) -> Result<Arc<dyn Backend>, Error> {
+    lookup(handle).ok_or_else(|| Error::other(format!("{kind} is not open")))
+}
+
+/// releases a handle and all handles registered below it
+pub(crate) fn unregister(handle: usize) {
+    let mut handles = handles();
+    handles.remove(&handle);
+    let mut released = vec![handle];
+    while let Some(parent) = released.pop() {
+        let children: Vec<usize> = handles
+            .iter()
+            .filter(|(_, h)| h.parent == Some(parent))
+            .map(|(child, _)| *child)
+            .collect();
+        for child in children {
+            handles.remove(&child);
+            released.push(child);
+        }
+    }
+}
@@ -1,21 +1,11 @@
 #![warn(missing_docs)]
 //! Tibco EMS binding.
 
-use enum_extract::extract;
 use std::collections::HashMap;
 use std::fmt;
-use std::io::Error;
+use std::io::{Error, ErrorKind};
 use std::sync::Arc;
 
-#[cfg(feature = "ems-sys")]
-use {
-    log::{error, trace},
-    std::ffi::{c_void, CStr, CString},
-    std::io::ErrorKind,
-    std::ops::Deref,
-    tibco_ems_sys::{tibemsDestinationType, tibemsMsgType, tibems_bool, tibems_status},
-};
-
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -23,14 +13,37 @@ use serde::{Deserialize, Serialize};
 pub mod stream;
 
 pub mod admin;
+pub mod backend;
+pub mod mock;
+pub mod selector;
+
+#[cfg(feature = "ems-sys")]
+pub mod native;
+
+use backend::{AcknowledgeMode, Backend, ConsumerKind};
 
-/// holds the native Connection pointer
+/// holds the native Connection pointer, closed together with its sessions when the
+/// last clone is dropped
 #[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
 pub struct Connection {
     pointer: Arc<usize>,
 }
 
+/// connection factory looked up in the naming service of the server, destroyed when
+/// the last clone is dropped
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionFactory {
+    name: String,
+    pointer: Arc<usize>,
+}
+
+/// context on the naming service of the server, closed when dropped
+#[derive(Debug, PartialEq)]
+pub struct LookupContext {
+    pointer: usize,
+}
+
 /// holds the native Session pointer
 #[allow(dead_code)]
 #[derive(Debug, Clone, PartialEq)]
@@ -48,7 +61,7 @@ pub struct Consumer {
 
 /// Destination, can either be Queue or Topic
 #[allow(dead_code)]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Destination {
     /// Destination type Queue
@@ -195,218 +208,193 @@ impl fmt::Display for Message {
     }
 }
 
-#[cfg(feature = "ems-sys")]
 /// open a connection to the Tibco EMS server
+///
+/// with the `ems-sys` feature this uses the [`native::NativeBackend`], otherwise it
+/// connects to the in-memory broker of the current thread, see [`mock::MockBroker`]
 pub fn connect(url: &str, user: &str, password: &str) -> Result<Connection, Error> {
-    let mut connection_pointer: usize = 0;
-    unsafe {
-        let factory = tibco_ems_sys::tibemsConnectionFactory_Create();
-        let c_url = CString::new(url).unwrap();
-        let status = tibco_ems_sys::tibemsConnectionFactory_SetServerURL(factory, c_url.as_ptr());
-        match status {
-            tibems_status::TIBEMS_OK => {
-                trace!("tibemsConnectionFactory_SetServerURL: {status:?}")
-            }
-            _ => {
-                // not testable, c_url protects from this error path
-                error!("tibemsConnectionFactory_SetServerURL: {status:?}");
-                return Err(Error::new(ErrorKind::InvalidData, "cannot set server url"));
-            }
-        }
-        let c_user = CString::new(user).unwrap();
-        let c_password = CString::new(password).unwrap();
-        let status = tibco_ems_sys::tibemsConnectionFactory_CreateConnection(
-            factory,
-            &mut connection_pointer,
-            c_user.as_ptr(),
-            c_password.as_ptr(),
-        );
-        match status {
-            tibems_status::TIBEMS_OK => {
-                trace!("tibemsConnectionFactory_CreateConnection: {status:?}")
-            }
-            _ => {
-                error!("tibemsConnectionFactory_CreateConnection: {status:?}");
-                return Err(Error::new(
-                    ErrorKind::NotConnected,
-                    "cannot create connection",
-                ));
-            }
-        }
-        let status = tibco_ems_sys::tibemsConnection_Start(connection_pointer);
-        match status {
-            tibems_status::TIBEMS_OK => trace!("tibemsConnection_Start: {status:?}"),
-            _ => {
-                error!("tibemsConnection_Start: {status:?}");
-                return Err(Error::new(
-                    ErrorKind::NotConnected,
-                    "cannot start connection",
-                ));
-            }
+    #[cfg(feature = "ems-sys")]
+    let backend = native::NativeBackend;
+    #[cfg(not(feature = "ems-sys"))]
+    let backend = mock::MockBroker::current(url);
+    connect_with_backend(backend, url, user, password)
+}
+
+/// open a connection through the given backend
+///
+/// all sessions, consumers and messages created from this connection use the same backend
+pub fn connect_with_backend<B: Backend + 'static>(
+    backend: B,
+    url: &str,
+    user: &str,
+    password: &str,
+) -> Result<Connection, Error> {
+    let backend: Arc<dyn Backend> = Arc::new(backend);
+    let connection_pointer = backend.connect(url, user, password)?;
+    backend::register(connection_pointer, &backend, None);
+    Ok(Connection {
+        pointer: Arc::from(connection_pointer),
+    })
+}
+
+/// open a context on the naming service of the Tibco EMS server
+///
+/// like [`connect`] this uses the native backend with the `ems-sys` feature and the
+/// in-memory broker of the current thread otherwise.
+pub fn lookup_context(url: &str, user: &str, password: &str) -> Result<LookupContext, Error> {
+    #[cfg(feature = "ems-sys")]
+    let backend = native::NativeBackend;
+    #[cfg(not(feature = "ems-sys"))]
+    let backend = mock::MockBroker::current(url);
+    lookup_context_with_backend(backend, url, user, password)
+}
+
+/// open a lookup context through the given backend
+pub fn lookup_context_with_backend<B: Backend + 'static>(
+    backend: B,
+    url: &str,
+    user: &str,
+    password: &str,
+) -> Result<LookupContext, Error> {
+    let backend: Arc<dyn Backend> = Arc::new(backend);
+    let context_pointer = backend.lookup_context(url, user, password)?;
+    backend::register(context_pointer, &backend, None);
+    Ok(LookupContext {
+        pointer: context_pointer,
+    })
+}
+
+/// look up a connection factory by its JNDI name with an anonymous lookup context
+///
+/// the factory carries the url, client id and reconnect settings configured on the
+/// server, see [`admin::create_factory`].
+pub fn lookup_factory(url: &str, name: &str) -> Result<ConnectionFactory, Error> {
+    lookup_context(url, "", "")?.lookup_factory(name)
+}
+
+/// look up a connection factory through the given backend
+pub fn lookup_factory_with_backend<B: Backend + 'static>(
+    backend: B,
+    url: &str,
+    name: &str,
+) -> Result<ConnectionFactory, Error> {
+    lookup_context_with_backend(backend, url, "", "")?.lookup_factory(name)
+}
+
+//
+// lookup context
+//
+
+impl LookupContext {
+    /// look up a destination by its JNDI name
+    pub fn lookup_destination(&self, name: &str) -> Result<Destination, Error> {
+        backend::backend_of(self.pointer, "lookup context")?.lookup_destination(self.pointer, name)
+    }
+
+    /// look up a connection factory by its JNDI name
+    pub fn lookup_factory(&self, name: &str) -> Result<ConnectionFactory, Error> {
+        let backend = backend::backend_of(self.pointer, "lookup context")?;
+        let factory_pointer = backend.lookup_factory(self.pointer, name)?;
+        backend::register(factory_pointer, &backend, None);
+        Ok(ConnectionFactory {
+            name: name.to_string(),
+            pointer: Arc::from(factory_pointer),
+        })
+    }
+
+    /// close the lookup context
+    fn close(&self) {
+        if let Some(backend) = backend::lookup(self.pointer) {
+            backend.close_lookup_context(self.pointer);
+            backend::unregister(self.pointer);
         }
     }
-    let conn = Connection {
-        pointer: Arc::from(connection_pointer),
-    };
-    Ok(conn)
 }
-#[cfg(not(feature = "ems-sys"))]
-/// open a connection to the Tibco EMS server
-pub fn connect(_url: &str, _user: &str, _password: &str) -> Result<Connection, Error> {
-    let conn = Connection {
-        pointer: Arc::from(0),
-    };
-    unsafe {
-        SERVER.connection = Some(conn.clone());
+
+impl Drop for LookupContext {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+//
+// connection factory
+//
+
+impl ConnectionFactory {
+    /// JNDI name the factory was looked up with
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// open a connection with the settings of the factory
+    pub fn connect(&self, user: &str, password: &str) -> Result<Connection, Error> {
+        let backend = backend::backend_of(*self.pointer, "connection factory")?;
+        let connection_pointer = backend.connect_factory(*self.pointer, user, password)?;
+        backend::register(connection_pointer, &backend, None);
+        Ok(Connection {
+            pointer: Arc::from(connection_pointer),
+        })
+    }
+
+    /// destroy the factory
+    fn close(&self) {
+        if let Some(backend) = backend::lookup(*self.pointer) {
+            backend.close_factory(*self.pointer);
+            backend::unregister(*self.pointer);
+        }
     }
-    Ok(conn)
 }
 
-#[cfg(not(feature = "ems-sys"))]
-/// contains a MockServer to emulate a Tibco EMS server
-pub struct MockServer {
-    pub connection: Option<Connection>,
-    pub session: Option<Session>,
-    pub messages: Vec<(Destination, Message)>,
-    pub consumer: Option<Destination>,
+impl Drop for ConnectionFactory {
+    fn drop(&mut self) {
+        if Arc::strong_count(&self.pointer) == 1 {
+            self.close();
+        }
+    }
 }
-#[cfg(not(feature = "ems-sys"))]
-pub static mut SERVER: MockServer = MockServer {
-    connection: None,
-    session: None,
-    messages: vec![],
-    consumer: None,
-};
 
 //
 // connection
 //
 
-impl Connection {
-    #[cfg(feature = "ems-sys")]
-    /// open a session
-    pub fn session(&self) -> Result<Session, Error> {
-        unsafe {
-            let mut session_pointer: usize = 0;
-            let connection_pointer = *self.pointer.deref();
-            let status = tibco_ems_sys::tibemsConnection_CreateSession(
-                connection_pointer,
-                &mut session_pointer,
-                tibco_ems_sys::tibems_bool::TIBEMS_FALSE,
-                tibco_ems_sys::tibemsAcknowledgeMode::TIBEMS_AUTO_ACKNOWLEDGE,
-            );
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsConnection_CreateSession: {status:?}"),
-                _ => {
-                    error!("tibemsConnection_CreateSession: {status:?}");
-                    return Err(Error::new(ErrorKind::Other, "creating session failed"));
-                }
-            }
-            let mut producer: usize = 0;
-            let dest: usize = 0;
-            let status =
-                tibco_ems_sys::tibemsSession_CreateProducer(session_pointer, &mut producer, dest);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsSession_CreateProducer: {status:?}"),
-                _ => {
-                    error!("tibemsSession_CreateProducer: {status:?}");
-                    return Err(Error::new(ErrorKind::Other, "creating producer failed"));
-                }
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if Arc::strong_count(&self.pointer) == 1 {
+            if let Some(backend) = backend::lookup(*self.pointer) {
+                backend.close_connection(*self.pointer);
+                backend::unregister(*self.pointer);
             }
-            let session = Session {
-                pointer: session_pointer,
-                producer_pointer: producer,
-            };
-            Ok(session)
         }
     }
-    #[cfg(not(feature = "ems-sys"))]
+}
+
+impl Connection {
     /// open a session
     pub fn session(&self) -> Result<Session, Error> {
-        Ok(Session {
-            pointer: 0,
-            producer_pointer: 0,
-        })
+        self.open_session(AcknowledgeMode::Auto)
     }
-    #[cfg(feature = "ems-sys")]
+
     /// open a session with transaction support
     pub fn transacted_session(&self) -> Result<Session, Error> {
-        let session: Session;
-        unsafe {
-            let mut session_pointer: usize = 0;
-            let connection_pointer = *self.pointer.deref();
-            let status = tibco_ems_sys::tibemsConnection_CreateSession(
-                connection_pointer,
-                &mut session_pointer,
-                tibco_ems_sys::tibems_bool::TIBEMS_FALSE,
-                tibco_ems_sys::tibemsAcknowledgeMode::TIBEMS_EXPLICIT_CLIENT_ACKNOWLEDGE,
-            );
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsConnection_CreateSession: {status:?}"),
-                _ => {
-                    error!("tibemsConnection_CreateSession: {status:?}");
-                    return Err(Error::new(ErrorKind::Other, "creating session failed"));
-                }
-            }
-            let mut producer: usize = 0;
-            let dest: usize = 0;
-            let status =
-                tibco_ems_sys::tibemsSession_CreateProducer(session_pointer, &mut producer, dest);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsSession_CreateProducer: {status:?}"),
-                _ => {
-                    error!("tibemsSession_CreateProducer: {status:?}");
-                    return Err(Error::new(ErrorKind::Other, "creating producer failed"));
-                }
-            }
-            session = Session {
-                pointer: session_pointer,
-                producer_pointer: producer,
-            };
-        }
-        Ok(session)
+        self.open_session(AcknowledgeMode::ExplicitClient)
     }
 
-    #[cfg(not(feature = "ems-sys"))]
-    /// open a session
-    pub fn transacted_session(&self) -> Result<Session, Error> {
+    fn open_session(&self, mode: AcknowledgeMode) -> Result<Session, Error> {
+        let backend = backend::backend_of(*self.pointer, "connection")?;
+        let (session_pointer, producer) = backend.session(*self.pointer, mode)?;
+        backend::register(session_pointer, &backend, Some(*self.pointer));
         Ok(Session {
-            pointer: 0,
-            producer_pointer: 0,
+            pointer: session_pointer,
+            producer_pointer: producer,
         })
     }
 
-    #[cfg(feature = "ems-sys")]
-    /// get active url from a ft connection
-    /// this is only required for admin connections,
-    /// normal connections automatically choose the active server
-    pub fn get_active_url(&self) -> Result<String, Error> {
-        let connection_pointer = *self.pointer.deref();
-        unsafe {
-            let buf_vec: Vec<i8> = vec![0; 0];
-            let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
-            let status = tibco_ems_sys::tibemsConnection_GetActiveURL(connection_pointer, &buf_ref);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsConnection_GetActiveURL: {status:?}"),
-                _ => {
-                    error!("tibemsConnection_GetActiveURL: {status:?}");
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        "failed to retrieve active url",
-                    ));
-                }
-            }
-            let url = CStr::from_ptr(buf_ref).to_str().unwrap();
-            Ok(url.to_string())
-        }
-    }
-
-    #[cfg(not(feature = "ems-sys"))]
     /// get active url from a ft connection
     /// this is only required for admin connections,
     /// normal connections automatically choose the active server
     pub fn get_active_url(&self) -> Result<String, Error> {
-        Ok("".to_string())
+        backend::backend_of(*self.pointer, "connection")?.active_url(*self.pointer)
     }
 
     /// open a consumer as stream of messages
@@ -436,62 +424,19 @@ impl Connection {
 //
 
 impl Consumer {
-    #[cfg(feature = "ems-sys")]
     /// receive messages from a consumer
     ///
     /// function returns after wait time with a Message or None
     /// a wait time of None blocks until a message is available
     pub fn receive_message(&self, wait_time_ms: Option<i64>) -> Result<Option<Message>, Error> {
-        unsafe {
-            let mut msg_pointer: usize = 0;
-            match wait_time_ms {
-                Some(time_ms) => {
-                    let status = tibco_ems_sys::tibemsMsgConsumer_ReceiveTimeout(
-                        self.pointer,
-                        &mut msg_pointer,
-                        time_ms,
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsMsgConsumer_ReceiveTimeout: {status:?}")
-                        }
-                        tibems_status::TIBEMS_TIMEOUT => {
-                            return Ok(None);
-                        }
-                        _ => {
-                            let status_str = format!("{status:?}");
-                            error!("tibemsMsgConsumer_ReceiveTimeout: {status_str}");
-                            return Err(Error::new(
-                                ErrorKind::Other,
-                                format!("receive message failed: {status_str}"),
-                            ));
-                        }
-                    }
-                }
-                None => {
-                    let status =
-                        tibco_ems_sys::tibemsMsgConsumer_Receive(self.pointer, &mut msg_pointer);
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsMsgConsumer_Receive: {status:?}")
-                        }
-                        _ => {
-                            let status_str = format!("{status:?}");
-                            error!("tibemsMsgConsumer_Receive: {status_str}");
-                            return Err(Error::new(
-                                ErrorKind::Other,
-                                format!("receive message failed: {status_str}"),
-                            ));
-                        }
-                    }
-                }
-            }
-            let msg = build_message_from_pointer(msg_pointer);
-            Ok(Some(msg))
+        let backend = backend::backend_of(self.pointer, "consumer")?;
+        let msg = backend.receive(self.pointer, wait_time_ms)?;
+        if let Some(pointer) = msg.as_ref().and_then(Message::pointer) {
+            backend::register(pointer, &backend, None);
         }
+        Ok(msg)
     }
 
-    #[cfg(feature = "ems-sys")]
     /// receive text messages from a consumer
     ///
     /// function returns after wait time with a Message or None
@@ -515,7 +460,6 @@ impl Consumer {
         }
     }
 
-    #[cfg(feature = "ems-sys")]
     /// receive bytes messages from a consumer
     ///
     /// function returns after wait time with a Message or None
@@ -539,7 +483,6 @@ impl Consumer {
         }
     }
 
-    #[cfg(feature = "ems-sys")]
     /// receive map messages from a consumer
     ///
     /// function returns after wait time with a Message or None
@@ -563,7 +506,6 @@ impl Consumer {
         }
     }
 
-    #[cfg(feature = "ems-sys")]
     /// receive object messages from a consumer
     ///
     /// function returns after wait time with a Message or None
@@ -586,119 +528,6 @@ impl Consumer {
             None => Ok(None),
         }
     }
-
-    #[cfg(not(feature = "ems-sys"))]
-    /// receive messages from a consumer
-    ///
-    /// function returns after wait time with a Message or None
-    /// the wait time is ignored
-    pub fn receive_message(&self, _wait_time_ms: Option<i64>) -> Result<Option<Message>, Error> {
-        unsafe {
-            let consumer_destination = SERVER.consumer.clone().unwrap();
-            let messages = SERVER.messages.clone();
-            for (dest, msg) in messages {
-                if dest == consumer_destination {
-                    return Ok(Some(msg));
-                }
-            }
-        }
-        Ok(None)
-    }
-    #[cfg(not(feature = "ems-sys"))]
-    /// receive text messages from a consumer
-    ///
-    /// function returns after wait time with a Message or None
-    /// the wait time is ignored
-    pub fn receive_text_message(
-        &self,
-        _wait_time_ms: Option<i64>,
-    ) -> Result<Option<TextMessage>, Error> {
-        unsafe {
-            let consumer_destination = SERVER.consumer.clone().unwrap();
-            let messages = SERVER.messages.clone();
-            for (dest, msg) in messages {
-                if dest == consumer_destination {
-                    return match msg {
-                        Message::TextMessage(ref msg) => Ok(Some(msg.to_owned())),
-                        _ => Ok(None),
-                    };
-                };
-            }
-        }
-        Ok(None)
-    }
-
-    #[cfg(not(feature = "ems-sys"))]
-    /// receive bytes messages from a consumer
-    ///
-    /// function returns after wait time with a Message or None
-    /// the wait time is ignored
-    pub fn receive_bytes_message(
-        &self,
-        _wait_time_ms: Option<i64>,
-    ) -> Result<Option<BytesMessage>, Error> {
-        unsafe {
-            let consumer_destination = SERVER.consumer.clone().unwrap();
-            let messages = SERVER.messages.clone();
-            for (dest, msg) in messages {
-                if dest == consumer_destination {
-                    return match msg {
-                        Message::BytesMessage(ref msg) => Ok(Some(msg.to_owned())),
-                        _ => Ok(None),
-                    };
-                };
-            }
-        }
-        Ok(None)
-    }
-
-    #[cfg(not(feature = "ems-sys"))]
-    /// receive map messages from a consumer
-    ///
-    /// function returns after wait time with a Message or None
-    /// the wait time is ignored
-    pub fn receive_map_message(
-        &self,
-        _wait_time_ms: Option<i64>,
-    ) -> Result<Option<MapMessage>, Error> {
-        unsafe {
-            let consumer_destination = SERVER.consumer.clone().unwrap();
-            let messages = SERVER.messages.clone();
-            for (dest, msg) in messages {
-                if dest == consumer_destination {
-                    return match msg {
-                        Message::MapMessage(ref msg) => Ok(Some(msg.to_owned())),
-                        _ => Ok(None),
-                    };
-                };
-            }
-        }
-        Ok(None)
-    }
-
-    #[cfg(not(feature = "ems-sys"))]
-    /// receive object messages from a consumer
-    ///
-    /// function returns after wait time with a Message or None
-    /// the wait time is ignored
-    pub fn receive_object_message(
-        &self,
-        _wait_time_ms: Option<i64>,
-    ) -> Result<Option<ObjectMessage>, Error> {
-        unsafe {
-            let consumer_destination = SERVER.consumer.clone().unwrap();
-            let messages = SERVER.messages.clone();
-            for (dest, msg) in messages {
-                if dest == consumer_destination {
-                    return match msg {
-                        Message::ObjectMessage(ref msg) => Ok(Some(msg.to_owned())),
-                        _ => Ok(None),
-                    };
-                };
-            }
-        }
-        Ok(None)
-    }
 }
 
 //
@@ -706,105 +535,18 @@ impl Consumer {
 //
 
 impl Session {
-    #[cfg(feature = "ems-sys")]
     /// open a message consumer for a queue
+    ///
+    /// the selector is validated before the consumer is opened, an invalid selector
+    /// fails with [`ErrorKind::InvalidInput`].
     pub fn queue_consumer(
         &self,
         destination: &Destination,
         selector: Option<&str>,
     ) -> Result<Consumer, Error> {
-        let consumer: Consumer;
-        let mut destination_pointer: usize = 0;
-        unsafe {
-            //create destination
-            match destination {
-                Destination::Queue(name) => {
-                    let c_destination = CString::new(name.clone()).unwrap();
-                    let status = tibco_ems_sys::tibemsDestination_Create(
-                        &mut destination_pointer,
-                        tibemsDestinationType::TIBEMS_QUEUE,
-                        c_destination.as_ptr(),
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsDestination_Create: {status:?}")
-                        }
-                        _ => {
-                            let status_str = format!("{status:?}");
-                            error!("tibemsDestination_Create: {status_str}");
-                            return Err(Error::new(
-                                ErrorKind::Other,
-                                format!("create destination failed: {status_str}"),
-                            ));
-                        }
-                    }
-                }
-                Destination::Topic(name) => {
-                    let c_destination = CString::new(name.clone()).unwrap();
-                    let status = tibco_ems_sys::tibemsDestination_Create(
-                        &mut destination_pointer,
-                        tibemsDestinationType::TIBEMS_TOPIC,
-                        c_destination.as_ptr(),
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsDestination_Create: {status:?}")
-                        }
-                        _ => {
-                            let status_str = format!("{status:?}");
-                            error!("tibemsDestination_Create: {status_str}");
-                            return Err(Error::new(
-                                ErrorKind::Other,
-                                format!("create destination failed: {status_str}"),
-                            ));
-                        }
-                    }
-                }
-            }
-            //open consumer
-            let mut consumer_pointer: usize = 0;
-            let c_selector: CString = match selector {
-                Some(val) => CString::new(val).unwrap(),
-                _ => CString::new("".to_string()).unwrap(),
-            };
-            let status = tibco_ems_sys::tibemsSession_CreateConsumer(
-                self.pointer,
-                &mut consumer_pointer,
-                destination_pointer,
-                c_selector.as_ptr(),
-                tibco_ems_sys::tibems_bool::TIBEMS_TRUE,
-            );
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsSession_CreateConsumer: {status:?}"),
-                _ => {
-                    let status_str = format!("{status:?}");
-                    error!("tibemsSession_CreateConsumer: {status_str}");
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        format!("create consumer failed: {status_str}"),
-                    ));
-                }
-            }
-            consumer = Consumer {
-                pointer: consumer_pointer,
-            };
-        }
-        Ok(consumer)
-    }
-    #[cfg(not(feature = "ems-sys"))]
-    /// open a message consumer for a queue
-    pub fn queue_consumer(
-        &self,
-        destination: &Destination,
-        _selector: Option<&str>,
-    ) -> Result<Consumer, Error> {
-        unsafe {
-            SERVER.consumer = Some(destination.clone());
-        }
-        Ok(Consumer { pointer: 0 })
+        self.open_consumer(destination, ConsumerKind::Plain, selector)
     }
 
-    #[cfg(feature = "ems-sys")]
     /// open a message consumer for a topic
     pub fn topic_consumer(
         &self,
@@ -812,85 +554,13 @@ impl Session {
         subscription_name: &str,
         selector: Option<&str>,
     ) -> Result<Consumer, Error> {
-        let consumer: Consumer;
-        let mut destination_pointer: usize = 0;
-        unsafe {
-            //create destination
-            match destination {
-                Destination::Topic(name) => {
-                    let c_destination = CString::new(name.clone()).unwrap();
-                    let status = tibco_ems_sys::tibemsDestination_Create(
-                        &mut destination_pointer,
-                        tibemsDestinationType::TIBEMS_TOPIC,
-                        c_destination.as_ptr(),
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsDestination_Create: {status:?}")
-                        }
-                        _ => {
-                            let status_str = format!("{status:?}");
-                            error!("tibemsDestination_Create: {status_str}");
-                            return Err(Error::new(
-                                ErrorKind::Other,
-                                format!("create destination failed: {status_str}"),
-                            ));
-                        }
-                    }
-                }
-                Destination::Queue(_) => {
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        "destination is not of type topic",
-                    ));
-                }
-            }
-            //open consumer
-            let mut consumer_pointer: usize = 0;
-            let c_subscription_name = CString::new((*subscription_name).to_string()).unwrap();
-            let c_selector: CString = match selector {
-                Some(val) => CString::new(val).unwrap(),
-                _ => CString::new("".to_string()).unwrap(),
-            };
-            let status = tibco_ems_sys::tibemsSession_CreateSharedConsumer(
-                self.pointer,
-                &mut consumer_pointer,
-                destination_pointer,
-                c_subscription_name.as_ptr(),
-                c_selector.as_ptr(),
-            );
-            match status {
-                tibems_status::TIBEMS_OK => {
-                    trace!("tibemsSession_CreateSharedConsumer: {status:?}")
-                }
-                _ => {
-                    let status_str = format!("{status:?}");
-                    error!("tibemsSession_CreateSharedConsumer: {status_str}");
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        format!("create consumer failed: {status_str}"),
-                    ));
-                }
-            }
-            consumer = Consumer {
-                pointer: consumer_pointer,
-            };
-        }
-        Ok(consumer)
-    }
-
-    #[cfg(not(feature = "ems-sys"))]
-    /// open a message consumer for a topic
-    pub fn topic_consumer(
-        &self,
-        _destination: &Destination,
-        _subscription_name: &str,
-        _selector: Option<&str>,
-    ) -> Result<Consumer, Error> {
-        unimplemented!()
+        self.open_consumer(
+            destination,
+            ConsumerKind::Shared(subscription_name),
+            selector,
+        )
     }
 
-    #[cfg(feature = "ems-sys")]
     /// open a durable message consumer for a topic
     pub fn topic_durable_consumer(
         &self,
@@ -898,292 +568,32 @@ impl Session {
         durable_name: &str,
         selector: Option<&str>,
     ) -> Result<Consumer, Error> {
-        let consumer: Consumer;
-        let mut destination_pointer: usize = 0;
-        unsafe {
-            //create destination
-            match destination {
-                Destination::Topic(name) => {
-                    let c_destination = CString::new(name.clone()).unwrap();
-                    let status = tibco_ems_sys::tibemsDestination_Create(
-                        &mut destination_pointer,
-                        tibemsDestinationType::TIBEMS_TOPIC,
-                        c_destination.as_ptr(),
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsDestination_Create: {status:?}")
-                        }
-                        _ => {
-                            let status_str = format!("{status:?}");
-                            error!("tibemsDestination_Create: {status_str}");
-                            return Err(Error::new(
-                                ErrorKind::Other,
-                                format!("create destination failed: {status_str}"),
-                            ));
-                        }
-                    }
-                }
-                Destination::Queue(_) => {
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        "destination is not of type topic",
-                    ));
-                }
-            }
-            //open consumer
-            let mut consumer_pointer: usize = 0;
-            let c_durable_name = CString::new((*durable_name).to_string()).unwrap();
-            let c_selector: CString = match selector {
-                Some(val) => CString::new(val).unwrap(),
-                _ => CString::new("".to_string()).unwrap(),
-            };
-            let status = tibco_ems_sys::tibemsSession_CreateSharedDurableConsumer(
-                self.pointer,
-                &mut consumer_pointer,
-                destination_pointer,
-                c_durable_name.as_ptr(),
-                c_selector.as_ptr(),
-            );
-            match status {
-                tibems_status::TIBEMS_OK => {
-                    trace!("tibemsSession_CreateSharedDurableConsumer: {status:?}")
-                }
-                _ => {
-                    let status_str = format!("{status:?}");
-                    error!("tibemsSession_CreateSharedDurableConsumer: {status_str}");
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        format!("create consumer failed: {status_str}"),
-                    ));
-                }
-            }
-            consumer = Consumer {
-                pointer: consumer_pointer,
-            };
-        }
-        Ok(consumer)
+        self.open_consumer(destination, ConsumerKind::Durable(durable_name), selector)
     }
 
-    #[cfg(not(feature = "ems-sys"))]
-    /// open a durable message consumer for a topic
-    pub fn topic_durable_consumer(
+    fn open_consumer(
         &self,
-        _destination: &Destination,
-        _durable_name: &str,
-        _selector: Option<&str>,
+        destination: &Destination,
+        kind: ConsumerKind,
+        selector: Option<&str>,
     ) -> Result<Consumer, Error> {
-        unimplemented!()
+        selector::validate(selector)?;
+        let backend = backend::backend_of(self.pointer, "session")?;
+        let consumer_pointer = backend.consumer(self.pointer, destination, kind, selector)?;
+        backend::register(consumer_pointer, &backend, Some(self.pointer));
+        Ok(Consumer {
+            pointer: consumer_pointer,
+        })
     }
 
-    #[cfg(feature = "ems-sys")]
     /// close a session
     fn close(&self) {
-        unsafe {
-            //destroy producer
-            if self.producer_pointer != 0 {
-                let status = tibco_ems_sys::tibemsMsgProducer_Close(self.producer_pointer);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMsgProducer_Close: {status:?}"),
-                    _ => error!("tibemsMsgProducer_Close: {status:?}"),
-                }
-            }
-            let status = tibco_ems_sys::tibemsSession_Close(self.pointer);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsSession_Close: {status:?}"),
-                _ => error!("tibemsSession_Close: {status:?}"),
-            }
-        }
-    }
-    #[cfg(not(feature = "ems-sys"))]
-    /// close a session
-    fn close(&self) {}
-
-    #[cfg(feature = "tracing")]
-    fn add_trace_to_message(&self, message: &mut Message) -> impl opentelemetry::trace::Span {
-        let tracer_provider = opentelemetry::global::tracer_provider();
-        use opentelemetry::sdk::trace::IdGenerator;
-        use opentelemetry::sdk::trace::RandomIdGenerator;
-        use opentelemetry::trace::Span;
-        use opentelemetry::trace::SpanId;
-        use opentelemetry::trace::TraceId;
-        use opentelemetry::trace::Tracer;
-        use opentelemetry::trace::TracerProvider;
-        let tracer = tracer_provider.versioned_tracer("ems", Some("0.5"), None);
-        let span = tracer.start("send");
-        let id_generator = RandomIdGenerator::default();
-        let headers = match message {
-            Message::BytesMessage(b) => b.header.as_mut(),
-            Message::MapMessage(m) => m.header.as_mut(),
-            Message::TextMessage(t) => t.header.as_mut(),
-            Message::ObjectMessage(o) => o.header.as_mut(),
-        };
-        let ctx = span.span_context();
-        let span_id = if ctx.span_id() == SpanId::INVALID {
-            id_generator.new_span_id()
-        } else {
-            ctx.span_id()
-        };
-        let trace_id = if ctx.trace_id() == TraceId::INVALID {
-            id_generator.new_trace_id()
-        } else {
-            ctx.trace_id()
-        };
-        if let Some(e) = headers {
-            e.insert(
-                "spanId".to_string(),
-                TypedValue::String(span_id.to_string()),
-            );
-            e.insert(
-                "traceId".to_string(),
-                TypedValue::String(trace_id.to_string()),
-            );
-        };
-        span
-    }
-
-    #[cfg(feature = "ems-sys")]
-    /// sending a message to a destination (only queues are supported)
-    pub fn send_message<M: Into<Message>>(
-        &self,
-        destination: &Destination,
-        message: M,
-    ) -> Result<(), Error> {
-        #[cfg(feature = "tracing")]
-        let mut message: Message = message.into();
-        #[cfg(not(feature = "tracing"))]
-        let message: Message = message.into();
-
-        let mut dest: usize = 0;
-        let mut local_producer: usize = 0;
-        #[cfg(feature = "tracing")]
-        let mut span = self.add_trace_to_message(&mut message);
-        #[cfg(feature = "tracing")]
-        use opentelemetry::trace::Span;
-        unsafe {
-            match destination {
-                Destination::Queue(name) => {
-                    #[cfg(feature = "tracing")]
-                    span.set_attribute(opentelemetry::KeyValue::new(
-                        "messaging.destination",
-                        name.clone(),
-                    ));
-                    #[cfg(feature = "tracing")]
-                    span.set_attribute(opentelemetry::KeyValue::new(
-                        "messaging.destination_kind",
-                        "queue",
-                    ));
-                    #[cfg(feature = "tracing")]
-                    span.set_attribute(opentelemetry::KeyValue::new(
-                        "messaging.system",
-                        "TibcoEMS",
-                    ));
-                    let c_destination = CString::new(name.clone()).unwrap();
-                    let status = tibco_ems_sys::tibemsDestination_Create(
-                        &mut dest,
-                        tibemsDestinationType::TIBEMS_QUEUE,
-                        c_destination.as_ptr(),
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsDestination_Create: {status:?}")
-                        }
-                        _ => {
-                            let status_str = format!("{status:?}");
-                            error!("tibemsDestination_Create: {status_str}");
-                            return Err(Error::new(
-                                ErrorKind::Other,
-                                format!("create destination failed: {status_str}"),
-                            ));
-                        }
-                    }
-                }
-                Destination::Topic(name) => {
-                    let c_destination = CString::new(name.clone()).unwrap();
-                    let status = tibco_ems_sys::tibemsDestination_Create(
-                        &mut dest,
-                        tibemsDestinationType::TIBEMS_TOPIC,
-                        c_destination.as_ptr(),
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsDestination_Create: {status:?}")
-                        }
-                        _ => {
-                            let status_str = format!("{status:?}");
-                            error!("tibemsDestination_Create: {status_str}");
-                            return Err(Error::new(
-                                ErrorKind::Other,
-                                format!("create destination failed: {status_str}"),
-                            ));
-                        }
-                    }
-                }
-            }
-            if self.producer_pointer == 0 {
-                let status = tibco_ems_sys::tibemsSession_CreateProducer(
-                    self.pointer,
-                    &mut local_producer,
-                    dest,
-                );
-                match status {
-                    tibems_status::TIBEMS_OK => {
-                        trace!("tibemsSession_CreateProducer: {status:?}")
-                    }
-                    _ => {
-                        let status_str = format!("{status:?}");
-                        error!("tibemsSession_CreateProducer: {status_str}");
-                        return Err(Error::new(
-                            ErrorKind::Other,
-                            format!("create producer failed: {status_str}"),
-                        ));
-                    }
-                }
-            }
-            let msg = build_message_pointer_from_message(&message);
-            let status = tibco_ems_sys::tibemsMsgProducer_SendToDestination(
-                self.producer_pointer,
-                dest,
-                msg,
-            );
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsMsgProducer_Send: {status:?}"),
-                _ => {
-                    let status_str = format!("{status:?}");
-                    error!("tibemsMsgProducer_Send: {status_str}");
-                    return Err(Error::new(
-                        ErrorKind::Other,
-                        format!("send message failed: {status_str}"),
-                    ));
-                }
-            }
-            //destroy producer if generated inline
-            if self.producer_pointer == 0 {
-                let status = tibco_ems_sys::tibemsMsgProducer_Close(local_producer);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMsgProducer_Close: {status:?}"),
-                    _ => error!("tibemsMsgProducer_Close: {status:?}"),
-                }
-            }
-            //destroy message
-            let status = tibco_ems_sys::tibemsMsg_Destroy(msg);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsMsg_Destroy: {status:?}"),
-                _ => error!("tibemsMsg_Destroy: {status:?}"),
-            }
-            //destroy destination
-            let status = tibco_ems_sys::tibemsDestination_Destroy(dest);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsDestination_Destroy: {status:?}"),
-                _ => error!("tibemsDestination_Destroy: {status:?}"),
-            }
+        if let Some(backend) = backend::lookup(self.pointer) {
+            backend.close_session(self.pointer, self.producer_pointer);
+            backend::unregister(self.pointer);
         }
-        #[cfg(feature = "tracing")]
-        span.end();
-        Ok(())
     }
 
-    #[cfg(not(feature = "ems-sys"))]
     /// sending a message to a destination (only queues are supported)
     pub fn send_message<M: Into<Message>>(
         &self,
@@ -1191,13 +601,14 @@ impl Session {
         message: M,
     ) -> Result<(), Error> {
         let message: Message = message.into();
-        unsafe {
-            SERVER.messages.push((destination.clone(), message));
-        }
-        Ok(())
+        backend::backend_of(self.pointer, "session")?.send(
+            self.pointer,
+            self.producer_pointer,
+            destination,
+            &message,
+        )
     }
 
-    #[cfg(feature = "ems-sys")]
     /// request/reply
     pub fn request_reply<M: Into<Message>>(
         &self,
@@ -1206,174 +617,12 @@ impl Session {
         timeout: i64,
     ) -> Result<Option<Message>, Error> {
         let message: Message = message.into();
-        //create temporary destination
-        let mut reply_dest: usize = 0;
-        let mut dest: usize = 0;
-        unsafe {
-            match &destination {
-                Destination::Queue(name) => {
-                    let status = tibco_ems_sys::tibemsSession_CreateTemporaryQueue(
-                        self.pointer,
-                        &mut reply_dest,
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsSession_CreateTemporaryQueue: {status:?}")
-                        }
-                        _ => error!("tibemsSession_CreateTemporaryQueue: {status:?}"),
-                    }
-                    let c_destination = CString::new(name.clone()).unwrap();
-                    let status = tibco_ems_sys::tibemsDestination_Create(
-                        &mut dest,
-                        tibemsDestinationType::TIBEMS_QUEUE,
-                        c_destination.as_ptr(),
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsDestination_Create: {status:?}")
-                        }
-                        _ => error!("tibemsDestination_Create: {status:?}"),
-                    }
-                }
-                Destination::Topic(name) => {
-                    let status = tibco_ems_sys::tibemsSession_CreateTemporaryTopic(
-                        self.pointer,
-                        &mut reply_dest,
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsSession_CreateTemporaryTopic: {status:?}")
-                        }
-                        _ => error!("tibemsSession_CreateTemporaryTopic: {status:?}"),
-                    }
-                    let c_destination = CString::new(name.clone()).unwrap();
-                    let status = tibco_ems_sys::tibemsDestination_Create(
-                        &mut dest,
-                        tibemsDestinationType::TIBEMS_TOPIC,
-                        c_destination.as_ptr(),
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsDestination_Create: {status:?}")
-                        }
-                        _ => error!("tibemsDestination_Create: {status:?}"),
-                    }
-                }
-            }
-            let mut producer: usize = 0;
-            let status =
-                tibco_ems_sys::tibemsSession_CreateProducer(self.pointer, &mut producer, dest);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsSession_CreateProducer: {status:?}"),
-                _ => error!("tibemsSession_CreateProducer: {status:?}"),
-            }
-            let msg = build_message_pointer_from_message(&message);
-            //set reply to
-            let status = tibco_ems_sys::tibemsMsg_SetReplyTo(msg, reply_dest);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsMsg_SetReplyTo: {status:?}"),
-                _ => error!("tibemsMsg_SetReplyTo: {status:?}"),
-            }
-            let status = tibco_ems_sys::tibemsMsgProducer_Send(producer, msg);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsMsgProducer_Send: {status:?}"),
-                _ => error!("tibemsMsgProducer_Send: {status:?}"),
-            }
-            //destroy message
-            let status = tibco_ems_sys::tibemsMsg_Destroy(msg);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsMsg_Destroy: {status:?}"),
-                _ => error!("tibemsMsg_Destroy: {status:?}"),
-            }
-            //destroy producer
-            let status = tibco_ems_sys::tibemsMsgProducer_Close(producer);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsMsgProducer_Close: {status:?}"),
-                _ => error!("tibemsMsgProducer_Close: {status:?}"),
-            }
-            //destroy destination
-            let status = tibco_ems_sys::tibemsDestination_Destroy(dest);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsDestination_Destroy: {status:?}"),
-                _ => error!("tibemsDestination_Destroy: {status:?}"),
-            }
-            //open consumer
-            let mut consumer_pointer: usize = 0;
-            let status = tibco_ems_sys::tibemsSession_CreateConsumer(
-                self.pointer,
-                &mut consumer_pointer,
-                reply_dest,
-                std::ptr::null(),
-                tibco_ems_sys::tibems_bool::TIBEMS_TRUE,
-            );
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsSession_CreateConsumer: {status:?}"),
-                _ => error!("tibemsSession_CreateConsumer: {status:?}"),
-            }
-            let mut reply_message: usize = 0;
-            let status = tibco_ems_sys::tibemsMsgConsumer_ReceiveTimeout(
-                consumer_pointer,
-                &mut reply_message,
-                timeout,
-            );
-            match status {
-                tibems_status::TIBEMS_OK => {
-                    trace!("tibemsMsgConsumer_ReceiveTimeout: {status:?}")
-                }
-                tibems_status::TIBEMS_TIMEOUT => {
-                    return Ok(None);
-                }
-                _ => error!("tibemsMsgConsumer_ReceiveTimeout: {status:?}"),
-            }
-            let result = build_message_from_pointer(reply_message);
-            //close consumer
-            let status = tibco_ems_sys::tibemsMsgConsumer_Close(consumer_pointer);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsMsgConsumer_Close: {status:?}"),
-                _ => error!("tibemsMsgConsumer_Close: {status:?}"),
-            }
-            //destroy temporary destination
-            match &destination {
-                Destination::Queue { .. } => {
-                    //destroy reply_to_queue
-                    let status =
-                        tibco_ems_sys::tibemsSession_DeleteTemporaryQueue(self.pointer, reply_dest);
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsSession_DeleteTemporaryQueue: {status:?}")
-                        }
-                        _ => error!("tibemsSession_DeleteTemporaryQueue: {status:?}"),
-                    }
-                }
-                Destination::Topic { .. } => {
-                    //destroy reply_to_queue
-                    let status =
-                        tibco_ems_sys::tibemsSession_DeleteTemporaryTopic(self.pointer, reply_dest);
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            trace!("tibemsSession_DeleteTemporaryTopic: {status:?}")
-                        }
-                        _ => error!("tibemsSession_DeleteTemporaryTopic: {status:?}"),
-                    }
-                }
-            }
-            Ok(Some(result))
+        let backend = backend::backend_of(self.pointer, "session")?;
+        let reply = backend.request_reply(self.pointer, destination, &message, timeout)?;
+        if let Some(pointer) = reply.as_ref().and_then(Message::pointer) {
+            backend::register(pointer, &backend, None);
         }
-    }
-
-    #[cfg(not(feature = "ems-sys"))]
-    /// request/reply - always returns none
-    pub fn request_reply<M: Into<Message>>(
-        &self,
-        destination: &Destination,
-        message: M,
-        _timeout: i64,
-    ) -> Result<Option<Message>, Error> {
-        let message: Message = message.into();
-        unsafe {
-            SERVER.messages.push((destination.clone(), message));
-        }
-        Ok(None)
+        Ok(reply)
     }
 }
 
@@ -1450,116 +699,41 @@ impl fmt::Display for TypedValue {
 }
 
 impl Message {
-    #[cfg(feature = "ems-sys")]
-    fn destroy(&self) {
-        let destroy_msg = |pointer: usize| unsafe {
-            let status = tibco_ems_sys::tibemsMsg_Destroy(pointer);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsMsg_Destroy: {status:?}"),
-                _ => error!("tibemsMsg_Destroy: {status:?}"),
-            }
-        };
+    fn pointer(&self) -> Option<usize> {
         match self {
-            Message::TextMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    destroy_msg(pointer);
-                }
-            }
-            Message::BytesMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    destroy_msg(pointer);
-                }
-            }
-            Message::ObjectMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    destroy_msg(pointer);
-                }
-            }
-            Message::MapMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    destroy_msg(pointer);
-                }
-            }
+            Message::TextMessage(msg) => msg.pointer,
+            Message::BytesMessage(msg) => msg.pointer,
+            Message::ObjectMessage(msg) => msg.pointer,
+            Message::MapMessage(msg) => msg.pointer,
         }
     }
 
-    #[cfg(not(feature = "ems-sys"))]
-    fn destroy(&self) {}
+    fn destroy(&self) {
+        if let Some(pointer) = self.pointer() {
+            if let Some(backend) = backend::lookup(pointer) {
+                backend.destroy(pointer);
+                backend::unregister(pointer);
+            }
+        }
+    }
 
-    #[cfg(feature = "ems-sys")]
     /// confirms the message by invoking tibemsMsg_Acknowledge
     pub fn confirm(&self) {
-        let ack_msg = |pointer: usize| unsafe {
-            let status = tibco_ems_sys::tibemsMsg_Acknowledge(pointer);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsMsg_Acknowledge: {status:?}"),
-                _ => error!("tibemsMsg_Acknowledge: {status:?}"),
-            }
-        };
-        match self {
-            Message::TextMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    ack_msg(pointer);
-                }
-            }
-            Message::BytesMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    ack_msg(pointer);
-                }
-            }
-            Message::ObjectMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    ack_msg(pointer);
-                }
-            }
-            Message::MapMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    ack_msg(pointer);
-                }
+        if let Some(pointer) = self.pointer() {
+            if let Some(backend) = backend::lookup(pointer) {
+                backend.acknowledge(pointer);
             }
         }
     }
-    #[cfg(not(feature = "ems-sys"))]
-    /// confirms the message by invoking tibemsMsg_Acknowledge
-    pub fn confirm(&self) {}
 
-    #[cfg(feature = "ems-sys")]
     /// rolls the message back by invoking tibemsMsg_Recover
     pub fn rollback(&self) {
-        let recover = |pointer: usize| unsafe {
-            let status = tibco_ems_sys::tibemsMsg_Recover(pointer);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsMsg_Recover: {status:?}"),
-                _ => error!("tibemsMsg_Recover: {status:?}"),
-            }
-        };
-        match self {
-            Message::TextMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    recover(pointer);
-                }
-            }
-            Message::BytesMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    recover(pointer);
-                }
-            }
-            Message::ObjectMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    recover(pointer);
-                }
-            }
-            Message::MapMessage(msg) => {
-                if let Some(pointer) = msg.pointer {
-                    recover(pointer);
-                }
+        if let Some(pointer) = self.pointer() {
+            if let Some(backend) = backend::lookup(pointer) {
+                backend.recover(pointer);
             }
         }
     }
-
-    #[cfg(not(feature = "ems-sys"))]
-    /// rolls the message back by invoking tibemsMsg_Recover
-    pub fn rollback(&self) {}
 }
 
 impl Drop for Message {
@@ -1568,732 +742,6 @@ impl Drop for Message {
     }
 }
 
-#[cfg(feature = "ems-sys")]
-fn build_message_pointer_from_message(message: &Message) -> usize {
-    let mut msg_pointer: usize = 0;
-    unsafe {
-        match message {
-            Message::TextMessage(msg) => {
-                let status = tibco_ems_sys::tibemsTextMsg_Create(&mut msg_pointer);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsTextMsg_Create: {status:?}"),
-                    _ => error!("tibemsTextMsg_Create: {status:?}"),
-                }
-                let c_text = CString::new(msg.body.clone()).unwrap();
-                let status = tibco_ems_sys::tibemsTextMsg_SetText(msg_pointer, c_text.as_ptr());
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsTextMsg_SetText: {status:?}"),
-                    _ => error!("tibemsTextMsg_SetText: {status:?}"),
-                }
-            }
-            Message::BytesMessage(msg) => {
-                let status = tibco_ems_sys::tibemsBytesMsg_Create(&mut msg_pointer);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsBytesMsg_Create: {status:?}"),
-                    _ => error!("tibemsBytesMsg_Create: {status:?}"),
-                }
-                let content = msg.body.clone();
-                let body_size = content.len();
-                if body_size > 0 {
-                    let body_ptr = content.as_ptr() as *const c_void;
-                    let status = tibco_ems_sys::tibemsBytesMsg_SetBytes(
-                        msg_pointer,
-                        body_ptr,
-                        body_size as u32,
-                    );
-                    match status {
-                        tibems_status::TIBEMS_OK => trace!("tibemsBytesMsg_SetBytes: {status:?}"),
-                        _ => error!("tibemsBytesMsg_SetBytes: {status:?}"),
-                    }
-                }
-            }
-            Message::ObjectMessage(msg) => {
-                let status = tibco_ems_sys::tibemsObjectMsg_Create(&mut msg_pointer);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsObjectMsg_Create: {status:?}"),
-                    _ => error!("tibemsObjectMsg_Create: {status:?}"),
-                }
-                let content = msg.body.clone();
-                let body_size = content.len();
-                let body_ptr = content.as_ptr() as *const c_void;
-                let status = tibco_ems_sys::tibemsObjectMsg_SetObjectBytes(
-                    msg_pointer,
-                    body_ptr,
-                    body_size as u32,
-                );
-                match status {
-                    tibems_status::TIBEMS_OK => {
-                        trace!("tibemsObjectMsg_SetObjectBytes: {status:?}")
-                    }
-                    _ => error!("tibemsObjectMsg_SetObjectBytes: {status:?}"),
-                }
-            }
-            Message::MapMessage(msg) => {
-                let status = tibco_ems_sys::tibemsMapMsg_Create(&mut msg_pointer);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMapMsg_Create: {status:?}"),
-                    _ => error!("tibemsMapMsg_Create: {status:?}"),
-                }
-                for (key, val) in msg.body.clone() {
-                    let c_name = CString::new(key).unwrap();
-                    match val {
-                        TypedValue::Boolean(value) => {
-                            let status = if value {
-                                tibco_ems_sys::tibemsMapMsg_SetBoolean(
-                                    msg_pointer,
-                                    c_name.as_ptr(),
-                                    tibems_bool::TIBEMS_TRUE,
-                                )
-                            } else {
-                                tibco_ems_sys::tibemsMapMsg_SetBoolean(
-                                    msg_pointer,
-                                    c_name.as_ptr(),
-                                    tibems_bool::TIBEMS_FALSE,
-                                )
-                            };
-                            match status {
-                                tibems_status::TIBEMS_OK => {
-                                    trace!("tibemsMapMsg_SetBoolean: {status:?}")
-                                }
-                                _ => error!("tibemsMapMsg_SetBoolean: {status:?}"),
-                            }
-                        }
-                        TypedValue::String(value) => {
-                            let c_value = CString::new(value).unwrap();
-                            let status = tibco_ems_sys::tibemsMapMsg_SetString(
-                                msg_pointer,
-                                c_name.as_ptr(),
-                                c_value.as_ptr(),
-                            );
-                            match status {
-                                tibems_status::TIBEMS_OK => {
-                                    trace!("tibemsMapMsg_SetString: {status:?}")
-                                }
-                                _ => error!("tibemsMapMsg_SetString: {status:?}"),
-                            }
-                        }
-                        TypedValue::Integer(value) => {
-                            let status = tibco_ems_sys::tibemsMapMsg_SetInt(
-                                msg_pointer,
-                                c_name.as_ptr(),
-                                value,
-                            );
-                            match status {
-                                tibems_status::TIBEMS_OK => {
-                                    trace!("tibemsMapMsg_SetInt: {status:?}")
-                                }
-                                _ => error!("tibemsMapMsg_SetInt: {status:?}"),
-                            }
-                        }
-                        TypedValue::Long(value) => {
-                            let status = tibco_ems_sys::tibemsMapMsg_SetLong(
-                                msg_pointer,
-                                c_name.as_ptr(),
-                                value,
-                            );
-                            match status {
-                                tibems_status::TIBEMS_OK => {
-                                    trace!("tibemsMapMsg_SetLong: {status:?}")
-                                }
-                                _ => error!("tibemsMapMsg_SetLong: {status:?}"),
-                            }
-                        }
-                        TypedValue::Float(value) => {
-                            let status = tibco_ems_sys::tibemsMapMsg_SetFloat(
-                                msg_pointer,
-                                c_name.as_ptr(),
-                                value,
-                            );
-                            match status {
-                                tibems_status::TIBEMS_OK => {
-                                    trace!("tibemsMapMsg_SetFloat: {status:?}")
-                                }
-                                _ => error!("tibemsMapMsg_SetFloat: {status:?}"),
-                            }
-                        }
-
-                        TypedValue::Double(value) => {
-                            let status = tibco_ems_sys::tibemsMapMsg_SetDouble(
-                                msg_pointer,
-                                c_name.as_ptr(),
-                                value,
-                            );
-                            match status {
-                                tibems_status::TIBEMS_OK => {
-                                    trace!("tibemsMapMsg_SetDouble: {status:?}")
-                                }
-                                _ => error!("tibemsMapMsg_SetDouble: {status:?}"),
-                            }
-                        }
-                        TypedValue::Binary(_value) => {
-                            //TODO implement
-                            // let status = tibco_ems_sys::tibemsMapMsg_SetBytes(message: usize, name: *const c_char, bytes: *mut c_void, bytesSize: u64)Long(msg, c_name.as_ptr(), value);
-                            // match status {
-                            // tibems_status::TIBEMS_OK => trace!("tibemsMapMsg_SetLong: {:?}",status),
-                            // _ => error!("tibemsMapMsg_SetLong: {:?}",status),
-                            // }
-                        }
-                        _ => {
-                            panic!("missing map message type implementation for {val:?}");
-                        }
-                    }
-                }
-            }
-        }
-        //set header
-        let header = match message {
-            Message::TextMessage(msg) => msg.header.clone(),
-            Message::BytesMessage(msg) => msg.header.clone(),
-            Message::MapMessage(msg) => msg.header.clone(),
-            Message::ObjectMessage(msg) => msg.header.clone(),
-        };
-        if let Some(headers) = header {
-            //look for correlation id
-            if let Some(correlation_id) = headers.get("CorrelationID") {
-                let correlation_id_val = extract!(TypedValue::String(_), correlation_id)
-                    .expect("extract correlation id");
-                let c_correlation_id = CString::new(correlation_id_val.as_str()).unwrap();
-                let status = tibco_ems_sys::tibemsMsg_SetCorrelationID(
-                    msg_pointer,
-                    c_correlation_id.as_ptr(),
-                );
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_SetCorrelationId: {status:?}"),
-                    _ => error!("tibemsMsg_SetCorrelationId: {status:?}"),
-                }
-            }
-            //look for jms type
-            if let Some(jms_type) = headers.get("JMSType") {
-                let jms_type_val =
-                    extract!(TypedValue::String(_), jms_type).expect("extract correlation id");
-                let c_jms_type = CString::new(jms_type_val.as_str()).unwrap();
-                let status = tibco_ems_sys::tibemsMsg_SetType(msg_pointer, c_jms_type.as_ptr());
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_SetType: {status:?}"),
-                    _ => error!("tibemsMsg_SetType: {status:?}"),
-                }
-            }
-            //do other headers (also do correlation id again as custom header)
-            for (key, val) in &headers {
-                let c_name = CString::new(key.to_string()).unwrap();
-                match val {
-                    TypedValue::String(value) => {
-                        let c_val = CString::new(value.as_bytes()).unwrap();
-                        let status = tibco_ems_sys::tibemsMsg_SetStringProperty(
-                            msg_pointer,
-                            c_name.as_ptr(),
-                            c_val.as_ptr(),
-                        );
-                        match status {
-                            tibems_status::TIBEMS_OK => {
-                                trace!("tibemsMsg_SetStringProperty: {status:?}")
-                            }
-                            _ => error!("tibemsMsg_SetStringProperty: {status:?}"),
-                        }
-                    }
-                    TypedValue::Boolean(value) => {
-                        let status = if *value {
-                            tibco_ems_sys::tibemsMsg_SetBooleanProperty(
-                                msg_pointer,
-                                c_name.as_ptr(),
-                                tibems_bool::TIBEMS_TRUE,
-                            )
-                        } else {
-                            tibco_ems_sys::tibemsMsg_SetBooleanProperty(
-                                msg_pointer,
-                                c_name.as_ptr(),
-                                tibems_bool::TIBEMS_FALSE,
-                            )
-                        };
-                        match status {
-                            tibems_status::TIBEMS_OK => {
-                                trace!("tibemsMsg_SetBooleanProperty: {status:?}")
-                            }
-                            _ => error!("tibemsMsg_SetBooleanProperty: {status:?}"),
-                        }
-                    }
-                    TypedValue::Integer(value) => {
-                        let status = tibco_ems_sys::tibemsMsg_SetIntProperty(
-                            msg_pointer,
-                            c_name.as_ptr(),
-                            *value,
-                        );
-                        match status {
-                            tibems_status::TIBEMS_OK => {
-                                trace!("tibemsMsg_SetIntProperty: {status:?}")
-                            }
-                            _ => error!("tibemsMsg_SetIntProperty: {status:?}"),
-                        }
-                    }
-                    TypedValue::Long(value) => {
-                        let status = tibco_ems_sys::tibemsMsg_SetLongProperty(
-                            msg_pointer,
-                            c_name.as_ptr(),
-                            *value,
-                        );
-                        match status {
-                            tibems_status::TIBEMS_OK => {
-                                trace!("tibemsMsg_SetLongProperty: {status:?}")
-                            }
-                            _ => error!("tibemsMsg_SetLongProperty: {status:?}"),
-                        }
-                    }
-                    _ => {
-                        panic!("missing property type implementation for {val:?}");
-                    }
-                }
-            }
-        }
-    }
-    msg_pointer
-}
-
-#[cfg(feature = "ems-sys")]
-fn build_message_from_pointer(msg_pointer: usize) -> Message {
-    let mut msg: Message;
-    let mut header: HashMap<String, TypedValue> = HashMap::new();
-    unsafe {
-        let mut msg_type: tibemsMsgType = tibemsMsgType::TIBEMS_TEXT_MESSAGE;
-        let status = tibco_ems_sys::tibemsMsg_GetBodyType(msg_pointer, &mut msg_type);
-        match status {
-            tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetBodyType: {status:?}"),
-            _ => error!("tibemsMsg_GetBodyType: {status:?}"),
-        }
-        match msg_type {
-            tibemsMsgType::TIBEMS_TEXT_MESSAGE => {
-                let buf_vec: Vec<i8> = vec![0; 0];
-                let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
-                let status = tibco_ems_sys::tibemsTextMsg_GetText(msg_pointer, &buf_ref);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsTextMsg_GetText: {status:?}"),
-                    _ => error!("tibemsTextMsg_GetText: {status:?}"),
-                }
-                let content = CStr::from_ptr(buf_ref).to_str().unwrap();
-                let status = tibco_ems_sys::tibemsMsg_GetMessageID(msg_pointer, &buf_ref);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetMessageID: {status:?}"),
-                    _ => error!("tibemsMsg_GetMessageID: {status:?}"),
-                }
-                let message_id = CStr::from_ptr(buf_ref).to_str().unwrap();
-                header.insert(
-                    "MessageID".to_string(),
-                    TypedValue::String(message_id.to_string()),
-                );
-                msg = Message::TextMessage(TextMessage {
-                    body: content.to_string(),
-                    header: None,
-                    pointer: Some(msg_pointer),
-                    destination: None,
-                    reply_to: None,
-                });
-            }
-            tibemsMsgType::TIBEMS_MAP_MESSAGE => {
-                let buf_vec: Vec<i8> = vec![0; 0];
-                let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
-                let status = tibco_ems_sys::tibemsMsg_GetMessageID(msg_pointer, &buf_ref);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetMessageID: {status:?}"),
-                    _ => error!("tibemsMsg_GetMessageID: {status:?}"),
-                }
-                //admin messages do not have a message id
-                if !buf_vec.is_empty() {
-                    let message_id = CStr::from_ptr(buf_ref).to_str().unwrap();
-                    header.insert(
-                        "MessageID".to_string(),
-                        TypedValue::String(message_id.to_string()),
-                    );
-                }
-                let mut names_pointer: usize = 0;
-                trace!("tibemsMapMsg_GetMapNames");
-                let status =
-                    tibco_ems_sys::tibemsMapMsg_GetMapNames(msg_pointer, &mut names_pointer);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMapMsg_GetMapNames: {status:?}"),
-                    _ => error!("tibemsMapMsg_GetMapNames: {status:?}"),
-                }
-                let mut body_entries: HashMap<String, TypedValue> = HashMap::new();
-                loop {
-                    let buf_vec: Vec<i8> = vec![0; 0];
-                    let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
-                    let status = tibco_ems_sys::tibemsMsgEnum_GetNextName(names_pointer, &buf_ref);
-                    match status {
-                        tibems_status::TIBEMS_OK => {
-                            let header_name = CStr::from_ptr(buf_ref).to_str().unwrap();
-                            trace!("getting value for property: {}", header_name);
-                            let mut val_buf_vec: Vec<i8> = vec![0; 0];
-                            let mut val_buf_ref: *mut std::os::raw::c_char =
-                                val_buf_vec.as_mut_ptr();
-                            let status = tibco_ems_sys::tibemsMapMsg_GetString(
-                                msg_pointer,
-                                buf_ref,
-                                &mut val_buf_ref,
-                            );
-                            match status {
-                                tibems_status::TIBEMS_OK => {
-                                    trace!("tibemsMapMsg_GetString: {status:?}");
-                                    if !val_buf_ref.is_null() {
-                                        let header_value =
-                                            CStr::from_ptr(val_buf_ref).to_str().unwrap();
-                                        body_entries.insert(
-                                            header_name.to_string(),
-                                            TypedValue::String(header_value.to_string()),
-                                        );
-                                    }
-                                }
-                                tibems_status::TIBEMS_CONVERSION_FAILED => {
-                                    //it must be a map msg inside
-                                    let mut msg2: usize = 0;
-                                    let status = tibco_ems_sys::tibemsMapMsg_GetMapMsg(
-                                        msg_pointer,
-                                        buf_ref,
-                                        &mut msg2,
-                                    );
-                                    match status {
-                                        tibems_status::TIBEMS_CONVERSION_FAILED => {
-                                            //it must be something binary, ingore it for now
-                                            trace!(
-                                                "tibemsMapMsg_GetMapMsg: ignoring unkown content"
-                                            );
-                                        }
-                                        tibems_status::TIBEMS_OK => {
-                                            trace!("tibemsMapMsg_GetMapMsg: {status:?}");
-                                            let mut raw_message = build_message_from_pointer(msg2);
-                                            match &mut raw_message {
-                                                Message::TextMessage(_msg) => {}
-                                                Message::ObjectMessage(_msg) => {}
-                                                Message::BytesMessage(_msg) => {}
-                                                Message::MapMessage(msg) => {
-                                                    msg.pointer = None;
-                                                    body_entries.insert(
-                                                        header_name.to_string(),
-                                                        TypedValue::Map(msg.clone()),
-                                                    );
-                                                }
-                                            }
-                                        }
-                                        _ => error!("tibemsMapMsg_GetMapMsg: {status:?}"),
-                                    }
-                                }
-                                _ => error!("tibemsMapMsg_GetString: {status:?}"),
-                            }
-                        }
-                        tibems_status::TIBEMS_NOT_FOUND => {
-                            break;
-                        }
-                        _ => {
-                            println!("tibemsMsgEnum_GetNextName: {status:?}");
-                            break;
-                        }
-                    }
-                }
-                let status = tibco_ems_sys::tibemsMsgEnum_Destroy(names_pointer);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMsgEnum_Destroy: {status:?}"),
-                    _ => error!("tibemsMsgEnum_Destroy: {status:?}"),
-                }
-                msg = Message::MapMessage(MapMessage {
-                    body: body_entries,
-                    header: None,
-                    pointer: Some(msg_pointer),
-                    destination: None,
-                    reply_to: None,
-                });
-            }
-            tibemsMsgType::TIBEMS_BYTES_MESSAGE => {
-                let buf_vec: Vec<i8> = vec![0; 0];
-                let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
-                let status = tibco_ems_sys::tibemsMsg_GetMessageID(msg_pointer, &buf_ref);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetMessageID: {status:?}"),
-                    _ => error!("tibemsMsg_GetMessageID: {status:?}"),
-                }
-                let message_id = CStr::from_ptr(buf_ref).to_str().unwrap();
-                header.insert(
-                    "MessageID".to_string(),
-                    TypedValue::String(message_id.to_string()),
-                );
-                //check body length
-                let mut body_length: i32 = 0;
-                let mut body_value: Vec<u8> = vec![0; 0];
-                let status =
-                    tibco_ems_sys::tibemsBytesMsg_GetBodyLength(msg_pointer, &mut body_length);
-                match status {
-                    tibems_status::TIBEMS_OK => {
-                        trace!("tibemsBytesMsg_GetBodyLength: {status:?}");
-                        if body_length > 0 {
-                            //extract body
-                            let buf_vec: Vec<u8> = vec![0; 0];
-                            let buf_ref: *const std::os::raw::c_uchar = buf_vec.as_ptr();
-                            let mut result_size: u32 = 0;
-                            let status = tibco_ems_sys::tibemsBytesMsg_GetBytes(
-                                msg_pointer,
-                                &buf_ref,
-                                &mut result_size,
-                            );
-                            match status {
-                                tibems_status::TIBEMS_OK => {
-                                    trace!("tibemsBytesMsg_GetBytes: {status:?}")
-                                }
-                                _ => error!("tibemsBytesMsg_GetBytes: {status:?}"),
-                            }
-                            let slice = core::slice::from_raw_parts(buf_ref, result_size as usize);
-                            body_value = slice.to_vec();
-                        }
-                    }
-                    _ => error!("tibemsBytesMsg_GetBodyLength: {status:?}"),
-                }
-                msg = Message::BytesMessage(BytesMessage {
-                    body: body_value,
-                    header: None,
-                    pointer: Some(msg_pointer),
-                    destination: None,
-                    reply_to: None,
-                });
-            }
-            tibemsMsgType::TIBEMS_OBJECT_MESSAGE => {
-                let buf_vec: Vec<i8> = vec![0; 0];
-                let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
-                let status = tibco_ems_sys::tibemsMsg_GetMessageID(msg_pointer, &buf_ref);
-                match status {
-                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetMessageID: {status:?}"),
-                    _ => error!("tibemsMsg_GetMessageID: {status:?}"),
-                }
-                let message_id = CStr::from_ptr(buf_ref).to_str().unwrap();
-                header.insert(
-                    "MessageID".to_string(),
-                    TypedValue::String(message_id.to_string()),
-                );
-                //extract body
-                let buf_vec: Vec<u8> = vec![0; 0];
-                let buf_ref: *const std::os::raw::c_uchar = buf_vec.as_ptr();
-                let mut result_size: u32 = 0;
-                let status = tibco_ems_sys::tibemsObjectMsg_GetObjectBytes(
-                    msg_pointer,
-                    &buf_ref,
-                    &mut result_size,
-                );
-                match status {
-                    tibems_status::TIBEMS_OK => {
-                        trace!("tibemsObjectMsg_GetObjectBytes: {status:?}")
-                    }
-                    _ => error!("tibemsObjectMsg_GetObjectBytes: {status:?}"),
-                }
-                let slice = core::slice::from_raw_parts(buf_ref, result_size as usize);
-                msg = Message::ObjectMessage(ObjectMessage {
-                    body: slice.to_vec(),
-                    header: None,
-                    pointer: Some(msg_pointer),
-                    destination: None,
-                    reply_to: None,
-                });
-            }
-            _ => {
-                //unknown
-                panic!("BodyType {msg_type:?} not implemented");
-            }
-        }
-        //add correlation id to header
-        let buf_vec: Vec<i8> = vec![0; 0];
-        let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
-        let status = tibco_ems_sys::tibemsMsg_GetCorrelationID(msg_pointer, &buf_ref);
-        match status {
-            tibems_status::TIBEMS_OK => {
-                trace!("tibemsMsg_GetCorrelationID: {status:?}");
-                // check for null pointer (when no correlation id was set)
-                if !buf_ref.is_null() {
-                    let correlation_id = CStr::from_ptr(buf_ref).to_str().unwrap();
-                    header.insert(
-                        "CorrelationID".to_string(),
-                        TypedValue::String(correlation_id.to_string()),
-                    );
-                }
-            }
-            _ => trace!("tibemsMsg_GetCorrelationID: {status:?}"),
-        }
-        // fetch header
-        let mut header_enumeration: usize = 0;
-        let status =
-            tibco_ems_sys::tibemsMsg_GetPropertyNames(msg_pointer, &mut header_enumeration);
-        match status {
-            tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetPropertyNames: {status:?}"),
-            _ => error!("tibemsMsg_GetPropertyNames: {status:?}"),
-        }
-        loop {
-            let buf_vec: Vec<i8> = vec![0; 0];
-            let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
-            let status = tibco_ems_sys::tibemsMsgEnum_GetNextName(header_enumeration, &buf_ref);
-            match status {
-                tibems_status::TIBEMS_OK => {
-                    let header_name = CStr::from_ptr(buf_ref).to_str().unwrap();
-                    let val_buf_vec: Vec<i8> = vec![0; 0];
-                    let val_buf_ref: *const std::os::raw::c_char = val_buf_vec.as_ptr();
-                    let mut bool_result: tibems_bool = tibems_bool::TIBEMS_TRUE;
-                    //check for ems compress header
-                    if header_name == "JMS_TIBCO_COMPRESS" {
-                        let status = tibco_ems_sys::tibemsMsg_GetBooleanProperty(
-                            msg_pointer,
-                            buf_ref,
-                            &mut bool_result,
-                        );
-                        match status {
-                            tibems_status::TIBEMS_OK => {
-                                trace!("tibemsMsg_GetBooleanProperty: {status:?}");
-                                let value = match bool_result {
-                                    tibems_bool::TIBEMS_TRUE => true,
-                                    tibems_bool::TIBEMS_FALSE => false,
-                                };
-                                header.insert(header_name.to_string(), TypedValue::Boolean(value));
-                            }
-                            _ => error!("tibemsMsg_GetBooleanProperty: {status:?}"),
-                        }
-                    } else {
-                        let status = tibco_ems_sys::tibemsMsg_GetStringProperty(
-                            msg_pointer,
-                            buf_ref,
-                            &val_buf_ref,
-                        );
-                        match status {
-                            tibems_status::TIBEMS_OK => {
-                                trace!("tibemsMsg_GetStringProperty: {status:?}")
-                            }
-                            _ => error!("tibemsMsg_GetStringProperty: {status:?}"),
-                        }
-                        let header_value = CStr::from_ptr(val_buf_ref).to_str().unwrap();
-                        header.insert(
-                            header_name.to_string(),
-                            TypedValue::String(header_value.to_string()),
-                        );
-                    }
-                }
-                tibems_status::TIBEMS_NOT_FOUND => {
-                    break;
-                }
-                _ => {
-                    println!("tibemsMsgEnum_GetNextName: {status:?}");
-                    break;
-                }
-            }
-        }
-        let status = tibco_ems_sys::tibemsMsgEnum_Destroy(header_enumeration);
-        match status {
-            tibems_status::TIBEMS_OK => trace!("tibemsMsgEnum_Destroy: {status:?}"),
-            _ => error!("tibemsMsgEnum_Destroy: {status:?}"),
-        }
-        //add JMSType to header
-        let val_buf_vec: Vec<i8> = vec![0; 0];
-        let val_buf_ref: *const std::os::raw::c_char = val_buf_vec.as_ptr();
-        let status = tibco_ems_sys::tibemsMsg_GetType(msg_pointer, &val_buf_ref);
-        match status {
-            tibems_status::TIBEMS_OK => {
-                trace!("tibemsMsg_GetType: {status:?}");
-                // check for null pointer (when no correlation id was set)
-                if !val_buf_ref.is_null() {
-                    let header_value = CStr::from_ptr(val_buf_ref).to_str().unwrap();
-                    if !header_value.is_empty() {
-                        header.insert(
-                            "JMSType".to_string(),
-                            TypedValue::String(header_value.to_string()),
-                        );
-                    }
-                }
-            }
-            _ => error!("tibemsMsg_GetType: {status:?}"),
-        }
-        //add header to message
-        match &mut msg {
-            Message::TextMessage(msg) => msg.header = Some(header),
-            Message::BytesMessage(msg) => msg.header = Some(header),
-            Message::MapMessage(msg) => msg.header = Some(header),
-            Message::ObjectMessage(msg) => msg.header = Some(header),
-        }
-        // look for JMSDestination header
-        let mut jms_destination: usize = 0;
-        let status = tibco_ems_sys::tibemsMsg_GetDestination(msg_pointer, &mut jms_destination);
-        match status {
-            tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetDestination: {status:?}"),
-            _ => error!("tibemsMsg_GetDestination: {status:?}"),
-        }
-        if jms_destination != 0 {
-            //has a destination
-            let mut destination_type = tibemsDestinationType::TIBEMS_UNKNOWN;
-            let status =
-                tibco_ems_sys::tibemsDestination_GetType(jms_destination, &mut destination_type);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsDestination_GetType: {status:?}"),
-                _ => error!("tibemsDestination_GetType: {status:?}"),
-            }
-            let buf_size = 1024;
-            let buf_vec: Vec<i8> = vec![0; buf_size];
-            let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
-            let status =
-                tibco_ems_sys::tibemsDestination_GetName(jms_destination, buf_ref, buf_size);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsDestination_GetName: {status:?}"),
-                _ => error!("tibemsDestination_GetName: {status:?}"),
-            }
-            let destination_name: String = CStr::from_ptr(buf_ref).to_str().unwrap().to_string();
-            let jms_destination_obj: Option<Destination> = match destination_type {
-                tibemsDestinationType::TIBEMS_QUEUE => Some(Destination::Queue(destination_name)),
-                tibemsDestinationType::TIBEMS_TOPIC => Some(Destination::Topic(destination_name)),
-                _ => {
-                    //ignore unknown type
-                    None
-                }
-            };
-            //add replyTo to message
-            match &mut msg {
-                Message::TextMessage(msg) => msg.destination = jms_destination_obj,
-                Message::BytesMessage(msg) => msg.destination = jms_destination_obj,
-                Message::MapMessage(msg) => msg.destination = jms_destination_obj,
-                Message::ObjectMessage(msg) => msg.destination = jms_destination_obj,
-            }
-        }
-        // look for replyTo header
-        let mut reply_destination: usize = 0;
-        let status = tibco_ems_sys::tibemsMsg_GetReplyTo(msg_pointer, &mut reply_destination);
-        match status {
-            tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetReplyTo: {status:?}"),
-            _ => error!("tibemsMsg_GetReplyTo: {status:?}"),
-        }
-        if reply_destination != 0 {
-            //has a destination
-            let mut destination_type = tibemsDestinationType::TIBEMS_UNKNOWN;
-            let status =
-                tibco_ems_sys::tibemsDestination_GetType(reply_destination, &mut destination_type);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsDestination_GetType: {status:?}"),
-                _ => error!("tibemsDestination_GetType: {status:?}"),
-            }
-            let buf_size = 1024;
-            let buf_vec: Vec<i8> = vec![0; buf_size];
-            let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
-            let status =
-                tibco_ems_sys::tibemsDestination_GetName(reply_destination, buf_ref, buf_size);
-            match status {
-                tibems_status::TIBEMS_OK => trace!("tibemsDestination_GetName: {status:?}"),
-                _ => error!("tibemsDestination_GetName: {status:?}"),
-            }
-            let destination_name: String = CStr::from_ptr(buf_ref).to_str().unwrap().to_string();
-            let reply_destination_obj: Option<Destination> = match destination_type {
-                tibemsDestinationType::TIBEMS_QUEUE => Some(Destination::Queue(destination_name)),
-                tibemsDestinationType::TIBEMS_TOPIC => Some(Destination::Topic(destination_name)),
-                _ => {
-                    //ignore unknown type
-                    None
-                }
-            };
-            //add replyTo to message
-            match &mut msg {
-                Message::TextMessage(msg) => msg.reply_to = reply_destination_obj,
-                Message::BytesMessage(msg) => msg.reply_to = reply_destination_obj,
-                Message::MapMessage(msg) => msg.reply_to = reply_destination_obj,
-                Message::ObjectMessage(msg) => msg.reply_to = reply_destination_obj,
-            }
-        }
-    }
-    msg
-}
-
 // Tests with Private Fields
 
 #[cfg(test)]
@@ -2380,4 +828,27 @@ mod private_field_tests {
         // Ensure that the cloned consumer is equal to the original consumer
         assert_eq!(cloned_consumer, consumer);
     }
+
+    #[test]
+    fn test_connection_factory_is_closed_with_last_clone() {
+        let broker = mock::MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let factory = admin::FactoryInfo {
+            name: "DropFactory".to_string(),
+            url: broker.url().to_string(),
+            ..Default::default()
+        };
+        admin::create_factory(&conn.session().unwrap(), &factory).unwrap();
+        let factory =
+            lookup_factory_with_backend(broker.clone(), broker.url(), "DropFactory").unwrap();
+        let pointer = *factory.pointer;
+
+        let cloned_factory = factory.clone();
+        drop(factory);
+        assert!(backend::lookup(pointer).is_some());
+        assert!(cloned_factory.connect("app", "").is_ok());
+
+        drop(cloned_factory);
+        assert!(backend::lookup(pointer).is_none());
+    }
 }
@@ -0,0 +1,1162 @@
+//! Emulation of the admin queue.
+//!
+//! Requests sent to `$sys.admin` are not queued, they are answered from a catalogue
+//! of destinations, bridges, routes, factories, users, groups and permissions and from the open
+//! connections, consumers and producers, so the functions of `tibco_ems::admin` can be used
+//! against the mock broker. Replies carry the return code `rc`, one of the `RC_*`
+//! codes of `tibco_ems::admin`, and an error text `em` in their header.
+
+use super::{
+    topic_matches, ConnectionState, ConsumerState, FactoryState, Source, State, Subscription,
+    SubscriptionState,
+};
+use crate::admin::{
+    AdminCommands, BridgeInfo, Principal, ServerState, RC_ALREADY_EXISTS, RC_INVALID_REQUEST,
+    RC_NOT_FOUND, RC_OK,
+};
+use crate::selector::Selector;
+use crate::{Destination, MapMessage, Message, TypedValue};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Instant;
+
+pub(super) const ADMIN_QUEUE_NAME: &str = "$sys.admin";
+
+const DESTINATION_TYPE_QUEUE: i32 = 1;
+const DESTINATION_TYPE_TOPIC: i32 = 2;
+
+/// version reported by the mock server
+const SERVER_VERSION: &str = "10.2.1";
+
+/// administrative state of the broker
+#[derive(Debug)]
+pub(super) struct Catalogue {
+    queues: BTreeMap<String, Properties>,
+    topics: BTreeMap<String, Properties>,
+    bridges: Vec<BridgeInfo>,
+    users: BTreeMap<String, User>,
+    groups: BTreeMap<String, Group>,
+    acls: Vec<Acl>,
+    routes: BTreeMap<String, Properties>,
+    factories: BTreeMap<String, Properties>,
+    pub(super) server_state: ServerState,
+    pub(super) ignore_cursor: bool,
+    started: Instant,
+}
+
+#[derive(Debug)]
+struct User {
+    description: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Debug)]
+struct Group {
+    description: Option<String>,
+    members: BTreeSet<String>,
+}
+
+#[derive(Debug)]
+struct Acl {
+    destination: Destination,
+    principal: Principal,
+    permissions: BTreeSet<String>,
+}
+
+impl Default for Catalogue {
+    fn default() -> Self {
+        Catalogue {
+            queues: BTreeMap::new(),
+            topics: BTreeMap::new(),
+            bridges: Vec::new(),
+            users: BTreeMap::new(),
+            groups: BTreeMap::new(),
+            acls: Vec::new(),
+            routes: BTreeMap::new(),
+            factories: BTreeMap::new(),
+            server_state: ServerState::Active,
+            ignore_cursor: false,
+            started: Instant::now(),
+        }
+    }
+}
+
+/// properties of a destination as sent by the create request
+type Properties = HashMap<String, TypedValue>;
+
+/// return code and error text of a failed request
+type Failure = (i32, String);
+
+type Response = Result<HashMap<String, TypedValue>, Failure>;
+
+/// answers an admin request, the reply is None for requests which are not map messages
+pub(super) fn respond(state: &mut State, request: &Message) -> Option<Message> {
+    let Message::MapMessage(request) = request else {
+        return None;
+    };
+    let code = match request.header.as_ref().and_then(|h| h.get("code")) {
+        Some(TypedValue::Integer(code)) => *code,
+        _ => -1,
+    };
+    let response = match code {
+        c if c == AdminCommands::CreateDestination as i32 => create_destination(state, request),
+        c if c == AdminCommands::DeleteDestination as i32 => delete_destination(state, request),
+        c if c == AdminCommands::AlterDestination as i32 => alter_destination(state, request),
+        c if c == AdminCommands::ListDestination as i32 => list_destinations(state, request),
+        c if c == AdminCommands::PurgeDestination as i32 => purge_destination(state, request),
+        c if c == AdminCommands::PurgeDurable as i32 => purge_durable(state, request),
+        c if c == AdminCommands::PurgeAllQueues as i32 => purge_all_queues(state, request),
+        c if c == AdminCommands::ListDurables as i32 => list_durables(state, request),
+        c if c == AdminCommands::CreateDurable as i32 => create_durable(state, request),
+        c if c == AdminCommands::DeleteDurable as i32 => delete_durable(state, request),
+        c if c == AdminCommands::CreateBridge as i32 => create_bridge(state, request),
+        c if c == AdminCommands::DeleteBridge as i32 => delete_bridge(state, request),
+        c if c == AdminCommands::ListBridges as i32 => list_bridges(state, request),
+        c if c == AdminCommands::CreateUser as i32 => create_user(state, request),
+        c if c == AdminCommands::DeleteUser as i32 => delete_user(state, request),
+        c if c == AdminCommands::SetPassword as i32 => set_password(state, request),
+        c if c == AdminCommands::ListUsers as i32 => list_users(state),
+        c if c == AdminCommands::CreateGroup as i32 => create_group(state, request),
+        c if c == AdminCommands::DeleteGroup as i32 => delete_group(state, request),
+        c if c == AdminCommands::ListGroups as i32 => list_groups(state),
+        c if c == AdminCommands::AddUserToGroup as i32 => change_membership(state, request, true),
+        c if c == AdminCommands::RemoveUserFromGroup as i32 => {
+            change_membership(state, request, false)
+        }
+        c if c == AdminCommands::GrantPermissions as i32 => {
+            change_permissions(state, request, true)
+        }
+        c if c == AdminCommands::RevokePermissions as i32 => {
+            change_permissions(state, request, false)
+        }
+        c if c == AdminCommands::ListAcls as i32 => list_acls(state, request),
+        c if c == AdminCommands::ListConnections as i32 => list_connections(state, request),
+        c if c == AdminCommands::DestroyConnection as i32 => destroy_connection(state, request),
+        c if c == AdminCommands::ListConsumers as i32 => list_consumers(state, request),
+        c if c == AdminCommands::ListProducers as i32 => list_producers(state, request),
+        c if c == AdminCommands::GetStateInfo as i32 => Ok(HashMap::from([(
+            "state".to_string(),
+            TypedValue::String(state.catalogue.server_state.code().to_string()),
+        )])),
+        c if c == AdminCommands::GetServerInfo as i32 => server_info(state),
+        c if c == AdminCommands::ListRoutes as i32 => list_routes(state),
+        c if c == AdminCommands::CreateRoute as i32 => create_route(state, request),
+        c if c == AdminCommands::DeleteRoute as i32 => delete_route(state, request),
+        c if c == AdminCommands::ListFactories as i32 => list_factories(state),
+        c if c == AdminCommands::CreateFactory as i32 => create_factory(state, request),
+        c if c == AdminCommands::DeleteFactory as i32 => delete_factory(state, request),
+        code => Err((
+            RC_INVALID_REQUEST,
+            format!("unsupported admin command {code}"),
+        )),
+    };
+    let mut header = HashMap::new();
+    let body = match response {
+        Ok(body) => {
+            header.insert("rc".to_string(), TypedValue::Integer(RC_OK));
+            body
+        }
+        Err((rc, message)) => {
+            header.insert("rc".to_string(), TypedValue::Integer(rc));
+            header.insert("em".to_string(), TypedValue::String(message));
+            HashMap::new()
+        }
+    };
+    Some(Message::MapMessage(MapMessage {
+        body,
+        header: Some(header),
+        ..Default::default()
+    }))
+}
+
+fn string(request: &MapMessage, name: &str) -> Result<String, Failure> {
+    match request.body.get(name) {
+        Some(TypedValue::String(val)) => Ok(val.clone()),
+        _ => Err((RC_INVALID_REQUEST, format!("missing field {name}"))),
+    }
+}
+
+fn integer(request: &MapMessage, name: &str) -> Result<i32, Failure> {
+    match request.body.get(name) {
+        Some(TypedValue::Integer(val)) => Ok(*val),
+        _ => Err((RC_INVALID_REQUEST, format!("missing field {name}"))),
+    }
+}
+
+fn destination(request: &MapMessage, kind: &str, name: &str) -> Result<Destination, Failure> {
+    let name = string(request, name)?;
+    match integer(request, kind)? {
+        DESTINATION_TYPE_QUEUE => Ok(Destination::Queue(name)),
+        DESTINATION_TYPE_TOPIC => Ok(Destination::Topic(name)),
+        other => Err((
+            RC_INVALID_REQUEST,
+            format!("invalid destination type {other}"),
+        )),
+    }
+}
+
+impl Catalogue {
+    /// settings of a connection factory, None if there is no factory with this name
+    pub(super) fn factory(&self, name: &str) -> Option<FactoryState> {
+        let properties = self.factories.get(name)?;
+        let client_id = match properties.get("cid") {
+            Some(TypedValue::String(client_id)) => Some(client_id.clone()),
+            _ => None,
+        };
+        Some(FactoryState { client_id })
+    }
+
+    fn destinations(
+        &mut self,
+        destination_type: i32,
+    ) -> Result<&mut BTreeMap<String, Properties>, Failure> {
+        match destination_type {
+            DESTINATION_TYPE_QUEUE => Ok(&mut self.queues),
+            DESTINATION_TYPE_TOPIC => Ok(&mut self.topics),
+            other => Err((
+                RC_INVALID_REQUEST,
+                format!("invalid destination type {other}"),
+            )),
+        }
+    }
+}
+
+fn create_destination(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "dn")?;
+    let destinations = state.catalogue.destinations(integer(request, "dt")?)?;
+    if destinations.contains_key(&name) {
+        return Err((
+            RC_ALREADY_EXISTS,
+            format!("destination {name} already exists"),
+        ));
+    }
+    let mut properties = request.body.clone();
+    properties.remove("dn");
+    properties.remove("dt");
+    destinations.insert(name, properties);
+    Ok(HashMap::new())
+}
+
+fn alter_destination(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "dn")?;
+    let destinations = state.catalogue.destinations(integer(request, "dt")?)?;
+    let Some(properties) = destinations.get_mut(&name) else {
+        return Err((RC_NOT_FOUND, format!("destination {name} does not exist")));
+    };
+    if let Ok(cleared) = string(request, "clr") {
+        for field in cleared.split(',') {
+            properties.remove(field);
+        }
+    }
+    for (field, value) in &request.body {
+        if !matches!(field.as_str(), "dn" | "dt" | "clr") {
+            properties.insert(field.clone(), value.clone());
+        }
+    }
+    Ok(HashMap::new())
+}
+
+fn delete_destination(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "dn")?;
+    let destination_type = integer(request, "dt")?;
+    let destinations = state.catalogue.destinations(destination_type)?;
+    if destinations.remove(&name).is_none() {
+        return Err((RC_NOT_FOUND, format!("destination {name} does not exist")));
+    }
+    // pending messages are lost together with the destination
+    if destination_type == DESTINATION_TYPE_QUEUE {
+        state.queues.remove(&name);
+    } else {
+        state.subscriptions.retain(|sub, _| sub.topic() != name);
+    }
+    Ok(HashMap::new())
+}
+
+fn list_destinations(state: &mut State, request: &MapMessage) -> Response {
+    let destination_type = integer(request, "dt")?;
+    let pattern = string(request, "pattern").unwrap_or_else(|_| ">".to_string());
+    let cursor = string(request, "cursor")
+        .ok()
+        .filter(|_| !state.catalogue.ignore_cursor);
+    let first = integer(request, "first").unwrap_or(i32::MAX).max(1) as usize;
+    // names are sorted, a page starts after the cursor
+    let names: Vec<String> = state
+        .catalogue
+        .destinations(destination_type)?
+        .keys()
+        .filter(|name| cursor.as_ref().is_none_or(|cursor| *name > cursor))
+        .filter(|name| topic_matches(&pattern, name))
+        .take(first)
+        .cloned()
+        .collect();
+    let mut body = HashMap::new();
+    for name in names {
+        let properties = &state.catalogue.destinations(destination_type)?[&name];
+        // like the server, report defaults for properties which were never set
+        let mut defaults = vec![
+            "mb", "mm", "op", "pf", "expy", "failsafe", "secure", "global", "sname", "snameenf",
+        ];
+        if destination_type == DESTINATION_TYPE_QUEUE {
+            defaults.push("rdd");
+        }
+        let mut info: HashMap<String, String> = defaults
+            .iter()
+            .map(|field| (field.to_string(), "0".to_string()))
+            .collect();
+        for (field, value) in properties {
+            info.insert(field.clone(), render(value));
+        }
+        info.insert("inct".to_string(), "0".to_string());
+        info.insert("outct".to_string(), "0".to_string());
+        if destination_type == DESTINATION_TYPE_QUEUE {
+            let pending = state.queues.get(&name).map(|q| q.len()).unwrap_or(0)
+                + state
+                    .deliveries
+                    .values()
+                    .filter(|d| d.source == Source::Queue(name.clone()))
+                    .count();
+            let consumers = state
+                .consumers
+                .values()
+                .filter(|c| c.source == Source::Queue(name.clone()))
+                .count();
+            info.insert("nm".to_string(), pending.to_string());
+            info.insert("cc".to_string(), consumers.to_string());
+        } else {
+            let subscriptions = state
+                .subscriptions
+                .iter()
+                .filter(|(sub, _)| sub.topic() == name);
+            let (mut durables, mut subscribers, mut pending) = (0, 0, 0);
+            for (sub, subscription) in subscriptions {
+                if let Subscription::Durable { .. } = sub {
+                    durables += 1;
+                }
+                subscribers += 1;
+                pending += subscription.messages.len();
+            }
+            info.insert("cd".to_string(), durables.to_string());
+            info.insert("sc".to_string(), subscribers.to_string());
+            info.insert("nm".to_string(), pending.to_string());
+        }
+        let info = MapMessage {
+            body: info
+                .into_iter()
+                .map(|(field, value)| (field, TypedValue::String(value)))
+                .collect(),
+            ..Default::default()
+        };
+        body.insert(name, TypedValue::Map(info));
+    }
+    Ok(body)
+}
+
+fn purged(count: usize) -> Response {
+    Ok(HashMap::from([(
+        "nm".to_string(),
+        TypedValue::Long(count as i64),
+    )]))
+}
+
+fn purge_destination(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "dn")?;
+    let destination_type = integer(request, "dt")?;
+    let known = state
+        .catalogue
+        .destinations(destination_type)?
+        .contains_key(&name);
+    let mut count = 0;
+    if destination_type == DESTINATION_TYPE_QUEUE {
+        match state.queues.get_mut(&name) {
+            Some(messages) => count = messages.drain(..).count(),
+            None if known => {}
+            None => return Err((RC_NOT_FOUND, format!("queue {name} does not exist"))),
+        }
+    } else {
+        let mut found = known;
+        for (sub, subscription) in state.subscriptions.iter_mut() {
+            if sub.topic() == name {
+                found = true;
+                count += subscription.messages.drain(..).count();
+            }
+        }
+        if !found {
+            return Err((RC_NOT_FOUND, format!("topic {name} does not exist")));
+        }
+    }
+    purged(count)
+}
+
+fn purge_durable(state: &mut State, request: &MapMessage) -> Response {
+    let durable = string(request, "dur")?;
+    let subscription = state
+        .subscriptions
+        .iter_mut()
+        .find(|(sub, _)| matches!(sub, Subscription::Durable { name, .. } if *name == durable));
+    match subscription {
+        Some((_, subscription)) => purged(subscription.messages.drain(..).count()),
+        None => Err((RC_NOT_FOUND, format!("durable {durable} does not exist"))),
+    }
+}
+
+fn purge_all_queues(state: &mut State, request: &MapMessage) -> Response {
+    let pattern = string(request, "pattern").unwrap_or_else(|_| ">".to_string());
+    let count = state
+        .queues
+        .iter_mut()
+        .filter(|(name, _)| topic_matches(&pattern, name))
+        .map(|(_, messages)| messages.drain(..).count())
+        .sum();
+    purged(count)
+}
+
+/// admin replies carry all destination properties as strings, flags as 1 or 0
+fn render(value: &TypedValue) -> String {
+    match value {
+        TypedValue::Boolean(true) => "1".to_string(),
+        TypedValue::Boolean(false) => "0".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// writes the type and name of a destination to a reply entry
+fn destination_fields(
+    info: &mut HashMap<String, TypedValue>,
+    type_field: &str,
+    name_field: &str,
+    destination: &Destination,
+) {
+    let (destination_type, name) = match destination {
+        Destination::Queue(name) => (DESTINATION_TYPE_QUEUE, name),
+        Destination::Topic(name) => (DESTINATION_TYPE_TOPIC, name),
+    };
+    info.insert(
+        type_field.to_string(),
+        TypedValue::Integer(destination_type),
+    );
+    info.insert(name_field.to_string(), TypedValue::String(name.clone()));
+}
+
+fn bridge(request: &MapMessage) -> Result<BridgeInfo, Failure> {
+    Ok(BridgeInfo {
+        source: destination(request, "st", "sn")?,
+        target: destination(request, "tt", "tn")?,
+        selector: string(request, "sel").ok(),
+    })
+}
+
+fn create_bridge(state: &mut State, request: &MapMessage) -> Response {
+    let bridge = bridge(request)?;
+    let bridges = &mut state.catalogue.bridges;
+    if bridges
+        .iter()
+        .any(|b| b.source == bridge.source && b.target == bridge.target)
+    {
+        return Err((
+            RC_ALREADY_EXISTS,
+            format!(
+                "bridge {:?}->{:?} already exists",
+                bridge.source, bridge.target
+            ),
+        ));
+    }
+    bridges.push(bridge);
+    Ok(HashMap::new())
+}
+
+fn delete_bridge(state: &mut State, request: &MapMessage) -> Response {
+    let bridge = bridge(request)?;
+    let bridges = &mut state.catalogue.bridges;
+    let count = bridges.len();
+    bridges.retain(|b| b.source != bridge.source || b.target != bridge.target);
+    if bridges.len() == count {
+        return Err((
+            RC_NOT_FOUND,
+            format!(
+                "bridge {:?}->{:?} does not exist",
+                bridge.source, bridge.target
+            ),
+        ));
+    }
+    Ok(HashMap::new())
+}
+
+fn list_bridges(state: &mut State, request: &MapMessage) -> Response {
+    let source = match request.body.contains_key("sn") {
+        true => Some(destination(request, "st", "sn")?),
+        false => None,
+    };
+    let bridges = state
+        .catalogue
+        .bridges
+        .iter()
+        .filter(|b| source.as_ref().is_none_or(|source| b.source == *source));
+    let mut body = HashMap::new();
+    for (index, bridge) in bridges.enumerate() {
+        let mut info = HashMap::new();
+        destination_fields(&mut info, "st", "sn", &bridge.source);
+        destination_fields(&mut info, "tt", "tn", &bridge.target);
+        info.insert(
+            "sel".to_string(),
+            TypedValue::String(bridge.selector.clone().unwrap_or_default()),
+        );
+        let info = MapMessage {
+            body: info,
+            ..Default::default()
+        };
+        body.insert(index.to_string(), TypedValue::Map(info));
+    }
+    Ok(body)
+}
+
+fn create_user(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "un")?;
+    let users = &mut state.catalogue.users;
+    if users.contains_key(&name) {
+        return Err((RC_ALREADY_EXISTS, format!("user {name} already exists")));
+    }
+    users.insert(
+        name,
+        User {
+            description: string(request, "desc").ok(),
+            password: string(request, "pw").ok().filter(|pw| !pw.is_empty()),
+        },
+    );
+    Ok(HashMap::new())
+}
+
+fn delete_user(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "un")?;
+    if state.catalogue.users.remove(&name).is_none() {
+        return Err((RC_NOT_FOUND, format!("user {name} does not exist")));
+    }
+    for group in state.catalogue.groups.values_mut() {
+        group.members.remove(&name);
+    }
+    let principal = Principal::User(name);
+    state
+        .catalogue
+        .acls
+        .retain(|acl| acl.principal != principal);
+    Ok(HashMap::new())
+}
+
+fn set_password(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "un")?;
+    let Some(user) = state.catalogue.users.get_mut(&name) else {
+        return Err((RC_NOT_FOUND, format!("user {name} does not exist")));
+    };
+    user.password = string(request, "pw").ok().filter(|pw| !pw.is_empty());
+    Ok(HashMap::new())
+}
+
+fn list_users(state: &mut State) -> Response {
+    let mut body = HashMap::new();
+    for (name, user) in &state.catalogue.users {
+        let mut info = HashMap::new();
+        if let Some(description) = &user.description {
+            info.insert("desc".to_string(), TypedValue::String(description.clone()));
+        }
+        let info = MapMessage {
+            body: info,
+            ..Default::default()
+        };
+        body.insert(name.clone(), TypedValue::Map(info));
+    }
+    Ok(body)
+}
+
+fn create_group(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "gn")?;
+    if state.catalogue.groups.contains_key(&name) {
+        return Err((RC_ALREADY_EXISTS, format!("group {name} already exists")));
+    }
+    let mut members = BTreeSet::new();
+    for member in string(request, "mbrs").unwrap_or_default().split(',') {
+        let member = member.trim();
+        if member.is_empty() {
+            continue;
+        }
+        if !state.catalogue.users.contains_key(member) {
+            return Err((RC_NOT_FOUND, format!("user {member} does not exist")));
+        }
+        members.insert(member.to_string());
+    }
+    state.catalogue.groups.insert(
+        name,
+        Group {
+            description: string(request, "desc").ok(),
+            members,
+        },
+    );
+    Ok(HashMap::new())
+}
+
+fn delete_group(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "gn")?;
+    if state.catalogue.groups.remove(&name).is_none() {
+        return Err((RC_NOT_FOUND, format!("group {name} does not exist")));
+    }
+    let principal = Principal::Group(name);
+    state
+        .catalogue
+        .acls
+        .retain(|acl| acl.principal != principal);
+    Ok(HashMap::new())
+}
+
+fn list_groups(state: &mut State) -> Response {
+    let mut body = HashMap::new();
+    for (name, group) in &state.catalogue.groups {
+        let mut info = HashMap::new();
+        if let Some(description) = &group.description {
+            info.insert("desc".to_string(), TypedValue::String(description.clone()));
+        }
+        let members: Vec<&str> = group.members.iter().map(String::as_str).collect();
+        info.insert("mbrs".to_string(), TypedValue::String(members.join(",")));
+        let info = MapMessage {
+            body: info,
+            ..Default::default()
+        };
+        body.insert(name.clone(), TypedValue::Map(info));
+    }
+    Ok(body)
+}
+
+fn change_membership(state: &mut State, request: &MapMessage, add: bool) -> Response {
+    let user = string(request, "un")?;
+    let name = string(request, "gn")?;
+    if !state.catalogue.users.contains_key(&user) {
+        return Err((RC_NOT_FOUND, format!("user {user} does not exist")));
+    }
+    let Some(group) = state.catalogue.groups.get_mut(&name) else {
+        return Err((RC_NOT_FOUND, format!("group {name} does not exist")));
+    };
+    if add {
+        if !group.members.insert(user.clone()) {
+            return Err((
+                RC_ALREADY_EXISTS,
+                format!("user {user} is already a member of group {name}"),
+            ));
+        }
+    } else if !group.members.remove(&user) {
+        return Err((
+            RC_NOT_FOUND,
+            format!("user {user} is not a member of group {name}"),
+        ));
+    }
+    Ok(HashMap::new())
+}
+
+/// permissions which only apply to one kind of destination
+const QUEUE_PERMISSIONS: [&str; 3] = ["receive", "send", "browse"];
+const TOPIC_PERMISSIONS: [&str; 4] = ["publish", "subscribe", "durable", "use_durable"];
+const ADMIN_PERMISSIONS: [&str; 4] = ["create", "delete", "modify", "purge"];
+
+fn principal(state: &State, request: &MapMessage) -> Result<Principal, Failure> {
+    if let Ok(user) = string(request, "un") {
+        if !state.catalogue.users.contains_key(&user) {
+            return Err((RC_NOT_FOUND, format!("user {user} does not exist")));
+        }
+        Ok(Principal::User(user))
+    } else if let Ok(group) = string(request, "gn") {
+        if !state.catalogue.groups.contains_key(&group) {
+            return Err((RC_NOT_FOUND, format!("group {group} does not exist")));
+        }
+        Ok(Principal::Group(group))
+    } else {
+        Err((RC_INVALID_REQUEST, "missing field un or gn".to_string()))
+    }
+}
+
+fn permissions(
+    request: &MapMessage,
+    destination: &Destination,
+) -> Result<BTreeSet<String>, Failure> {
+    let applicable: &[&str] = match destination {
+        Destination::Queue(_) => &QUEUE_PERMISSIONS,
+        Destination::Topic(_) => &TOPIC_PERMISSIONS,
+    };
+    let mut permissions = BTreeSet::new();
+    for permission in string(request, "perm")?.split(',').map(str::trim) {
+        if permission.is_empty() {
+            continue;
+        }
+        if !applicable.contains(&permission) && !ADMIN_PERMISSIONS.contains(&permission) {
+            return Err((
+                RC_INVALID_REQUEST,
+                format!("permission {permission} does not apply to {destination:?}"),
+            ));
+        }
+        permissions.insert(permission.to_string());
+    }
+    Ok(permissions)
+}
+
+fn change_permissions(state: &mut State, request: &MapMessage, grant: bool) -> Response {
+    let destination = destination(request, "dt", "dn")?;
+    let principal = principal(state, request)?;
+    let permissions = permissions(request, &destination)?;
+    let acls = &mut state.catalogue.acls;
+    let index = acls
+        .iter()
+        .position(|acl| acl.destination == destination && acl.principal == principal);
+    match (index, grant) {
+        (Some(index), true) => acls[index].permissions.extend(permissions),
+        (None, true) => acls.push(Acl {
+            destination,
+            principal,
+            permissions,
+        }),
+        (Some(index), false) => {
+            let acl = &mut acls[index];
+            acl.permissions.retain(|p| !permissions.contains(p));
+            if acl.permissions.is_empty() {
+                acls.remove(index);
+            }
+        }
+        (None, false) => {
+            return Err((
+                RC_NOT_FOUND,
+                format!("no permissions of {principal:?} on {destination:?}"),
+            ))
+        }
+    }
+    Ok(HashMap::new())
+}
+
+fn list_acls(state: &mut State, request: &MapMessage) -> Response {
+    let destination = match request.body.contains_key("dn") {
+        true => Some(destination(request, "dt", "dn")?),
+        false => None,
+    };
+    let principal = match string(request, "un") {
+        Ok(user) => Some(Principal::User(user)),
+        Err(_) => string(request, "gn").ok().map(Principal::Group),
+    };
+    let acls = state.catalogue.acls.iter().filter(|acl| {
+        destination.as_ref().is_none_or(|d| acl.destination == *d)
+            && principal.as_ref().is_none_or(|p| acl.principal == *p)
+    });
+    let mut body = HashMap::new();
+    for (index, acl) in acls.enumerate() {
+        let mut info = HashMap::new();
+        destination_fields(&mut info, "dt", "dn", &acl.destination);
+        match &acl.principal {
+            Principal::User(user) => {
+                info.insert("un".to_string(), TypedValue::String(user.clone()))
+            }
+            Principal::Group(group) => {
+                info.insert("gn".to_string(), TypedValue::String(group.clone()))
+            }
+        };
+        let permissions: Vec<&str> = acl.permissions.iter().map(String::as_str).collect();
+        info.insert(
+            "perm".to_string(),
+            TypedValue::String(permissions.join(",")),
+        );
+        let info = MapMessage {
+            body: info,
+            ..Default::default()
+        };
+        body.insert(index.to_string(), TypedValue::Map(info));
+    }
+    Ok(body)
+}
+
+fn durable(topic: &str, request: &MapMessage) -> Result<Subscription, Failure> {
+    // the mock has no client ids, durables are identified by topic and name
+    Ok(Subscription::Durable {
+        topic: topic.to_string(),
+        name: string(request, "dn")?,
+    })
+}
+
+fn list_durables(state: &mut State, request: &MapMessage) -> Response {
+    let pattern = string(request, "pattern").unwrap_or_else(|_| ">".to_string());
+    let mut durables: Vec<(&String, &String, &SubscriptionState)> = state
+        .subscriptions
+        .iter()
+        .filter_map(|(sub, subscription)| match sub {
+            Subscription::Durable { topic, name } if topic_matches(&pattern, topic) => {
+                Some((topic, name, subscription))
+            }
+            _ => None,
+        })
+        .collect();
+    durables.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+    let mut body = HashMap::new();
+    for (index, (topic, name, subscription)) in durables.into_iter().enumerate() {
+        let source = Source::Subscription(Subscription::Durable {
+            topic: topic.clone(),
+            name: name.clone(),
+        });
+        let consumers = state
+            .consumers
+            .values()
+            .filter(|c| c.source == source)
+            .count();
+        let pending = subscription.messages.len()
+            + state
+                .deliveries
+                .values()
+                .filter(|d| d.source == source)
+                .count();
+        let selector = subscription
+            .selector
+            .as_ref()
+            .map(|s| s.as_str().to_string())
+            .unwrap_or_default();
+        let info = MapMessage {
+            body: HashMap::from([
+                ("dn".to_string(), TypedValue::String(name.clone())),
+                ("tn".to_string(), TypedValue::String(topic.clone())),
+                ("sel".to_string(), TypedValue::String(selector)),
+                ("nm".to_string(), TypedValue::String(pending.to_string())),
+                (
+                    "conn".to_string(),
+                    TypedValue::String(((consumers > 0) as i32).to_string()),
+                ),
+                // durables of the mock are shared, they have no client id
+                ("shared".to_string(), TypedValue::String("1".to_string())),
+            ]),
+            ..Default::default()
+        };
+        body.insert(index.to_string(), TypedValue::Map(info));
+    }
+    Ok(body)
+}
+
+fn create_durable(state: &mut State, request: &MapMessage) -> Response {
+    let topic = string(request, "tn")?;
+    let sub = durable(&topic, request)?;
+    if state.subscriptions.contains_key(&sub) {
+        return Err((RC_ALREADY_EXISTS, format!("durable {sub:?} already exists")));
+    }
+    let selector = match string(request, "sel") {
+        Ok(text) if !text.trim().is_empty() => match Selector::parse(&text) {
+            Ok(selector) => Some(selector),
+            Err(err) => return Err((RC_INVALID_REQUEST, err.to_string())),
+        },
+        _ => None,
+    };
+    state.subscriptions.insert(
+        sub,
+        SubscriptionState {
+            selector,
+            ..Default::default()
+        },
+    );
+    Ok(HashMap::new())
+}
+
+fn delete_durable(state: &mut State, request: &MapMessage) -> Response {
+    let topic = string(request, "tn")?;
+    let sub = durable(&topic, request)?;
+    if !state.subscriptions.contains_key(&sub) {
+        return Err((RC_NOT_FOUND, format!("durable {sub:?} does not exist")));
+    }
+    let source = Source::Subscription(sub.clone());
+    if state.consumers.values().any(|c| c.source == source) {
+        return Err((
+            RC_INVALID_REQUEST,
+            format!("durable {sub:?} has active consumers"),
+        ));
+    }
+    state.subscriptions.remove(&sub);
+    Ok(HashMap::new())
+}
+
+fn list_connections(state: &mut State, request: &MapMessage) -> Response {
+    let user = string(request, "un").ok();
+    let mut connections: Vec<(&usize, &ConnectionState)> = state
+        .connections
+        .iter()
+        .filter(|(_, connection)| user.as_ref().is_none_or(|user| connection.user == *user))
+        .collect();
+    connections.sort_by_key(|(id, _)| **id);
+    let mut body = HashMap::new();
+    for (index, (id, connection)) in connections.into_iter().enumerate() {
+        let sessions: Vec<usize> = state
+            .sessions
+            .iter()
+            .filter(|(_, session)| session.connection == *id)
+            .map(|(pointer, _)| *pointer)
+            .collect();
+        let consumers = state
+            .consumers
+            .values()
+            .filter(|consumer| sessions.contains(&consumer.session))
+            .count();
+        let connection_type = if connection.admin { "admin" } else { "jms" };
+        let mut info = MapMessage {
+            body: HashMap::from([
+                ("id".to_string(), TypedValue::String(id.to_string())),
+                (
+                    "un".to_string(),
+                    TypedValue::String(connection.user.clone()),
+                ),
+                (
+                    "type".to_string(),
+                    TypedValue::String(connection_type.to_string()),
+                ),
+                (
+                    "ns".to_string(),
+                    TypedValue::String(sessions.len().to_string()),
+                ),
+                ("nc".to_string(), TypedValue::String(consumers.to_string())),
+                // the mock sends without producers
+                ("np".to_string(), TypedValue::String("0".to_string())),
+                (
+                    "up".to_string(),
+                    TypedValue::String(connection.opened.elapsed().as_millis().to_string()),
+                ),
+            ]),
+            ..Default::default()
+        };
+        if let Some(client_id) = &connection.client_id {
+            info.body
+                .insert("cid".to_string(), TypedValue::String(client_id.clone()));
+        }
+        body.insert(index.to_string(), TypedValue::Map(info));
+    }
+    Ok(body)
+}
+
+fn destroy_connection(state: &mut State, request: &MapMessage) -> Response {
+    let id = match request.body.get("id") {
+        Some(TypedValue::Long(id)) => *id as usize,
+        Some(TypedValue::Integer(id)) => *id as usize,
+        _ => return Err((RC_INVALID_REQUEST, "missing field id".to_string())),
+    };
+    if state.connections.remove(&id).is_none() {
+        return Err((RC_NOT_FOUND, format!("connection {id} does not exist")));
+    }
+    let sessions: Vec<usize> = state
+        .sessions
+        .iter()
+        .filter(|(_, session)| session.connection == id)
+        .map(|(pointer, _)| *pointer)
+        .collect();
+    for session in sessions {
+        state.close_session(session);
+    }
+    Ok(HashMap::new())
+}
+
+/// fields describing the connection of a consumer or producer
+fn client_fields(state: &State, session: usize, info: &mut HashMap<String, TypedValue>) {
+    let Some(connection) = state.sessions.get(&session).map(|s| s.connection) else {
+        return;
+    };
+    info.insert(
+        "conn".to_string(),
+        TypedValue::String(connection.to_string()),
+    );
+    if let Some(connection) = state.connections.get(&connection) {
+        info.insert(
+            "un".to_string(),
+            TypedValue::String(connection.user.clone()),
+        );
+    }
+}
+
+fn list_consumers(state: &mut State, request: &MapMessage) -> Response {
+    let destination = destination(request, "dt", "dn")?;
+    let mut consumers: Vec<(&usize, &ConsumerState)> = state
+        .consumers
+        .iter()
+        .filter(|(_, consumer)| match (&consumer.source, &destination) {
+            (Source::Queue(queue), Destination::Queue(name)) => queue == name,
+            (Source::Subscription(sub), Destination::Topic(name)) => sub.topic() == name,
+            _ => false,
+        })
+        .collect();
+    consumers.sort_by_key(|(id, _)| **id);
+    let mut body = HashMap::new();
+    for (index, (id, consumer)) in consumers.into_iter().enumerate() {
+        let pending_ack = state
+            .deliveries
+            .values()
+            .filter(|d| d.session == consumer.session && d.source == consumer.source)
+            .count();
+        let mut info = HashMap::from([
+            ("id".to_string(), TypedValue::String(id.to_string())),
+            (
+                "nd".to_string(),
+                TypedValue::String(consumer.delivered.to_string()),
+            ),
+            (
+                "npa".to_string(),
+                TypedValue::String(pending_ack.to_string()),
+            ),
+            ("rate".to_string(), TypedValue::String("0".to_string())),
+        ]);
+        destination_fields(&mut info, "dt", "dn", &destination);
+        if let Some(selector) = &consumer.selector {
+            info.insert(
+                "sel".to_string(),
+                TypedValue::String(selector.as_str().to_string()),
+            );
+        }
+        if let Source::Subscription(Subscription::Durable { name, .. }) = &consumer.source {
+            info.insert("dur".to_string(), TypedValue::String(name.clone()));
+        }
+        client_fields(state, consumer.session, &mut info);
+        let info = MapMessage {
+            body: info,
+            ..Default::default()
+        };
+        body.insert(index.to_string(), TypedValue::Map(info));
+    }
+    Ok(body)
+}
+
+fn list_producers(state: &mut State, request: &MapMessage) -> Response {
+    let destination = destination(request, "dt", "dn")?;
+    let mut body = HashMap::new();
+    let producers = state
+        .producers
+        .iter()
+        .filter(|producer| producer.destination == destination);
+    for (index, producer) in producers.enumerate() {
+        let mut info = HashMap::from([
+            (
+                "id".to_string(),
+                TypedValue::String(producer.id.to_string()),
+            ),
+            (
+                "ns".to_string(),
+                TypedValue::String(producer.sent.to_string()),
+            ),
+            ("rate".to_string(), TypedValue::String("0".to_string())),
+        ]);
+        destination_fields(&mut info, "dt", "dn", &destination);
+        client_fields(state, producer.session, &mut info);
+        let info = MapMessage {
+            body: info,
+            ..Default::default()
+        };
+        body.insert(index.to_string(), TypedValue::Map(info));
+    }
+    Ok(body)
+}
+
+fn server_info(state: &mut State) -> Response {
+    let pending: usize = state.queues.values().map(|q| q.len()).sum::<usize>()
+        + state
+            .subscriptions
+            .values()
+            .map(|sub| sub.messages.len())
+            .sum::<usize>()
+        + state.deliveries.len();
+    let uptime = state.catalogue.started.elapsed().as_millis();
+    let info = [
+        ("sn", "mock".to_string()),
+        ("ver", SERVER_VERSION.to_string()),
+        ("state", state.catalogue.server_state.code().to_string()),
+        ("up", uptime.to_string()),
+        ("cc", state.connections.len().to_string()),
+        // the mock has no limits
+        ("mc", "0".to_string()),
+        ("sc", state.sessions.len().to_string()),
+        ("nm", pending.to_string()),
+        ("mmm", "0".to_string()),
+        ("imr", "0".to_string()),
+        ("omr", "0".to_string()),
+    ];
+    Ok(info
+        .into_iter()
+        .map(|(field, value)| (field.to_string(), TypedValue::String(value)))
+        .collect())
+}
+
+fn create_route(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "rn")?;
+    let url = string(request, "url")?;
+    if name.is_empty() || url.is_empty() {
+        return Err((
+            RC_INVALID_REQUEST,
+            "route name and url are required".to_string(),
+        ));
+    }
+    if state.catalogue.routes.contains_key(&name) {
+        return Err((RC_ALREADY_EXISTS, format!("route {name} already exists")));
+    }
+    let mut properties = request.body.clone();
+    properties.remove("rn");
+    state.catalogue.routes.insert(name, properties);
+    Ok(HashMap::new())
+}
+
+fn delete_route(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "rn")?;
+    if state.catalogue.routes.remove(&name).is_none() {
+        return Err((RC_NOT_FOUND, format!("route {name} does not exist")));
+    }
+    Ok(HashMap::new())
+}
+
+fn list_routes(state: &mut State) -> Response {
+    let mut body = HashMap::new();
+    for (name, properties) in &state.catalogue.routes {
+        let mut info = properties.clone();
+        // a route is connected if the current thread runs a broker for its url
+        let connected = match properties.get("url") {
+            Some(TypedValue::String(url)) => super::broker_exists(url),
+            _ => false,
+        };
+        info.insert(
+            "conn".to_string(),
+            TypedValue::String((connected as i32).to_string()),
+        );
+        let info = MapMessage {
+            body: info,
+            ..Default::default()
+        };
+        body.insert(name.clone(), TypedValue::Map(info));
+    }
+    Ok(body)
+}
+
+fn create_factory(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "jn")?;
+    let url = string(request, "url")?;
+    if name.is_empty() || url.is_empty() {
+        return Err((
+            RC_INVALID_REQUEST,
+            "factory name and url are required".to_string(),
+        ));
+    }
+    if state.catalogue.factories.contains_key(&name) {
+        return Err((RC_ALREADY_EXISTS, format!("factory {name} already exists")));
+    }
+    let mut properties = request.body.clone();
+    properties.remove("jn");
+    state.catalogue.factories.insert(name, properties);
+    Ok(HashMap::new())
+}
+
+fn delete_factory(state: &mut State, request: &MapMessage) -> Response {
+    let name = string(request, "jn")?;
+    if state.catalogue.factories.remove(&name).is_none() {
+        return Err((RC_NOT_FOUND, format!("factory {name} does not exist")));
+    }
+    Ok(HashMap::new())
+}
+
+fn list_factories(state: &mut State) -> Response {
+    let mut body = HashMap::new();
+    for (name, properties) in &state.catalogue.factories {
+        // the server reports all settings as text
+        let info = properties
+            .iter()
+            .map(|(field, value)| (field.clone(), TypedValue::String(render(value))))
+            .collect();
+        let info = MapMessage {
+            body: info,
+            ..Default::default()
+        };
+        body.insert(name.clone(), TypedValue::Map(info));
+    }
+    Ok(body)
+}
@@ -0,0 +1,834 @@
+//! In-memory mock broker.
+//!
+//! Without the `ems-sys` feature `tibco_ems::connect` uses the broker of the current
+//! thread, so tests running in parallel never see each others messages. With any
+//! feature set a [`MockBroker`] can be used through [`MockBroker::connect`] or
+//! `tibco_ems::connect_with_backend`, which also shares a broker between threads.
+//!
+//! Requests on the admin queue are answered by the broker, so the `tibco_ems::admin`
+//! functions work on a catalogue of destinations, bridges, routes, connection factories,
+//! users and permissions and on the open connections, consumers and producers. Factories
+//! of the catalogue and destinations bound with [`MockBroker::bind`] are found through
+//! `tibco_ems::LookupContext`.
+
+use super::admin::ServerState;
+use super::backend::{AcknowledgeMode, Backend, ConsumerKind};
+use super::selector::Selector;
+use super::{Connection, Destination, Message, TypedValue};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+mod admin;
+
+const DEFAULT_URL: &str = "tcp://localhost:7222";
+const ADMIN_URL_PREFIX: &str = "<$admin>:";
+
+/// next free handle, 0 is never handed out as it marks a missing pointer
+static NEXT_HANDLE: AtomicUsize = AtomicUsize::new(1);
+
+thread_local! {
+    static BROKERS: RefCell<HashMap<String, MockBroker>> = RefCell::new(HashMap::new());
+}
+
+fn next_handle() -> usize {
+    NEXT_HANDLE.fetch_add(1, Ordering::Relaxed)
+}
+
+/// whether the current thread has a broker for the given server url
+fn broker_exists(url: &str) -> bool {
+    BROKERS.with(|brokers| brokers.borrow().contains_key(url))
+}
+
+/// in-memory broker emulating a Tibco EMS server
+///
+/// queues are FIFO and only lose a message once it is acknowledged, topics fan out
+/// to every subscription, durable subscriptions outlive their consumers and
+/// unacknowledged messages are redelivered on rollback or session close. Message
+/// selectors are evaluated by the broker, so consumers only see matching messages.
+#[derive(Debug, Clone)]
+pub struct MockBroker {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    url: String,
+    state: Mutex<State>,
+    available: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    queues: HashMap<String, VecDeque<Envelope>>,
+    subscriptions: HashMap<Subscription, SubscriptionState>,
+    connections: HashMap<usize, ConnectionState>,
+    factories: HashMap<usize, FactoryState>,
+    lookup_contexts: HashSet<usize>,
+    bindings: HashMap<String, Destination>,
+    sessions: HashMap<usize, SessionState>,
+    consumers: HashMap<usize, ConsumerState>,
+    producers: Vec<ProducerState>,
+    deliveries: HashMap<usize, Delivery>,
+    message_count: u64,
+    catalogue: admin::Catalogue,
+    unreachable: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Envelope {
+    message: Message,
+    delivery_count: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Subscription {
+    /// subscription of a single non-shared consumer
+    Private { topic: String, consumer: usize },
+    /// shared non-durable subscription
+    Shared { topic: String, name: String },
+    /// shared durable subscription
+    Durable { topic: String, name: String },
+}
+
+impl Subscription {
+    fn topic(&self) -> &str {
+        match self {
+            Subscription::Private { topic, .. } => topic,
+            Subscription::Shared { topic, .. } => topic,
+            Subscription::Durable { topic, .. } => topic,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SubscriptionState {
+    selector: Option<Selector>,
+    messages: VecDeque<Envelope>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    Queue(String),
+    Subscription(Subscription),
+}
+
+#[derive(Debug)]
+struct ConnectionState {
+    user: String,
+    client_id: Option<String>,
+    admin: bool,
+    opened: Instant,
+}
+
+/// settings of a looked up connection factory
+#[derive(Debug, Clone)]
+struct FactoryState {
+    client_id: Option<String>,
+}
+
+#[derive(Debug)]
+struct SessionState {
+    connection: usize,
+    client_acknowledge: bool,
+}
+
+#[derive(Debug)]
+struct ConsumerState {
+    session: usize,
+    source: Source,
+    selector: Option<Selector>,
+    delivered: u64,
+}
+
+/// sender of a session to a destination, created with its first message
+#[derive(Debug)]
+struct ProducerState {
+    id: usize,
+    session: usize,
+    destination: Destination,
+    sent: u64,
+}
+
+#[derive(Debug)]
+struct Delivery {
+    session: usize,
+    source: Source,
+    envelope: Envelope,
+}
+
+impl Default for MockBroker {
+    fn default() -> Self {
+        MockBroker::with_url(DEFAULT_URL)
+    }
+}
+
+impl MockBroker {
+    /// creates a new, empty broker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// creates a new, empty broker which reports the given url as its active url
+    pub fn with_url(url: &str) -> Self {
+        MockBroker {
+            inner: Arc::new(Inner {
+                url: url.to_string(),
+                state: Mutex::new(State::default()),
+                available: Condvar::new(),
+            }),
+        }
+    }
+
+    /// returns the broker the current thread uses for the given server url
+    ///
+    /// this is the broker `tibco_ems::connect` connects to
+    pub fn current(url: &str) -> Self {
+        let url = url.strip_prefix(ADMIN_URL_PREFIX).unwrap_or(url);
+        BROKERS.with(|brokers| {
+            brokers
+                .borrow_mut()
+                .entry(url.to_string())
+                .or_insert_with(|| MockBroker::with_url(url))
+                .clone()
+        })
+    }
+
+    /// open a connection to this broker
+    pub fn connect(&self) -> Result<Connection, Error> {
+        super::connect_with_backend(self.clone(), self.url(), "", "")
+    }
+
+    /// returns the url of this broker
+    pub fn url(&self) -> &str {
+        &self.inner.url
+    }
+
+    /// number of messages waiting on a destination, including unacknowledged ones
+    ///
+    /// for topics the messages of all subscriptions are summed up
+    pub fn pending_messages(&self, destination: &Destination) -> usize {
+        let state = self.lock();
+        let (queued, source) = match destination {
+            Destination::Queue(name) => {
+                (state.queues.get(name).map(VecDeque::len).unwrap_or(0), None)
+            }
+            Destination::Topic(name) => (
+                state
+                    .subscriptions
+                    .iter()
+                    .filter(|(sub, _)| sub.topic() == name)
+                    .map(|(_, sub)| sub.messages.len())
+                    .sum(),
+                Some(name),
+            ),
+        };
+        let in_flight = state
+            .deliveries
+            .values()
+            .filter(|delivery| match (&delivery.source, destination, source) {
+                (Source::Queue(queue), Destination::Queue(name), _) => queue == name,
+                (Source::Subscription(sub), _, Some(topic)) => sub.topic() == topic,
+                _ => false,
+            })
+            .count();
+        queued + in_flight
+    }
+
+    /// binds a destination to a JNDI name, replacing an earlier binding
+    ///
+    /// bound destinations are found by `tibco_ems::LookupContext::lookup_destination`,
+    /// connection factories are looked up from the factories created through the admin queue
+    pub fn bind(&self, name: &str, destination: Destination) {
+        self.lock().bindings.insert(name.to_string(), destination);
+    }
+
+    /// removes the binding of a JNDI name
+    pub fn unbind(&self, name: &str) {
+        self.lock().bindings.remove(name);
+    }
+
+    /// sets the state reported to `tibco_ems::admin::get_server_state`
+    pub fn set_server_state(&self, server_state: ServerState) {
+        self.lock().catalogue.server_state = server_state;
+    }
+
+    /// makes new connections fail like a server which is down, when not reachable
+    pub fn set_reachable(&self, reachable: bool) {
+        self.lock().unreachable = !reachable;
+    }
+
+    /// makes destination listings ignore the paging cursor, like a server without paging support
+    pub fn set_cursor_ignored(&self, ignored: bool) {
+        self.lock().catalogue.ignore_cursor = ignored;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn open_consumer(
+        &self,
+        session: usize,
+        destination: &Destination,
+        kind: ConsumerKind,
+        selector: Option<&str>,
+    ) -> Result<usize, Error> {
+        let selector = match selector {
+            Some(text) if !text.trim().is_empty() => Some(Selector::parse(text)?),
+            _ => None,
+        };
+        let pointer = next_handle();
+        let source = match (destination, kind) {
+            (Destination::Queue(name), ConsumerKind::Plain) => Source::Queue(name.clone()),
+            (Destination::Queue(_), _) => {
+                return Err(Error::other("destination is not of type topic"));
+            }
+            (Destination::Topic(topic), ConsumerKind::Plain) => {
+                Source::Subscription(Subscription::Private {
+                    topic: topic.clone(),
+                    consumer: pointer,
+                })
+            }
+            (Destination::Topic(topic), ConsumerKind::Shared(name)) => {
+                Source::Subscription(Subscription::Shared {
+                    topic: topic.clone(),
+                    name: name.to_string(),
+                })
+            }
+            (Destination::Topic(topic), ConsumerKind::Durable(name)) => {
+                Source::Subscription(Subscription::Durable {
+                    topic: topic.clone(),
+                    name: name.to_string(),
+                })
+            }
+        };
+        let mut state = self.lock();
+        if !state.sessions.contains_key(&session) {
+            return Err(Error::other("session is closed"));
+        }
+        match &source {
+            Source::Queue(name) => {
+                state.queues.entry(name.clone()).or_default();
+            }
+            Source::Subscription(sub) => {
+                let subscription = state.subscriptions.entry(sub.clone()).or_default();
+                let changed = subscription.selector.as_ref().map(Selector::as_str)
+                    != selector.as_ref().map(Selector::as_str);
+                if subscription.selector.is_none() && subscription.messages.is_empty() {
+                    subscription.selector = selector.clone();
+                } else if changed && matches!(kind, ConsumerKind::Durable(_)) {
+                    // a durable subscription with a new selector starts over
+                    *subscription = SubscriptionState {
+                        selector: selector.clone(),
+                        messages: VecDeque::new(),
+                    };
+                } else if changed {
+                    // like JMS, consumers of a shared subscription need the same selector
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "shared subscription exists with a different selector",
+                    ));
+                }
+            }
+        }
+        state.consumers.insert(
+            pointer,
+            ConsumerState {
+                session,
+                source,
+                selector,
+                delivered: 0,
+            },
+        );
+        Ok(pointer)
+    }
+
+    fn publish(&self, destination: &Destination, message: &Message) {
+        if *destination == Destination::Queue(admin::ADMIN_QUEUE_NAME.to_string()) {
+            let reply = admin::respond(&mut self.lock(), message);
+            if let (Some(reply), Some(reply_to)) = (reply, reply_to(message)) {
+                self.publish(&reply_to, &reply);
+            }
+            return;
+        }
+        let mut message = message.clone();
+        let mut state = self.lock();
+        state.message_count += 1;
+        let message_id = format!("ID:mock.{}", state.message_count);
+        let header = match &mut message {
+            Message::TextMessage(msg) => {
+                msg.destination = Some(destination.clone());
+                msg.header.get_or_insert_with(HashMap::new)
+            }
+            Message::BytesMessage(msg) => {
+                msg.destination = Some(destination.clone());
+                msg.header.get_or_insert_with(HashMap::new)
+            }
+            Message::MapMessage(msg) => {
+                msg.destination = Some(destination.clone());
+                msg.header.get_or_insert_with(HashMap::new)
+            }
+            Message::ObjectMessage(msg) => {
+                msg.destination = Some(destination.clone());
+                msg.header.get_or_insert_with(HashMap::new)
+            }
+        };
+        header.insert("MessageID".to_string(), TypedValue::String(message_id));
+        let envelope = Envelope {
+            message,
+            delivery_count: 0,
+        };
+        match destination {
+            Destination::Queue(name) => {
+                state
+                    .queues
+                    .entry(name.clone())
+                    .or_default()
+                    .push_back(envelope);
+            }
+            Destination::Topic(name) => {
+                for (sub, subscription) in state.subscriptions.iter_mut() {
+                    let selected = match &subscription.selector {
+                        Some(selector) => selector.matches(&envelope.message),
+                        None => true,
+                    };
+                    if selected && topic_matches(sub.topic(), name) {
+                        subscription.messages.push_back(envelope.clone());
+                    }
+                }
+            }
+        }
+        drop(state);
+        self.inner.available.notify_all();
+    }
+
+    fn next_message(
+        &self,
+        consumer: usize,
+        wait_time_ms: Option<i64>,
+    ) -> Result<Option<Message>, Error> {
+        let deadline =
+            wait_time_ms.map(|ms| Instant::now() + Duration::from_millis(ms.max(0) as u64));
+        let mut state = self.lock();
+        loop {
+            let (session, source, selector) = match state.consumers.get(&consumer) {
+                Some(consumer) => (
+                    consumer.session,
+                    consumer.source.clone(),
+                    consumer.selector.clone(),
+                ),
+                None => return Err(Error::other("consumer is closed")),
+            };
+            let next = match &source {
+                // queue consumers skip messages not matching their selector
+                Source::Queue(name) => state.queues.get_mut(name).and_then(|messages| {
+                    let index = messages.iter().position(|envelope| match &selector {
+                        Some(selector) => selector.matches(&envelope.message),
+                        None => true,
+                    })?;
+                    messages.remove(index)
+                }),
+                Source::Subscription(sub) => state
+                    .subscriptions
+                    .get_mut(sub)
+                    .and_then(|sub| sub.messages.pop_front()),
+            };
+            if let Some(mut envelope) = next {
+                envelope.delivery_count += 1;
+                if let Some(consumer) = state.consumers.get_mut(&consumer) {
+                    consumer.delivered += 1;
+                }
+                let mut message = envelope.message.clone();
+                if envelope.delivery_count > 1 {
+                    set_header(
+                        &mut message,
+                        "JMSXDeliveryCount",
+                        TypedValue::Integer(envelope.delivery_count),
+                    );
+                }
+                let client_acknowledge = state
+                    .sessions
+                    .get(&session)
+                    .map(|s| s.client_acknowledge)
+                    .unwrap_or(false);
+                if client_acknowledge {
+                    let pointer = next_handle();
+                    set_pointer(&mut message, pointer);
+                    state.deliveries.insert(
+                        pointer,
+                        Delivery {
+                            session,
+                            source,
+                            envelope,
+                        },
+                    );
+                }
+                return Ok(Some(message));
+            }
+            state = match deadline {
+                None => self
+                    .inner
+                    .available
+                    .wait(state)
+                    .unwrap_or_else(|err| err.into_inner()),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Ok(None);
+                    }
+                    self.inner
+                        .available
+                        .wait_timeout(state, deadline - now)
+                        .unwrap_or_else(|err| err.into_inner())
+                        .0
+                }
+            };
+        }
+    }
+
+    fn remove_consumer(&self, consumer: usize) {
+        let mut state = self.lock();
+        if let Some(consumer) = state.consumers.remove(&consumer) {
+            match consumer.source {
+                Source::Queue(name) => {
+                    state.queues.remove(&name);
+                }
+                Source::Subscription(sub) => {
+                    state.subscriptions.remove(&sub);
+                }
+            }
+        }
+    }
+}
+
+impl Backend for MockBroker {
+    fn connect(&self, url: &str, user: &str, _password: &str) -> Result<usize, Error> {
+        let mut state = self.lock();
+        if state.unreachable {
+            return Err(Error::new(
+                ErrorKind::ConnectionRefused,
+                format!("server {url} is not reachable"),
+            ));
+        }
+        let pointer = next_handle();
+        state.connections.insert(
+            pointer,
+            ConnectionState {
+                user: user.to_string(),
+                client_id: None,
+                admin: url.starts_with(ADMIN_URL_PREFIX),
+                opened: Instant::now(),
+            },
+        );
+        Ok(pointer)
+    }
+
+    fn lookup_context(&self, _url: &str, _user: &str, _password: &str) -> Result<usize, Error> {
+        let pointer = next_handle();
+        self.lock().lookup_contexts.insert(pointer);
+        Ok(pointer)
+    }
+
+    fn lookup_destination(&self, context: usize, name: &str) -> Result<Destination, Error> {
+        let state = self.lock();
+        if !state.lookup_contexts.contains(&context) {
+            return Err(Error::other("lookup context is closed"));
+        }
+        match state.bindings.get(name) {
+            Some(destination) => Ok(destination.clone()),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("destination {name} not found"),
+            )),
+        }
+    }
+
+    fn lookup_factory(&self, context: usize, name: &str) -> Result<usize, Error> {
+        let mut state = self.lock();
+        if !state.lookup_contexts.contains(&context) {
+            return Err(Error::other("lookup context is closed"));
+        }
+        let Some(factory) = state.catalogue.factory(name) else {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("connection factory {name} not found"),
+            ));
+        };
+        let pointer = next_handle();
+        state.factories.insert(pointer, factory);
+        Ok(pointer)
+    }
+
+    fn close_lookup_context(&self, context: usize) {
+        self.lock().lookup_contexts.remove(&context);
+    }
+
+    /// connections of a factory always connect to this broker, whatever url the factory has
+    fn connect_factory(&self, factory: usize, user: &str, _password: &str) -> Result<usize, Error> {
+        let mut state = self.lock();
+        let Some(factory) = state.factories.get(&factory).cloned() else {
+            return Err(Error::other("connection factory is not open"));
+        };
+        if state.unreachable {
+            return Err(Error::new(
+                ErrorKind::ConnectionRefused,
+                format!("server {} is not reachable", self.url()),
+            ));
+        }
+        let pointer = next_handle();
+        state.connections.insert(
+            pointer,
+            ConnectionState {
+                user: user.to_string(),
+                client_id: factory.client_id,
+                admin: false,
+                opened: Instant::now(),
+            },
+        );
+        Ok(pointer)
+    }
+
+    fn close_factory(&self, factory: usize) {
+        self.lock().factories.remove(&factory);
+    }
+
+    fn close_connection(&self, connection: usize) {
+        let mut state = self.lock();
+        if state.connections.remove(&connection).is_none() {
+            return;
+        }
+        let sessions: Vec<usize> = state
+            .sessions
+            .iter()
+            .filter(|(_, session)| session.connection == connection)
+            .map(|(pointer, _)| *pointer)
+            .collect();
+        for session in sessions {
+            state.close_session(session);
+        }
+        drop(state);
+        self.inner.available.notify_all();
+    }
+
+    fn active_url(&self, _connection: usize) -> Result<String, Error> {
+        Ok(self.url().to_string())
+    }
+
+    fn session(&self, connection: usize, mode: AcknowledgeMode) -> Result<(usize, usize), Error> {
+        let mut state = self.lock();
+        if !state.connections.contains_key(&connection) {
+            return Err(Error::other("connection is closed"));
+        }
+        let pointer = next_handle();
+        let client_acknowledge = mode == AcknowledgeMode::ExplicitClient;
+        state.sessions.insert(
+            pointer,
+            SessionState {
+                connection,
+                client_acknowledge,
+            },
+        );
+        Ok((pointer, 0))
+    }
+
+    fn close_session(&self, session: usize, _producer: usize) {
+        self.lock().close_session(session);
+        self.inner.available.notify_all();
+    }
+
+    fn consumer(
+        &self,
+        session: usize,
+        destination: &Destination,
+        kind: ConsumerKind,
+        selector: Option<&str>,
+    ) -> Result<usize, Error> {
+        self.open_consumer(session, destination, kind, selector)
+    }
+
+    fn send(
+        &self,
+        session: usize,
+        _producer: usize,
+        destination: &Destination,
+        message: &Message,
+    ) -> Result<(), Error> {
+        {
+            let mut state = self.lock();
+            let producer = state
+                .producers
+                .iter_mut()
+                .find(|p| p.session == session && p.destination == *destination);
+            match producer {
+                Some(producer) => producer.sent += 1,
+                None => state.producers.push(ProducerState {
+                    id: next_handle(),
+                    session,
+                    destination: destination.clone(),
+                    sent: 1,
+                }),
+            }
+        }
+        self.publish(destination, message);
+        Ok(())
+    }
+
+    fn receive(
+        &self,
+        consumer: usize,
+        wait_time_ms: Option<i64>,
+    ) -> Result<Option<Message>, Error> {
+        self.next_message(consumer, wait_time_ms)
+    }
+
+    fn request_reply(
+        &self,
+        session: usize,
+        destination: &Destination,
+        message: &Message,
+        timeout: i64,
+    ) -> Result<Option<Message>, Error> {
+        let temporary_name = format!("$TMP$.mock.{}", next_handle());
+        let reply_to = match destination {
+            Destination::Queue(_) => Destination::Queue(temporary_name),
+            Destination::Topic(_) => Destination::Topic(temporary_name),
+        };
+        let consumer = self.open_consumer(session, &reply_to, ConsumerKind::Plain, None)?;
+        let mut message = message.clone();
+        set_reply_to(&mut message, &reply_to);
+        self.publish(destination, &message);
+        let reply = self.next_message(consumer, Some(timeout));
+        // temporary destinations vanish together with their consumer
+        self.remove_consumer(consumer);
+        reply
+    }
+
+    fn acknowledge(&self, message: usize) {
+        self.lock().deliveries.remove(&message);
+    }
+
+    fn recover(&self, message: usize) {
+        let mut state = self.lock();
+        if let Some(delivery) = state.deliveries.remove(&message) {
+            state.requeue(delivery);
+            drop(state);
+            self.inner.available.notify_all();
+        }
+    }
+
+    fn destroy(&self, _message: usize) {}
+
+    fn emulates_admin(&self) -> bool {
+        true
+    }
+}
+
+impl State {
+    /// closes a session, its unacknowledged messages are redelivered
+    fn close_session(&mut self, session: usize) {
+        if self.sessions.remove(&session).is_none() {
+            return;
+        }
+        self.consumers
+            .retain(|_, consumer| consumer.session != session);
+        self.producers
+            .retain(|producer| producer.session != session);
+        // unacknowledged messages go back to their source, oldest first
+        let mut deliveries: Vec<usize> = self
+            .deliveries
+            .iter()
+            .filter(|(_, delivery)| delivery.session == session)
+            .map(|(pointer, _)| *pointer)
+            .collect();
+        deliveries.sort_unstable_by(|a, b| b.cmp(a));
+        for pointer in deliveries {
+            if let Some(delivery) = self.deliveries.remove(&pointer) {
+                self.requeue(delivery);
+            }
+        }
+        // non durable subscriptions end with their last consumer
+        let active: Vec<Source> = self
+            .consumers
+            .values()
+            .map(|consumer| consumer.source.clone())
+            .collect();
+        self.subscriptions.retain(|sub, _| {
+            matches!(sub, Subscription::Durable { .. })
+                || active.contains(&Source::Subscription(sub.clone()))
+        });
+    }
+
+    /// puts a delivered message back in front of its source
+    fn requeue(&mut self, delivery: Delivery) {
+        let messages = match &delivery.source {
+            Source::Queue(name) => self.queues.get_mut(name),
+            Source::Subscription(sub) => {
+                self.subscriptions.get_mut(sub).map(|sub| &mut sub.messages)
+            }
+        };
+        if let Some(messages) = messages {
+            messages.push_front(delivery.envelope);
+        }
+    }
+}
+
+/// checks a topic name against a subscription topic which may contain wildcards
+///
+/// `*` matches exactly one element, `>` matches one or more trailing elements
+fn topic_matches(pattern: &str, name: &str) -> bool {
+    let mut names = name.split('.');
+    for element in pattern.split('.') {
+        match (element, names.next()) {
+            (">", Some(_)) => return true,
+            ("*", Some(_)) => {}
+            (element, Some(name)) if element == name => {}
+            _ => return false,
+        }
+    }
+    names.next().is_none()
+}
+
+fn set_header(message: &mut Message, name: &str, value: TypedValue) {
+    let header = match message {
+        Message::TextMessage(msg) => msg.header.get_or_insert_with(HashMap::new),
+        Message::BytesMessage(msg) => msg.header.get_or_insert_with(HashMap::new),
+        Message::MapMessage(msg) => msg.header.get_or_insert_with(HashMap::new),
+        Message::ObjectMessage(msg) => msg.header.get_or_insert_with(HashMap::new),
+    };
+    header.insert(name.to_string(), value);
+}
+
+fn set_pointer(message: &mut Message, pointer: usize) {
+    match message {
+        Message::TextMessage(msg) => msg.pointer = Some(pointer),
+        Message::BytesMessage(msg) => msg.pointer = Some(pointer),
+        Message::MapMessage(msg) => msg.pointer = Some(pointer),
+        Message::ObjectMessage(msg) => msg.pointer = Some(pointer),
+    }
+}
+
+fn reply_to(message: &Message) -> Option<Destination> {
+    match message {
+        Message::TextMessage(msg) => msg.reply_to.clone(),
+        Message::BytesMessage(msg) => msg.reply_to.clone(),
+        Message::MapMessage(msg) => msg.reply_to.clone(),
+        Message::ObjectMessage(msg) => msg.reply_to.clone(),
+    }
+}
+
+fn set_reply_to(message: &mut Message, reply_to: &Destination) {
+    let reply_to = Some(reply_to.clone());
+    match message {
+        Message::TextMessage(msg) => msg.reply_to = reply_to,
+        Message::BytesMessage(msg) => msg.reply_to = reply_to,
+        Message::MapMessage(msg) => msg.reply_to = reply_to,
+        Message::ObjectMessage(msg) => msg.reply_to = reply_to,
+    }
+}
@@ -0,0 +1,1708 @@
+//! Native backend using the Tibco EMS C library.
+
+use super::backend::{AcknowledgeMode, Backend, ConsumerKind};
+use super::{
+    BytesMessage, Destination, MapMessage, Message, ObjectMessage, TextMessage, TypedValue,
+};
+use enum_extract::extract;
+use log::{error, trace};
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr, CString};
+use std::io::{Error, ErrorKind};
+use std::os::raw::c_char;
+use tibco_ems_sys::{
+    tibemsAcknowledgeMode, tibemsConnectionFactory, tibemsDestinationType, tibemsMsgType,
+    tibems_bool, tibems_status,
+};
+
+// tibco_ems_sys passes lookup contexts as empty structs and lacks the factory lookup
+// and connection close, so these functions are declared with pointer sized handles here
+extern "C" {
+    fn tibemsLookupContext_Create(
+        context: *mut usize,
+        url: *const c_char,
+        user: *const c_char,
+        password: *const c_char,
+    ) -> tibems_status;
+    fn tibemsLookupContext_LookupDestination(
+        context: usize,
+        name: *const c_char,
+        destination: *mut usize,
+    ) -> tibems_status;
+    fn tibemsLookupContext_LookupConnectionFactory(
+        context: usize,
+        name: *const c_char,
+        factory: *mut *mut tibemsConnectionFactory,
+    ) -> tibems_status;
+    fn tibemsLookupContext_Destroy(context: usize) -> tibems_status;
+    fn tibemsConnection_Close(connection: usize) -> tibems_status;
+}
+
+/// backend talking to a Tibco EMS server through the C library
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NativeBackend;
+
+impl Backend for NativeBackend {
+    fn connect(&self, url: &str, user: &str, password: &str) -> Result<usize, Error> {
+        unsafe {
+            let factory = tibco_ems_sys::tibemsConnectionFactory_Create();
+            let c_url = CString::new(url).unwrap();
+            let status =
+                tibco_ems_sys::tibemsConnectionFactory_SetServerURL(factory, c_url.as_ptr());
+            match status {
+                tibems_status::TIBEMS_OK => {
+                    trace!("tibemsConnectionFactory_SetServerURL: {status:?}")
+                }
+                _ => {
+                    // not testable, c_url protects from this error path
+                    error!("tibemsConnectionFactory_SetServerURL: {status:?}");
+                    return Err(Error::new(ErrorKind::InvalidData, "cannot set server url"));
+                }
+            }
+            create_connection(factory, user, password)
+        }
+    }
+
+    fn lookup_context(&self, url: &str, user: &str, password: &str) -> Result<usize, Error> {
+        let mut context: usize = 0;
+        unsafe {
+            let c_url = CString::new(url)?;
+            let c_user = CString::new(user)?;
+            let c_password = CString::new(password)?;
+            let status = tibemsLookupContext_Create(
+                &mut context,
+                c_url.as_ptr(),
+                c_user.as_ptr(),
+                c_password.as_ptr(),
+            );
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsLookupContext_Create: {status:?}"),
+                _ => {
+                    error!("tibemsLookupContext_Create: {status:?}");
+                    return Err(Error::new(
+                        ErrorKind::NotConnected,
+                        "cannot create lookup context",
+                    ));
+                }
+            }
+        }
+        Ok(context)
+    }
+
+    fn lookup_destination(&self, context: usize, name: &str) -> Result<Destination, Error> {
+        let mut destination: usize = 0;
+        unsafe {
+            let c_name = CString::new(name)?;
+            let status =
+                tibemsLookupContext_LookupDestination(context, c_name.as_ptr(), &mut destination);
+            match status {
+                tibems_status::TIBEMS_OK => {
+                    trace!("tibemsLookupContext_LookupDestination: {status:?}")
+                }
+                _ => {
+                    error!("tibemsLookupContext_LookupDestination: {status:?}");
+                    return Err(lookup_error(status, "destination", name));
+                }
+            }
+            let mut destination_type = tibemsDestinationType::TIBEMS_UNKNOWN;
+            let status =
+                tibco_ems_sys::tibemsDestination_GetType(destination, &mut destination_type);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsDestination_GetType: {status:?}"),
+                _ => error!("tibemsDestination_GetType: {status:?}"),
+            }
+            let buf_size = 1024;
+            let buf_vec: Vec<i8> = vec![0; buf_size];
+            let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+            let status = tibco_ems_sys::tibemsDestination_GetName(destination, buf_ref, buf_size);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsDestination_GetName: {status:?}"),
+                _ => error!("tibemsDestination_GetName: {status:?}"),
+            }
+            let destination_name = CStr::from_ptr(buf_ref).to_string_lossy().to_string();
+            let status = tibco_ems_sys::tibemsDestination_Destroy(destination);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsDestination_Destroy: {status:?}"),
+                _ => error!("tibemsDestination_Destroy: {status:?}"),
+            }
+            match destination_type {
+                tibemsDestinationType::TIBEMS_QUEUE => Ok(Destination::Queue(destination_name)),
+                tibemsDestinationType::TIBEMS_TOPIC => Ok(Destination::Topic(destination_name)),
+                _ => Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("destination {name} has an unknown type"),
+                )),
+            }
+        }
+    }
+
+    fn lookup_factory(&self, context: usize, name: &str) -> Result<usize, Error> {
+        let mut factory: *mut tibemsConnectionFactory = std::ptr::null_mut();
+        unsafe {
+            let c_name = CString::new(name)?;
+            let status =
+                tibemsLookupContext_LookupConnectionFactory(context, c_name.as_ptr(), &mut factory);
+            match status {
+                tibems_status::TIBEMS_OK => {
+                    trace!("tibemsLookupContext_LookupConnectionFactory: {status:?}")
+                }
+                _ => {
+                    error!("tibemsLookupContext_LookupConnectionFactory: {status:?}");
+                    return Err(lookup_error(status, "connection factory", name));
+                }
+            }
+        }
+        Ok(factory as usize)
+    }
+
+    fn close_lookup_context(&self, context: usize) {
+        unsafe {
+            let status = tibemsLookupContext_Destroy(context);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsLookupContext_Destroy: {status:?}"),
+                _ => error!("tibemsLookupContext_Destroy: {status:?}"),
+            }
+        }
+    }
+
+    fn connect_factory(&self, factory: usize, user: &str, password: &str) -> Result<usize, Error> {
+        unsafe { create_connection(factory as *mut tibemsConnectionFactory, user, password) }
+    }
+
+    fn close_factory(&self, factory: usize) {
+        unsafe {
+            let status = tibco_ems_sys::tibemsConnectionFactory_Destroy(
+                factory as *mut tibemsConnectionFactory,
+            );
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsConnectionFactory_Destroy: {status:?}"),
+                _ => error!("tibemsConnectionFactory_Destroy: {status:?}"),
+            }
+        }
+    }
+
+    fn close_connection(&self, connection: usize) {
+        unsafe {
+            let status = tibemsConnection_Close(connection);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsConnection_Close: {status:?}"),
+                _ => error!("tibemsConnection_Close: {status:?}"),
+            }
+        }
+    }
+
+    fn active_url(&self, connection_pointer: usize) -> Result<String, Error> {
+        unsafe {
+            let buf_vec: Vec<i8> = vec![0; 0];
+            let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+            let status = tibco_ems_sys::tibemsConnection_GetActiveURL(connection_pointer, &buf_ref);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsConnection_GetActiveURL: {status:?}"),
+                _ => {
+                    error!("tibemsConnection_GetActiveURL: {status:?}");
+                    return Err(Error::new(
+                        ErrorKind::Other,
+                        "failed to retrieve active url",
+                    ));
+                }
+            }
+            let url = CStr::from_ptr(buf_ref).to_str().unwrap();
+            Ok(url.to_string())
+        }
+    }
+
+    fn session(
+        &self,
+        connection_pointer: usize,
+        mode: AcknowledgeMode,
+    ) -> Result<(usize, usize), Error> {
+        let acknowledge_mode = match mode {
+            AcknowledgeMode::Auto => tibemsAcknowledgeMode::TIBEMS_AUTO_ACKNOWLEDGE,
+            AcknowledgeMode::ExplicitClient => {
+                tibemsAcknowledgeMode::TIBEMS_EXPLICIT_CLIENT_ACKNOWLEDGE
+            }
+        };
+        unsafe {
+            let mut session_pointer: usize = 0;
+            let status = tibco_ems_sys::tibemsConnection_CreateSession(
+                connection_pointer,
+                &mut session_pointer,
+                tibco_ems_sys::tibems_bool::TIBEMS_FALSE,
+                acknowledge_mode,
+            );
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsConnection_CreateSession: {status:?}"),
+                _ => {
+                    error!("tibemsConnection_CreateSession: {status:?}");
+                    return Err(Error::new(ErrorKind::Other, "creating session failed"));
+                }
+            }
+            let mut producer: usize = 0;
+            let dest: usize = 0;
+            let status =
+                tibco_ems_sys::tibemsSession_CreateProducer(session_pointer, &mut producer, dest);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsSession_CreateProducer: {status:?}"),
+                _ => {
+                    error!("tibemsSession_CreateProducer: {status:?}");
+                    return Err(Error::new(ErrorKind::Other, "creating producer failed"));
+                }
+            }
+            Ok((session_pointer, producer))
+        }
+    }
+
+    fn close_session(&self, session: usize, producer: usize) {
+        unsafe {
+            //destroy producer
+            if producer != 0 {
+                let status = tibco_ems_sys::tibemsMsgProducer_Close(producer);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMsgProducer_Close: {status:?}"),
+                    _ => error!("tibemsMsgProducer_Close: {status:?}"),
+                }
+            }
+            let status = tibco_ems_sys::tibemsSession_Close(session);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsSession_Close: {status:?}"),
+                _ => error!("tibemsSession_Close: {status:?}"),
+            }
+        }
+    }
+
+    fn consumer(
+        &self,
+        session: usize,
+        destination: &Destination,
+        kind: ConsumerKind,
+        selector: Option<&str>,
+    ) -> Result<usize, Error> {
+        match kind {
+            ConsumerKind::Plain => queue_consumer(session, destination, selector),
+            ConsumerKind::Shared(name) => topic_consumer(session, destination, name, selector),
+            ConsumerKind::Durable(name) => {
+                topic_durable_consumer(session, destination, name, selector)
+            }
+        }
+    }
+
+    fn send(
+        &self,
+        session: usize,
+        producer: usize,
+        destination: &Destination,
+        message: &Message,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "tracing")]
+        let mut traced: Message = message.clone();
+
+        let mut dest: usize = 0;
+        let mut local_producer: usize = 0;
+        #[cfg(feature = "tracing")]
+        let mut span = add_trace_to_message(&mut traced);
+        #[cfg(feature = "tracing")]
+        let message = &traced;
+        #[cfg(feature = "tracing")]
+        use opentelemetry::trace::Span;
+        unsafe {
+            match destination {
+                Destination::Queue(name) => {
+                    #[cfg(feature = "tracing")]
+                    span.set_attribute(opentelemetry::KeyValue::new(
+                        "messaging.destination",
+                        name.clone(),
+                    ));
+                    #[cfg(feature = "tracing")]
+                    span.set_attribute(opentelemetry::KeyValue::new(
+                        "messaging.destination_kind",
+                        "queue",
+                    ));
+                    #[cfg(feature = "tracing")]
+                    span.set_attribute(opentelemetry::KeyValue::new(
+                        "messaging.system",
+                        "TibcoEMS",
+                    ));
+                    let c_destination = CString::new(name.clone()).unwrap();
+                    let status = tibco_ems_sys::tibemsDestination_Create(
+                        &mut dest,
+                        tibemsDestinationType::TIBEMS_QUEUE,
+                        c_destination.as_ptr(),
+                    );
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            trace!("tibemsDestination_Create: {status:?}")
+                        }
+                        _ => {
+                            let status_str = format!("{status:?}");
+                            error!("tibemsDestination_Create: {status_str}");
+                            return Err(Error::new(
+                                ErrorKind::Other,
+                                format!("create destination failed: {status_str}"),
+                            ));
+                        }
+                    }
+                }
+                Destination::Topic(name) => {
+                    let c_destination = CString::new(name.clone()).unwrap();
+                    let status = tibco_ems_sys::tibemsDestination_Create(
+                        &mut dest,
+                        tibemsDestinationType::TIBEMS_TOPIC,
+                        c_destination.as_ptr(),
+                    );
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            trace!("tibemsDestination_Create: {status:?}")
+                        }
+                        _ => {
+                            let status_str = format!("{status:?}");
+                            error!("tibemsDestination_Create: {status_str}");
+                            return Err(Error::new(
+                                ErrorKind::Other,
+                                format!("create destination failed: {status_str}"),
+                            ));
+                        }
+                    }
+                }
+            }
+            if producer == 0 {
+                let status =
+                    tibco_ems_sys::tibemsSession_CreateProducer(session, &mut local_producer, dest);
+                match status {
+                    tibems_status::TIBEMS_OK => {
+                        trace!("tibemsSession_CreateProducer: {status:?}")
+                    }
+                    _ => {
+                        let status_str = format!("{status:?}");
+                        error!("tibemsSession_CreateProducer: {status_str}");
+                        return Err(Error::new(
+                            ErrorKind::Other,
+                            format!("create producer failed: {status_str}"),
+                        ));
+                    }
+                }
+            }
+            let msg = build_message_pointer_from_message(message);
+            let status = tibco_ems_sys::tibemsMsgProducer_SendToDestination(producer, dest, msg);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsMsgProducer_Send: {status:?}"),
+                _ => {
+                    let status_str = format!("{status:?}");
+                    error!("tibemsMsgProducer_Send: {status_str}");
+                    return Err(Error::new(
+                        ErrorKind::Other,
+                        format!("send message failed: {status_str}"),
+                    ));
+                }
+            }
+            //destroy producer if generated inline
+            if producer == 0 {
+                let status = tibco_ems_sys::tibemsMsgProducer_Close(local_producer);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMsgProducer_Close: {status:?}"),
+                    _ => error!("tibemsMsgProducer_Close: {status:?}"),
+                }
+            }
+            //destroy message
+            let status = tibco_ems_sys::tibemsMsg_Destroy(msg);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsMsg_Destroy: {status:?}"),
+                _ => error!("tibemsMsg_Destroy: {status:?}"),
+            }
+            //destroy destination
+            let status = tibco_ems_sys::tibemsDestination_Destroy(dest);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsDestination_Destroy: {status:?}"),
+                _ => error!("tibemsDestination_Destroy: {status:?}"),
+            }
+        }
+        #[cfg(feature = "tracing")]
+        span.end();
+        Ok(())
+    }
+
+    fn receive(
+        &self,
+        consumer: usize,
+        wait_time_ms: Option<i64>,
+    ) -> Result<Option<Message>, Error> {
+        unsafe {
+            let mut msg_pointer: usize = 0;
+            match wait_time_ms {
+                Some(time_ms) => {
+                    let status = tibco_ems_sys::tibemsMsgConsumer_ReceiveTimeout(
+                        consumer,
+                        &mut msg_pointer,
+                        time_ms,
+                    );
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            trace!("tibemsMsgConsumer_ReceiveTimeout: {status:?}")
+                        }
+                        tibems_status::TIBEMS_TIMEOUT => {
+                            return Ok(None);
+                        }
+                        _ => {
+                            let status_str = format!("{status:?}");
+                            error!("tibemsMsgConsumer_ReceiveTimeout: {status_str}");
+                            return Err(Error::new(
+                                ErrorKind::Other,
+                                format!("receive message failed: {status_str}"),
+                            ));
+                        }
+                    }
+                }
+                None => {
+                    let status =
+                        tibco_ems_sys::tibemsMsgConsumer_Receive(consumer, &mut msg_pointer);
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            trace!("tibemsMsgConsumer_Receive: {status:?}")
+                        }
+                        _ => {
+                            let status_str = format!("{status:?}");
+                            error!("tibemsMsgConsumer_Receive: {status_str}");
+                            return Err(Error::new(
+                                ErrorKind::Other,
+                                format!("receive message failed: {status_str}"),
+                            ));
+                        }
+                    }
+                }
+            }
+            let msg = build_message_from_pointer(msg_pointer);
+            Ok(Some(msg))
+        }
+    }
+
+    fn request_reply(
+        &self,
+        session: usize,
+        destination: &Destination,
+        message: &Message,
+        timeout: i64,
+    ) -> Result<Option<Message>, Error> {
+        //create temporary destination
+        let mut reply_dest: usize = 0;
+        let mut dest: usize = 0;
+        unsafe {
+            match &destination {
+                Destination::Queue(name) => {
+                    let status =
+                        tibco_ems_sys::tibemsSession_CreateTemporaryQueue(session, &mut reply_dest);
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            trace!("tibemsSession_CreateTemporaryQueue: {status:?}")
+                        }
+                        _ => error!("tibemsSession_CreateTemporaryQueue: {status:?}"),
+                    }
+                    let c_destination = CString::new(name.clone()).unwrap();
+                    let status = tibco_ems_sys::tibemsDestination_Create(
+                        &mut dest,
+                        tibemsDestinationType::TIBEMS_QUEUE,
+                        c_destination.as_ptr(),
+                    );
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            trace!("tibemsDestination_Create: {status:?}")
+                        }
+                        _ => error!("tibemsDestination_Create: {status:?}"),
+                    }
+                }
+                Destination::Topic(name) => {
+                    let status =
+                        tibco_ems_sys::tibemsSession_CreateTemporaryTopic(session, &mut reply_dest);
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            trace!("tibemsSession_CreateTemporaryTopic: {status:?}")
+                        }
+                        _ => error!("tibemsSession_CreateTemporaryTopic: {status:?}"),
+                    }
+                    let c_destination = CString::new(name.clone()).unwrap();
+                    let status = tibco_ems_sys::tibemsDestination_Create(
+                        &mut dest,
+                        tibemsDestinationType::TIBEMS_TOPIC,
+                        c_destination.as_ptr(),
+                    );
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            trace!("tibemsDestination_Create: {status:?}")
+                        }
+                        _ => error!("tibemsDestination_Create: {status:?}"),
+                    }
+                }
+            }
+            let mut producer: usize = 0;
+            let status = tibco_ems_sys::tibemsSession_CreateProducer(session, &mut producer, dest);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsSession_CreateProducer: {status:?}"),
+                _ => error!("tibemsSession_CreateProducer: {status:?}"),
+            }
+            let msg = build_message_pointer_from_message(message);
+            //set reply to
+            let status = tibco_ems_sys::tibemsMsg_SetReplyTo(msg, reply_dest);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsMsg_SetReplyTo: {status:?}"),
+                _ => error!("tibemsMsg_SetReplyTo: {status:?}"),
+            }
+            let status = tibco_ems_sys::tibemsMsgProducer_Send(producer, msg);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsMsgProducer_Send: {status:?}"),
+                _ => error!("tibemsMsgProducer_Send: {status:?}"),
+            }
+            //destroy message
+            let status = tibco_ems_sys::tibemsMsg_Destroy(msg);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsMsg_Destroy: {status:?}"),
+                _ => error!("tibemsMsg_Destroy: {status:?}"),
+            }
+            //destroy producer
+            let status = tibco_ems_sys::tibemsMsgProducer_Close(producer);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsMsgProducer_Close: {status:?}"),
+                _ => error!("tibemsMsgProducer_Close: {status:?}"),
+            }
+            //destroy destination
+            let status = tibco_ems_sys::tibemsDestination_Destroy(dest);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsDestination_Destroy: {status:?}"),
+                _ => error!("tibemsDestination_Destroy: {status:?}"),
+            }
+            //open consumer
+            let mut consumer_pointer: usize = 0;
+            let status = tibco_ems_sys::tibemsSession_CreateConsumer(
+                session,
+                &mut consumer_pointer,
+                reply_dest,
+                std::ptr::null(),
+                tibco_ems_sys::tibems_bool::TIBEMS_TRUE,
+            );
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsSession_CreateConsumer: {status:?}"),
+                _ => error!("tibemsSession_CreateConsumer: {status:?}"),
+            }
+            let mut reply_message: usize = 0;
+            let status = tibco_ems_sys::tibemsMsgConsumer_ReceiveTimeout(
+                consumer_pointer,
+                &mut reply_message,
+                timeout,
+            );
+            match status {
+                tibems_status::TIBEMS_OK => {
+                    trace!("tibemsMsgConsumer_ReceiveTimeout: {status:?}")
+                }
+                tibems_status::TIBEMS_TIMEOUT => {
+                    return Ok(None);
+                }
+                _ => error!("tibemsMsgConsumer_ReceiveTimeout: {status:?}"),
+            }
+            let result = build_message_from_pointer(reply_message);
+            //close consumer
+            let status = tibco_ems_sys::tibemsMsgConsumer_Close(consumer_pointer);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsMsgConsumer_Close: {status:?}"),
+                _ => error!("tibemsMsgConsumer_Close: {status:?}"),
+            }
+            //destroy temporary destination
+            match &destination {
+                Destination::Queue { .. } => {
+                    //destroy reply_to_queue
+                    let status =
+                        tibco_ems_sys::tibemsSession_DeleteTemporaryQueue(session, reply_dest);
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            trace!("tibemsSession_DeleteTemporaryQueue: {status:?}")
+                        }
+                        _ => error!("tibemsSession_DeleteTemporaryQueue: {status:?}"),
+                    }
+                }
+                Destination::Topic { .. } => {
+                    //destroy reply_to_queue
+                    let status =
+                        tibco_ems_sys::tibemsSession_DeleteTemporaryTopic(session, reply_dest);
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            trace!("tibemsSession_DeleteTemporaryTopic: {status:?}")
+                        }
+                        _ => error!("tibemsSession_DeleteTemporaryTopic: {status:?}"),
+                    }
+                }
+            }
+            Ok(Some(result))
+        }
+    }
+
+    fn acknowledge(&self, message: usize) {
+        unsafe {
+            let status = tibco_ems_sys::tibemsMsg_Acknowledge(message);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsMsg_Acknowledge: {status:?}"),
+                _ => error!("tibemsMsg_Acknowledge: {status:?}"),
+            }
+        }
+    }
+
+    fn recover(&self, message: usize) {
+        unsafe {
+            let status = tibco_ems_sys::tibemsMsg_Recover(message);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsMsg_Recover: {status:?}"),
+                _ => error!("tibemsMsg_Recover: {status:?}"),
+            }
+        }
+    }
+
+    fn destroy(&self, message: usize) {
+        unsafe {
+            let status = tibco_ems_sys::tibemsMsg_Destroy(message);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsMsg_Destroy: {status:?}"),
+                _ => error!("tibemsMsg_Destroy: {status:?}"),
+            }
+        }
+    }
+}
+
+/// error of a failed name lookup
+fn lookup_error(status: tibems_status, kind: &str, name: &str) -> Error {
+    match status {
+        tibems_status::TIBEMS_NOT_FOUND | tibems_status::TIBEMS_INVALID_NAME => {
+            Error::new(ErrorKind::NotFound, format!("{kind} {name} not found"))
+        }
+        _ => Error::other(format!("cannot look up {kind} {name}")),
+    }
+}
+
+/// creates and starts a connection through a factory
+unsafe fn create_connection(
+    factory: *mut tibemsConnectionFactory,
+    user: &str,
+    password: &str,
+) -> Result<usize, Error> {
+    let mut connection_pointer: usize = 0;
+    let c_user = CString::new(user).unwrap();
+    let c_password = CString::new(password).unwrap();
+    let status = tibco_ems_sys::tibemsConnectionFactory_CreateConnection(
+        factory,
+        &mut connection_pointer,
+        c_user.as_ptr(),
+        c_password.as_ptr(),
+    );
+    match status {
+        tibems_status::TIBEMS_OK => {
+            trace!("tibemsConnectionFactory_CreateConnection: {status:?}")
+        }
+        _ => {
+            error!("tibemsConnectionFactory_CreateConnection: {status:?}");
+            return Err(Error::new(
+                ErrorKind::NotConnected,
+                "cannot create connection",
+            ));
+        }
+    }
+    let status = tibco_ems_sys::tibemsConnection_Start(connection_pointer);
+    match status {
+        tibems_status::TIBEMS_OK => trace!("tibemsConnection_Start: {status:?}"),
+        _ => {
+            error!("tibemsConnection_Start: {status:?}");
+            return Err(Error::new(
+                ErrorKind::NotConnected,
+                "cannot start connection",
+            ));
+        }
+    }
+    Ok(connection_pointer)
+}
+
+/// open a message consumer for a queue
+fn queue_consumer(
+    session: usize,
+    destination: &Destination,
+    selector: Option<&str>,
+) -> Result<usize, Error> {
+    let consumer: usize;
+    let mut destination_pointer: usize = 0;
+    unsafe {
+        //create destination
+        match destination {
+            Destination::Queue(name) => {
+                let c_destination = CString::new(name.clone()).unwrap();
+                let status = tibco_ems_sys::tibemsDestination_Create(
+                    &mut destination_pointer,
+                    tibemsDestinationType::TIBEMS_QUEUE,
+                    c_destination.as_ptr(),
+                );
+                match status {
+                    tibems_status::TIBEMS_OK => {
+                        trace!("tibemsDestination_Create: {status:?}")
+                    }
+                    _ => {
+                        let status_str = format!("{status:?}");
+                        error!("tibemsDestination_Create: {status_str}");
+                        return Err(Error::new(
+                            ErrorKind::Other,
+                            format!("create destination failed: {status_str}"),
+                        ));
+                    }
+                }
+            }
+            Destination::Topic(name) => {
+                let c_destination = CString::new(name.clone()).unwrap();
+                let status = tibco_ems_sys::tibemsDestination_Create(
+                    &mut destination_pointer,
+                    tibemsDestinationType::TIBEMS_TOPIC,
+                    c_destination.as_ptr(),
+                );
+                match status {
+                    tibems_status::TIBEMS_OK => {
+                        trace!("tibemsDestination_Create: {status:?}")
+                    }
+                    _ => {
+                        let status_str = format!("{status:?}");
+                        error!("tibemsDestination_Create: {status_str}");
+                        return Err(Error::new(
+                            ErrorKind::Other,
+                            format!("create destination failed: {status_str}"),
+                        ));
+                    }
+                }
+            }
+        }
+        //open consumer
+        let mut consumer_pointer: usize = 0;
+        let c_selector: CString = match selector {
+            Some(val) => CString::new(val).unwrap(),
+            _ => CString::new("".to_string()).unwrap(),
+        };
+        let status = tibco_ems_sys::tibemsSession_CreateConsumer(
+            session,
+            &mut consumer_pointer,
+            destination_pointer,
+            c_selector.as_ptr(),
+            tibco_ems_sys::tibems_bool::TIBEMS_TRUE,
+        );
+        match status {
+            tibems_status::TIBEMS_OK => trace!("tibemsSession_CreateConsumer: {status:?}"),
+            _ => {
+                let status_str = format!("{status:?}");
+                error!("tibemsSession_CreateConsumer: {status_str}");
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!("create consumer failed: {status_str}"),
+                ));
+            }
+        }
+        consumer = consumer_pointer;
+    }
+    Ok(consumer)
+}
+
+/// open a message consumer for a topic
+fn topic_consumer(
+    session: usize,
+    destination: &Destination,
+    subscription_name: &str,
+    selector: Option<&str>,
+) -> Result<usize, Error> {
+    let consumer: usize;
+    let mut destination_pointer: usize = 0;
+    unsafe {
+        //create destination
+        match destination {
+            Destination::Topic(name) => {
+                let c_destination = CString::new(name.clone()).unwrap();
+                let status = tibco_ems_sys::tibemsDestination_Create(
+                    &mut destination_pointer,
+                    tibemsDestinationType::TIBEMS_TOPIC,
+                    c_destination.as_ptr(),
+                );
+                match status {
+                    tibems_status::TIBEMS_OK => {
+                        trace!("tibemsDestination_Create: {status:?}")
+                    }
+                    _ => {
+                        let status_str = format!("{status:?}");
+                        error!("tibemsDestination_Create: {status_str}");
+                        return Err(Error::new(
+                            ErrorKind::Other,
+                            format!("create destination failed: {status_str}"),
+                        ));
+                    }
+                }
+            }
+            Destination::Queue(_) => {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    "destination is not of type topic",
+                ));
+            }
+        }
+        //open consumer
+        let mut consumer_pointer: usize = 0;
+        let c_subscription_name = CString::new((*subscription_name).to_string()).unwrap();
+        let c_selector: CString = match selector {
+            Some(val) => CString::new(val).unwrap(),
+            _ => CString::new("".to_string()).unwrap(),
+        };
+        let status = tibco_ems_sys::tibemsSession_CreateSharedConsumer(
+            session,
+            &mut consumer_pointer,
+            destination_pointer,
+            c_subscription_name.as_ptr(),
+            c_selector.as_ptr(),
+        );
+        match status {
+            tibems_status::TIBEMS_OK => {
+                trace!("tibemsSession_CreateSharedConsumer: {status:?}")
+            }
+            _ => {
+                let status_str = format!("{status:?}");
+                error!("tibemsSession_CreateSharedConsumer: {status_str}");
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!("create consumer failed: {status_str}"),
+                ));
+            }
+        }
+        consumer = consumer_pointer;
+    }
+    Ok(consumer)
+}
+
+/// open a message consumer for a durable topic subscription
+fn topic_durable_consumer(
+    session: usize,
+    destination: &Destination,
+    durable_name: &str,
+    selector: Option<&str>,
+) -> Result<usize, Error> {
+    let consumer: usize;
+    let mut destination_pointer: usize = 0;
+    unsafe {
+        //create destination
+        match destination {
+            Destination::Topic(name) => {
+                let c_destination = CString::new(name.clone()).unwrap();
+                let status = tibco_ems_sys::tibemsDestination_Create(
+                    &mut destination_pointer,
+                    tibemsDestinationType::TIBEMS_TOPIC,
+                    c_destination.as_ptr(),
+                );
+                match status {
+                    tibems_status::TIBEMS_OK => {
+                        trace!("tibemsDestination_Create: {status:?}")
+                    }
+                    _ => {
+                        let status_str = format!("{status:?}");
+                        error!("tibemsDestination_Create: {status_str}");
+                        return Err(Error::new(
+                            ErrorKind::Other,
+                            format!("create destination failed: {status_str}"),
+                        ));
+                    }
+                }
+            }
+            Destination::Queue(_) => {
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    "destination is not of type topic",
+                ));
+            }
+        }
+        //open consumer
+        let mut consumer_pointer: usize = 0;
+        let c_durable_name = CString::new((*durable_name).to_string()).unwrap();
+        let c_selector: CString = match selector {
+            Some(val) => CString::new(val).unwrap(),
+            _ => CString::new("".to_string()).unwrap(),
+        };
+        let status = tibco_ems_sys::tibemsSession_CreateSharedDurableConsumer(
+            session,
+            &mut consumer_pointer,
+            destination_pointer,
+            c_durable_name.as_ptr(),
+            c_selector.as_ptr(),
+        );
+        match status {
+            tibems_status::TIBEMS_OK => {
+                trace!("tibemsSession_CreateSharedDurableConsumer: {status:?}")
+            }
+            _ => {
+                let status_str = format!("{status:?}");
+                error!("tibemsSession_CreateSharedDurableConsumer: {status_str}");
+                return Err(Error::new(
+                    ErrorKind::Other,
+                    format!("create consumer failed: {status_str}"),
+                ));
+            }
+        }
+        consumer = consumer_pointer;
+    }
+    Ok(consumer)
+}
+
+#[cfg(feature = "tracing")]
+fn add_trace_to_message(message: &mut Message) -> impl opentelemetry::trace::Span {
+    let tracer_provider = opentelemetry::global::tracer_provider();
+    use opentelemetry::sdk::trace::IdGenerator;
+    use opentelemetry::sdk::trace::RandomIdGenerator;
+    use opentelemetry::trace::Span;
+    use opentelemetry::trace::SpanId;
+    use opentelemetry::trace::TraceId;
+    use opentelemetry::trace::Tracer;
+    use opentelemetry::trace::TracerProvider;
+    let tracer = tracer_provider.versioned_tracer("ems", Some("0.5"), None);
+    let span = tracer.start("send");
+    let id_generator = RandomIdGenerator::default();
+    let headers = match message {
+        Message::BytesMessage(b) => b.header.as_mut(),
+        Message::MapMessage(m) => m.header.as_mut(),
+        Message::TextMessage(t) => t.header.as_mut(),
+        Message::ObjectMessage(o) => o.header.as_mut(),
+    };
+    let ctx = span.span_context();
+    let span_id = if ctx.span_id() == SpanId::INVALID {
+        id_generator.new_span_id()
+    } else {
+        ctx.span_id()
+    };
+    let trace_id = if ctx.trace_id() == TraceId::INVALID {
+        id_generator.new_trace_id()
+    } else {
+        ctx.trace_id()
+    };
+    if let Some(e) = headers {
+        e.insert(
+            "spanId".to_string(),
+            TypedValue::String(span_id.to_string()),
+        );
+        e.insert(
+            "traceId".to_string(),
+            TypedValue::String(trace_id.to_string()),
+        );
+    };
+    span
+}
+
+fn build_message_pointer_from_message(message: &Message) -> usize {
+    let mut msg_pointer: usize = 0;
+    unsafe {
+        match message {
+            Message::TextMessage(msg) => {
+                let status = tibco_ems_sys::tibemsTextMsg_Create(&mut msg_pointer);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsTextMsg_Create: {status:?}"),
+                    _ => error!("tibemsTextMsg_Create: {status:?}"),
+                }
+                let c_text = CString::new(msg.body.clone()).unwrap();
+                let status = tibco_ems_sys::tibemsTextMsg_SetText(msg_pointer, c_text.as_ptr());
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsTextMsg_SetText: {status:?}"),
+                    _ => error!("tibemsTextMsg_SetText: {status:?}"),
+                }
+            }
+            Message::BytesMessage(msg) => {
+                let status = tibco_ems_sys::tibemsBytesMsg_Create(&mut msg_pointer);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsBytesMsg_Create: {status:?}"),
+                    _ => error!("tibemsBytesMsg_Create: {status:?}"),
+                }
+                let content = msg.body.clone();
+                let body_size = content.len();
+                if body_size > 0 {
+                    let body_ptr = content.as_ptr() as *const c_void;
+                    let status = tibco_ems_sys::tibemsBytesMsg_SetBytes(
+                        msg_pointer,
+                        body_ptr,
+                        body_size as u32,
+                    );
+                    match status {
+                        tibems_status::TIBEMS_OK => trace!("tibemsBytesMsg_SetBytes: {status:?}"),
+                        _ => error!("tibemsBytesMsg_SetBytes: {status:?}"),
+                    }
+                }
+            }
+            Message::ObjectMessage(msg) => {
+                let status = tibco_ems_sys::tibemsObjectMsg_Create(&mut msg_pointer);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsObjectMsg_Create: {status:?}"),
+                    _ => error!("tibemsObjectMsg_Create: {status:?}"),
+                }
+                let content = msg.body.clone();
+                let body_size = content.len();
+                let body_ptr = content.as_ptr() as *const c_void;
+                let status = tibco_ems_sys::tibemsObjectMsg_SetObjectBytes(
+                    msg_pointer,
+                    body_ptr,
+                    body_size as u32,
+                );
+                match status {
+                    tibems_status::TIBEMS_OK => {
+                        trace!("tibemsObjectMsg_SetObjectBytes: {status:?}")
+                    }
+                    _ => error!("tibemsObjectMsg_SetObjectBytes: {status:?}"),
+                }
+            }
+            Message::MapMessage(msg) => {
+                let status = tibco_ems_sys::tibemsMapMsg_Create(&mut msg_pointer);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMapMsg_Create: {status:?}"),
+                    _ => error!("tibemsMapMsg_Create: {status:?}"),
+                }
+                for (key, val) in msg.body.clone() {
+                    let c_name = CString::new(key).unwrap();
+                    match val {
+                        TypedValue::Boolean(value) => {
+                            let status = if value {
+                                tibco_ems_sys::tibemsMapMsg_SetBoolean(
+                                    msg_pointer,
+                                    c_name.as_ptr(),
+                                    tibems_bool::TIBEMS_TRUE,
+                                )
+                            } else {
+                                tibco_ems_sys::tibemsMapMsg_SetBoolean(
+                                    msg_pointer,
+                                    c_name.as_ptr(),
+                                    tibems_bool::TIBEMS_FALSE,
+                                )
+                            };
+                            match status {
+                                tibems_status::TIBEMS_OK => {
+                                    trace!("tibemsMapMsg_SetBoolean: {status:?}")
+                                }
+                                _ => error!("tibemsMapMsg_SetBoolean: {status:?}"),
+                            }
+                        }
+                        TypedValue::String(value) => {
+                            let c_value = CString::new(value).unwrap();
+                            let status = tibco_ems_sys::tibemsMapMsg_SetString(
+                                msg_pointer,
+                                c_name.as_ptr(),
+                                c_value.as_ptr(),
+                            );
+                            match status {
+                                tibems_status::TIBEMS_OK => {
+                                    trace!("tibemsMapMsg_SetString: {status:?}")
+                                }
+                                _ => error!("tibemsMapMsg_SetString: {status:?}"),
+                            }
+                        }
+                        TypedValue::Integer(value) => {
+                            let status = tibco_ems_sys::tibemsMapMsg_SetInt(
+                                msg_pointer,
+                                c_name.as_ptr(),
+                                value,
+                            );
+                            match status {
+                                tibems_status::TIBEMS_OK => {
+                                    trace!("tibemsMapMsg_SetInt: {status:?}")
+                                }
+                                _ => error!("tibemsMapMsg_SetInt: {status:?}"),
+                            }
+                        }
+                        TypedValue::Long(value) => {
+                            let status = tibco_ems_sys::tibemsMapMsg_SetLong(
+                                msg_pointer,
+                                c_name.as_ptr(),
+                                value,
+                            );
+                            match status {
+                                tibems_status::TIBEMS_OK => {
+                                    trace!("tibemsMapMsg_SetLong: {status:?}")
+                                }
+                                _ => error!("tibemsMapMsg_SetLong: {status:?}"),
+                            }
+                        }
+                        TypedValue::Float(value) => {
+                            let status = tibco_ems_sys::tibemsMapMsg_SetFloat(
+                                msg_pointer,
+                                c_name.as_ptr(),
+                                value,
+                            );
+                            match status {
+                                tibems_status::TIBEMS_OK => {
+                                    trace!("tibemsMapMsg_SetFloat: {status:?}")
+                                }
+                                _ => error!("tibemsMapMsg_SetFloat: {status:?}"),
+                            }
+                        }
+
+                        TypedValue::Double(value) => {
+                            let status = tibco_ems_sys::tibemsMapMsg_SetDouble(
+                                msg_pointer,
+                                c_name.as_ptr(),
+                                value,
+                            );
+                            match status {
+                                tibems_status::TIBEMS_OK => {
+                                    trace!("tibemsMapMsg_SetDouble: {status:?}")
+                                }
+                                _ => error!("tibemsMapMsg_SetDouble: {status:?}"),
+                            }
+                        }
+                        TypedValue::Binary(_value) => {
+                            //TODO implement
+                            // let status = tibco_ems_sys::tibemsMapMsg_SetBytes(message: usize, name: *const c_char, bytes: *mut c_void, bytesSize: u64)Long(msg, c_name.as_ptr(), value);
+                            // match status {
+                            // tibems_status::TIBEMS_OK => trace!("tibemsMapMsg_SetLong: {:?}",status),
+                            // _ => error!("tibemsMapMsg_SetLong: {:?}",status),
+                            // }
+                        }
+                        _ => {
+                            panic!("missing map message type implementation for {val:?}");
+                        }
+                    }
+                }
+            }
+        }
+        //set header
+        let header = match message {
+            Message::TextMessage(msg) => msg.header.clone(),
+            Message::BytesMessage(msg) => msg.header.clone(),
+            Message::MapMessage(msg) => msg.header.clone(),
+            Message::ObjectMessage(msg) => msg.header.clone(),
+        };
+        if let Some(headers) = header {
+            //look for correlation id
+            if let Some(correlation_id) = headers.get("CorrelationID") {
+                let correlation_id_val = extract!(TypedValue::String(_), correlation_id)
+                    .expect("extract correlation id");
+                let c_correlation_id = CString::new(correlation_id_val.as_str()).unwrap();
+                let status = tibco_ems_sys::tibemsMsg_SetCorrelationID(
+                    msg_pointer,
+                    c_correlation_id.as_ptr(),
+                );
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_SetCorrelationId: {status:?}"),
+                    _ => error!("tibemsMsg_SetCorrelationId: {status:?}"),
+                }
+            }
+            //look for jms type
+            if let Some(jms_type) = headers.get("JMSType") {
+                let jms_type_val =
+                    extract!(TypedValue::String(_), jms_type).expect("extract correlation id");
+                let c_jms_type = CString::new(jms_type_val.as_str()).unwrap();
+                let status = tibco_ems_sys::tibemsMsg_SetType(msg_pointer, c_jms_type.as_ptr());
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_SetType: {status:?}"),
+                    _ => error!("tibemsMsg_SetType: {status:?}"),
+                }
+            }
+            //do other headers (also do correlation id again as custom header)
+            for (key, val) in &headers {
+                let c_name = CString::new(key.to_string()).unwrap();
+                match val {
+                    TypedValue::String(value) => {
+                        let c_val = CString::new(value.as_bytes()).unwrap();
+                        let status = tibco_ems_sys::tibemsMsg_SetStringProperty(
+                            msg_pointer,
+                            c_name.as_ptr(),
+                            c_val.as_ptr(),
+                        );
+                        match status {
+                            tibems_status::TIBEMS_OK => {
+                                trace!("tibemsMsg_SetStringProperty: {status:?}")
+                            }
+                            _ => error!("tibemsMsg_SetStringProperty: {status:?}"),
+                        }
+                    }
+                    TypedValue::Boolean(value) => {
+                        let status = if *value {
+                            tibco_ems_sys::tibemsMsg_SetBooleanProperty(
+                                msg_pointer,
+                                c_name.as_ptr(),
+                                tibems_bool::TIBEMS_TRUE,
+                            )
+                        } else {
+                            tibco_ems_sys::tibemsMsg_SetBooleanProperty(
+                                msg_pointer,
+                                c_name.as_ptr(),
+                                tibems_bool::TIBEMS_FALSE,
+                            )
+                        };
+                        match status {
+                            tibems_status::TIBEMS_OK => {
+                                trace!("tibemsMsg_SetBooleanProperty: {status:?}")
+                            }
+                            _ => error!("tibemsMsg_SetBooleanProperty: {status:?}"),
+                        }
+                    }
+                    TypedValue::Integer(value) => {
+                        let status = tibco_ems_sys::tibemsMsg_SetIntProperty(
+                            msg_pointer,
+                            c_name.as_ptr(),
+                            *value,
+                        );
+                        match status {
+                            tibems_status::TIBEMS_OK => {
+                                trace!("tibemsMsg_SetIntProperty: {status:?}")
+                            }
+                            _ => error!("tibemsMsg_SetIntProperty: {status:?}"),
+                        }
+                    }
+                    TypedValue::Long(value) => {
+                        let status = tibco_ems_sys::tibemsMsg_SetLongProperty(
+                            msg_pointer,
+                            c_name.as_ptr(),
+                            *value,
+                        );
+                        match status {
+                            tibems_status::TIBEMS_OK => {
+                                trace!("tibemsMsg_SetLongProperty: {status:?}")
+                            }
+                            _ => error!("tibemsMsg_SetLongProperty: {status:?}"),
+                        }
+                    }
+                    _ => {
+                        panic!("missing property type implementation for {val:?}");
+                    }
+                }
+            }
+        }
+    }
+    msg_pointer
+}
+
+fn build_message_from_pointer(msg_pointer: usize) -> Message {
+    let mut msg: Message;
+    let mut header: HashMap<String, TypedValue> = HashMap::new();
+    unsafe {
+        let mut msg_type: tibemsMsgType = tibemsMsgType::TIBEMS_TEXT_MESSAGE;
+        let status = tibco_ems_sys::tibemsMsg_GetBodyType(msg_pointer, &mut msg_type);
+        match status {
+            tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetBodyType: {status:?}"),
+            _ => error!("tibemsMsg_GetBodyType: {status:?}"),
+        }
+        match msg_type {
+            tibemsMsgType::TIBEMS_TEXT_MESSAGE => {
+                let buf_vec: Vec<i8> = vec![0; 0];
+                let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+                let status = tibco_ems_sys::tibemsTextMsg_GetText(msg_pointer, &buf_ref);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsTextMsg_GetText: {status:?}"),
+                    _ => error!("tibemsTextMsg_GetText: {status:?}"),
+                }
+                let content = CStr::from_ptr(buf_ref).to_str().unwrap();
+                let status = tibco_ems_sys::tibemsMsg_GetMessageID(msg_pointer, &buf_ref);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetMessageID: {status:?}"),
+                    _ => error!("tibemsMsg_GetMessageID: {status:?}"),
+                }
+                let message_id = CStr::from_ptr(buf_ref).to_str().unwrap();
+                header.insert(
+                    "MessageID".to_string(),
+                    TypedValue::String(message_id.to_string()),
+                );
+                msg = Message::TextMessage(TextMessage {
+                    body: content.to_string(),
+                    header: None,
+                    pointer: Some(msg_pointer),
+                    destination: None,
+                    reply_to: None,
+                });
+            }
+            tibemsMsgType::TIBEMS_MAP_MESSAGE => {
+                let buf_vec: Vec<i8> = vec![0; 0];
+                let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+                let status = tibco_ems_sys::tibemsMsg_GetMessageID(msg_pointer, &buf_ref);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetMessageID: {status:?}"),
+                    _ => error!("tibemsMsg_GetMessageID: {status:?}"),
+                }
+                //admin messages do not have a message id
+                if !buf_vec.is_empty() {
+                    let message_id = CStr::from_ptr(buf_ref).to_str().unwrap();
+                    header.insert(
+                        "MessageID".to_string(),
+                        TypedValue::String(message_id.to_string()),
+                    );
+                }
+                let mut names_pointer: usize = 0;
+                trace!("tibemsMapMsg_GetMapNames");
+                let status =
+                    tibco_ems_sys::tibemsMapMsg_GetMapNames(msg_pointer, &mut names_pointer);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMapMsg_GetMapNames: {status:?}"),
+                    _ => error!("tibemsMapMsg_GetMapNames: {status:?}"),
+                }
+                let mut body_entries: HashMap<String, TypedValue> = HashMap::new();
+                loop {
+                    let buf_vec: Vec<i8> = vec![0; 0];
+                    let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+                    let status = tibco_ems_sys::tibemsMsgEnum_GetNextName(names_pointer, &buf_ref);
+                    match status {
+                        tibems_status::TIBEMS_OK => {
+                            let header_name = CStr::from_ptr(buf_ref).to_str().unwrap();
+                            trace!("getting value for property: {}", header_name);
+                            let mut val_buf_vec: Vec<i8> = vec![0; 0];
+                            let mut val_buf_ref: *mut std::os::raw::c_char =
+                                val_buf_vec.as_mut_ptr();
+                            let status = tibco_ems_sys::tibemsMapMsg_GetString(
+                                msg_pointer,
+                                buf_ref,
+                                &mut val_buf_ref,
+                            );
+                            match status {
+                                tibems_status::TIBEMS_OK => {
+                                    trace!("tibemsMapMsg_GetString: {status:?}");
+                                    if !val_buf_ref.is_null() {
+                                        let header_value =
+                                            CStr::from_ptr(val_buf_ref).to_str().unwrap();
+                                        body_entries.insert(
+                                            header_name.to_string(),
+                                            TypedValue::String(header_value.to_string()),
+                                        );
+                                    }
+                                }
+                                tibems_status::TIBEMS_CONVERSION_FAILED => {
+                                    //it must be a map msg inside
+                                    let mut msg2: usize = 0;
+                                    let status = tibco_ems_sys::tibemsMapMsg_GetMapMsg(
+                                        msg_pointer,
+                                        buf_ref,
+                                        &mut msg2,
+                                    );
+                                    match status {
+                                        tibems_status::TIBEMS_CONVERSION_FAILED => {
+                                            //it must be something binary, ingore it for now
+                                            trace!(
+                                                "tibemsMapMsg_GetMapMsg: ignoring unkown content"
+                                            );
+                                        }
+                                        tibems_status::TIBEMS_OK => {
+                                            trace!("tibemsMapMsg_GetMapMsg: {status:?}");
+                                            let mut raw_message = build_message_from_pointer(msg2);
+                                            match &mut raw_message {
+                                                Message::TextMessage(_msg) => {}
+                                                Message::ObjectMessage(_msg) => {}
+                                                Message::BytesMessage(_msg) => {}
+                                                Message::MapMessage(msg) => {
+                                                    msg.pointer = None;
+                                                    body_entries.insert(
+                                                        header_name.to_string(),
+                                                        TypedValue::Map(msg.clone()),
+                                                    );
+                                                }
+                                            }
+                                        }
+                                        _ => error!("tibemsMapMsg_GetMapMsg: {status:?}"),
+                                    }
+                                }
+                                _ => error!("tibemsMapMsg_GetString: {status:?}"),
+                            }
+                        }
+                        tibems_status::TIBEMS_NOT_FOUND => {
+                            break;
+                        }
+                        _ => {
+                            println!("tibemsMsgEnum_GetNextName: {status:?}");
+                            break;
+                        }
+                    }
+                }
+                let status = tibco_ems_sys::tibemsMsgEnum_Destroy(names_pointer);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMsgEnum_Destroy: {status:?}"),
+                    _ => error!("tibemsMsgEnum_Destroy: {status:?}"),
+                }
+                msg = Message::MapMessage(MapMessage {
+                    body: body_entries,
+                    header: None,
+                    pointer: Some(msg_pointer),
+                    destination: None,
+                    reply_to: None,
+                });
+            }
+            tibemsMsgType::TIBEMS_BYTES_MESSAGE => {
+                let buf_vec: Vec<i8> = vec![0; 0];
+                let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+                let status = tibco_ems_sys::tibemsMsg_GetMessageID(msg_pointer, &buf_ref);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetMessageID: {status:?}"),
+                    _ => error!("tibemsMsg_GetMessageID: {status:?}"),
+                }
+                let message_id = CStr::from_ptr(buf_ref).to_str().unwrap();
+                header.insert(
+                    "MessageID".to_string(),
+                    TypedValue::String(message_id.to_string()),
+                );
+                //check body length
+                let mut body_length: i32 = 0;
+                let mut body_value: Vec<u8> = vec![0; 0];
+                let status =
+                    tibco_ems_sys::tibemsBytesMsg_GetBodyLength(msg_pointer, &mut body_length);
+                match status {
+                    tibems_status::TIBEMS_OK => {
+                        trace!("tibemsBytesMsg_GetBodyLength: {status:?}");
+                        if body_length > 0 {
+                            //extract body
+                            let buf_vec: Vec<u8> = vec![0; 0];
+                            let buf_ref: *const std::os::raw::c_uchar = buf_vec.as_ptr();
+                            let mut result_size: u32 = 0;
+                            let status = tibco_ems_sys::tibemsBytesMsg_GetBytes(
+                                msg_pointer,
+                                &buf_ref,
+                                &mut result_size,
+                            );
+                            match status {
+                                tibems_status::TIBEMS_OK => {
+                                    trace!("tibemsBytesMsg_GetBytes: {status:?}")
+                                }
+                                _ => error!("tibemsBytesMsg_GetBytes: {status:?}"),
+                            }
+                            let slice = core::slice::from_raw_parts(buf_ref, result_size as usize);
+                            body_value = slice.to_vec();
+                        }
+                    }
+                    _ => error!("tibemsBytesMsg_GetBodyLength: {status:?}"),
+                }
+                msg = Message::BytesMessage(BytesMessage {
+                    body: body_value,
+                    header: None,
+                    pointer: Some(msg_pointer),
+                    destination: None,
+                    reply_to: None,
+                });
+            }
+            tibemsMsgType::TIBEMS_OBJECT_MESSAGE => {
+                let buf_vec: Vec<i8> = vec![0; 0];
+                let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+                let status = tibco_ems_sys::tibemsMsg_GetMessageID(msg_pointer, &buf_ref);
+                match status {
+                    tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetMessageID: {status:?}"),
+                    _ => error!("tibemsMsg_GetMessageID: {status:?}"),
+                }
+                let message_id = CStr::from_ptr(buf_ref).to_str().unwrap();
+                header.insert(
+                    "MessageID".to_string(),
+                    TypedValue::String(message_id.to_string()),
+                );
+                //extract body
+                let buf_vec: Vec<u8> = vec![0; 0];
+                let buf_ref: *const std::os::raw::c_uchar = buf_vec.as_ptr();
+                let mut result_size: u32 = 0;
+                let status = tibco_ems_sys::tibemsObjectMsg_GetObjectBytes(
+                    msg_pointer,
+                    &buf_ref,
+                    &mut result_size,
+                );
+                match status {
+                    tibems_status::TIBEMS_OK => {
+                        trace!("tibemsObjectMsg_GetObjectBytes: {status:?}")
+                    }
+                    _ => error!("tibemsObjectMsg_GetObjectBytes: {status:?}"),
+                }
+                let slice = core::slice::from_raw_parts(buf_ref, result_size as usize);
+                msg = Message::ObjectMessage(ObjectMessage {
+                    body: slice.to_vec(),
+                    header: None,
+                    pointer: Some(msg_pointer),
+                    destination: None,
+                    reply_to: None,
+                });
+            }
+            _ => {
+                //unknown
+                panic!("BodyType {msg_type:?} not implemented");
+            }
+        }
+        //add correlation id to header
+        let buf_vec: Vec<i8> = vec![0; 0];
+        let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+        let status = tibco_ems_sys::tibemsMsg_GetCorrelationID(msg_pointer, &buf_ref);
+        match status {
+            tibems_status::TIBEMS_OK => {
+                trace!("tibemsMsg_GetCorrelationID: {status:?}");
+                // check for null pointer (when no correlation id was set)
+                if !buf_ref.is_null() {
+                    let correlation_id = CStr::from_ptr(buf_ref).to_str().unwrap();
+                    header.insert(
+                        "CorrelationID".to_string(),
+                        TypedValue::String(correlation_id.to_string()),
+                    );
+                }
+            }
+            _ => trace!("tibemsMsg_GetCorrelationID: {status:?}"),
+        }
+        // fetch header
+        let mut header_enumeration: usize = 0;
+        let status =
+            tibco_ems_sys::tibemsMsg_GetPropertyNames(msg_pointer, &mut header_enumeration);
+        match status {
+            tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetPropertyNames: {status:?}"),
+            _ => error!("tibemsMsg_GetPropertyNames: {status:?}"),
+        }
+        loop {
+            let buf_vec: Vec<i8> = vec![0; 0];
+            let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+            let status = tibco_ems_sys::tibemsMsgEnum_GetNextName(header_enumeration, &buf_ref);
+            match status {
+                tibems_status::TIBEMS_OK => {
+                    let header_name = CStr::from_ptr(buf_ref).to_str().unwrap();
+                    let val_buf_vec: Vec<i8> = vec![0; 0];
+                    let val_buf_ref: *const std::os::raw::c_char = val_buf_vec.as_ptr();
+                    let mut bool_result: tibems_bool = tibems_bool::TIBEMS_TRUE;
+                    //check for ems compress header
+                    if header_name == "JMS_TIBCO_COMPRESS" {
+                        let status = tibco_ems_sys::tibemsMsg_GetBooleanProperty(
+                            msg_pointer,
+                            buf_ref,
+                            &mut bool_result,
+                        );
+                        match status {
+                            tibems_status::TIBEMS_OK => {
+                                trace!("tibemsMsg_GetBooleanProperty: {status:?}");
+                                let value = match bool_result {
+                                    tibems_bool::TIBEMS_TRUE => true,
+                                    tibems_bool::TIBEMS_FALSE => false,
+                                };
+                                header.insert(header_name.to_string(), TypedValue::Boolean(value));
+                            }
+                            _ => error!("tibemsMsg_GetBooleanProperty: {status:?}"),
+                        }
+                    } else {
+                        let status = tibco_ems_sys::tibemsMsg_GetStringProperty(
+                            msg_pointer,
+                            buf_ref,
+                            &val_buf_ref,
+                        );
+                        match status {
+                            tibems_status::TIBEMS_OK => {
+                                trace!("tibemsMsg_GetStringProperty: {status:?}")
+                            }
+                            _ => error!("tibemsMsg_GetStringProperty: {status:?}"),
+                        }
+                        let header_value = CStr::from_ptr(val_buf_ref).to_str().unwrap();
+                        header.insert(
+                            header_name.to_string(),
+                            TypedValue::String(header_value.to_string()),
+                        );
+                    }
+                }
+                tibems_status::TIBEMS_NOT_FOUND => {
+                    break;
+                }
+                _ => {
+                    println!("tibemsMsgEnum_GetNextName: {status:?}");
+                    break;
+                }
+            }
+        }
+        let status = tibco_ems_sys::tibemsMsgEnum_Destroy(header_enumeration);
+        match status {
+            tibems_status::TIBEMS_OK => trace!("tibemsMsgEnum_Destroy: {status:?}"),
+            _ => error!("tibemsMsgEnum_Destroy: {status:?}"),
+        }
+        //add JMSType to header
+        let val_buf_vec: Vec<i8> = vec![0; 0];
+        let val_buf_ref: *const std::os::raw::c_char = val_buf_vec.as_ptr();
+        let status = tibco_ems_sys::tibemsMsg_GetType(msg_pointer, &val_buf_ref);
+        match status {
+            tibems_status::TIBEMS_OK => {
+                trace!("tibemsMsg_GetType: {status:?}");
+                // check for null pointer (when no correlation id was set)
+                if !val_buf_ref.is_null() {
+                    let header_value = CStr::from_ptr(val_buf_ref).to_str().unwrap();
+                    if !header_value.is_empty() {
+                        header.insert(
+                            "JMSType".to_string(),
+                            TypedValue::String(header_value.to_string()),
+                        );
+                    }
+                }
+            }
+            _ => error!("tibemsMsg_GetType: {status:?}"),
+        }
+        //add header to message
+        match &mut msg {
+            Message::TextMessage(msg) => msg.header = Some(header),
+            Message::BytesMessage(msg) => msg.header = Some(header),
+            Message::MapMessage(msg) => msg.header = Some(header),
+            Message::ObjectMessage(msg) => msg.header = Some(header),
+        }
+        // look for JMSDestination header
+        let mut jms_destination: usize = 0;
+        let status = tibco_ems_sys::tibemsMsg_GetDestination(msg_pointer, &mut jms_destination);
+        match status {
+            tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetDestination: {status:?}"),
+            _ => error!("tibemsMsg_GetDestination: {status:?}"),
+        }
+        if jms_destination != 0 {
+            //has a destination
+            let mut destination_type = tibemsDestinationType::TIBEMS_UNKNOWN;
+            let status =
+                tibco_ems_sys::tibemsDestination_GetType(jms_destination, &mut destination_type);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsDestination_GetType: {status:?}"),
+                _ => error!("tibemsDestination_GetType: {status:?}"),
+            }
+            let buf_size = 1024;
+            let buf_vec: Vec<i8> = vec![0; buf_size];
+            let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+            let status =
+                tibco_ems_sys::tibemsDestination_GetName(jms_destination, buf_ref, buf_size);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsDestination_GetName: {status:?}"),
+                _ => error!("tibemsDestination_GetName: {status:?}"),
+            }
+            let destination_name: String = CStr::from_ptr(buf_ref).to_str().unwrap().to_string();
+            let jms_destination_obj: Option<Destination> = match destination_type {
+                tibemsDestinationType::TIBEMS_QUEUE => Some(Destination::Queue(destination_name)),
+                tibemsDestinationType::TIBEMS_TOPIC => Some(Destination::Topic(destination_name)),
+                _ => {
+                    //ignore unknown type
+                    None
+                }
+            };
+            //add replyTo to message
+            match &mut msg {
+                Message::TextMessage(msg) => msg.destination = jms_destination_obj,
+                Message::BytesMessage(msg) => msg.destination = jms_destination_obj,
+                Message::MapMessage(msg) => msg.destination = jms_destination_obj,
+                Message::ObjectMessage(msg) => msg.destination = jms_destination_obj,
+            }
+        }
+        // look for replyTo header
+        let mut reply_destination: usize = 0;
+        let status = tibco_ems_sys::tibemsMsg_GetReplyTo(msg_pointer, &mut reply_destination);
+        match status {
+            tibems_status::TIBEMS_OK => trace!("tibemsMsg_GetReplyTo: {status:?}"),
+            _ => error!("tibemsMsg_GetReplyTo: {status:?}"),
+        }
+        if reply_destination != 0 {
+            //has a destination
+            let mut destination_type = tibemsDestinationType::TIBEMS_UNKNOWN;
+            let status =
+                tibco_ems_sys::tibemsDestination_GetType(reply_destination, &mut destination_type);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsDestination_GetType: {status:?}"),
+                _ => error!("tibemsDestination_GetType: {status:?}"),
+            }
+            let buf_size = 1024;
+            let buf_vec: Vec<i8> = vec![0; buf_size];
+            let buf_ref: *const std::os::raw::c_char = buf_vec.as_ptr();
+            let status =
+                tibco_ems_sys::tibemsDestination_GetName(reply_destination, buf_ref, buf_size);
+            match status {
+                tibems_status::TIBEMS_OK => trace!("tibemsDestination_GetName: {status:?}"),
+                _ => error!("tibemsDestination_GetName: {status:?}"),
+            }
+            let destination_name: String = CStr::from_ptr(buf_ref).to_str().unwrap().to_string();
+            let reply_destination_obj: Option<Destination> = match destination_type {
+                tibemsDestinationType::TIBEMS_QUEUE => Some(Destination::Queue(destination_name)),
+                tibemsDestinationType::TIBEMS_TOPIC => Some(Destination::Topic(destination_name)),
+                _ => {
+                    //ignore unknown type
+                    None
+                }
+            };
+            //add replyTo to message
+            match &mut msg {
+                Message::TextMessage(msg) => msg.reply_to = reply_destination_obj,
+                Message::BytesMessage(msg) => msg.reply_to = reply_destination_obj,
+                Message::MapMessage(msg) => msg.reply_to = reply_destination_obj,
+                Message::ObjectMessage(msg) => msg.reply_to = reply_destination_obj,
+            }
+        }
+    }
+    msg
+}
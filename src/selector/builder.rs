@@ -0,0 +1,274 @@
+//! Builder for selectors.
+//!
+//! Values are rendered as escaped literals, so they can never change the structure
+//! of the selector. Floating point values are converted with `Literal::try_from`,
+//! as selectors have no literal for infinity or NaN.
+
+use super::{CompareOperator, Expression, Keyword, Literal, Selector, SelectorError};
+
+/// a message header or property to build a condition on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    name: String,
+}
+
+impl Selector {
+    /// starts a condition on a message header or property
+    ///
+    /// selectors have no quoting for identifiers, so a name which is not a valid
+    /// identifier is rejected.
+    pub fn prop(name: &str) -> Result<Property, SelectorError> {
+        let mut chars = name.chars();
+        let valid = chars
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+            && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+            && Keyword::from_identifier(name).is_none();
+        if !valid {
+            return Err(SelectorError {
+                message: format!("invalid identifier '{name}'"),
+                position: 0,
+            });
+        }
+        Ok(Property {
+            name: name.to_string(),
+        })
+    }
+
+    /// both selectors need to match
+    pub fn and(self, other: Selector) -> Selector {
+        Selector::from(Expression::And(
+            Box::new(self.expression),
+            Box::new(other.expression),
+        ))
+    }
+
+    /// either selector needs to match
+    pub fn or(self, other: Selector) -> Selector {
+        Selector::from(Expression::Or(
+            Box::new(self.expression),
+            Box::new(other.expression),
+        ))
+    }
+}
+
+impl std::ops::Not for Selector {
+    type Output = Selector;
+
+    /// the selector must not match
+    fn not(self) -> Selector {
+        Selector::from(Expression::Not(Box::new(self.expression)))
+    }
+}
+
+impl From<Expression> for Selector {
+    fn from(expression: Expression) -> Self {
+        Selector {
+            text: expression.to_string(),
+            expression,
+        }
+    }
+}
+
+impl From<Selector> for String {
+    fn from(selector: Selector) -> Self {
+        selector.text
+    }
+}
+
+impl AsRef<str> for Selector {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Property {
+    fn identifier(&self) -> Box<Expression> {
+        Box::new(Expression::Identifier(self.name.clone()))
+    }
+
+    fn compare(self, operator: CompareOperator, value: impl Into<Literal>) -> Selector {
+        Selector::from(Expression::Compare(
+            self.identifier(),
+            operator,
+            Box::new(Expression::Literal(value.into())),
+        ))
+    }
+
+    /// `property = value`
+    pub fn eq(self, value: impl Into<Literal>) -> Selector {
+        self.compare(CompareOperator::Equal, value)
+    }
+
+    /// `property <> value`
+    pub fn ne(self, value: impl Into<Literal>) -> Selector {
+        self.compare(CompareOperator::NotEqual, value)
+    }
+
+    /// `property < value`
+    pub fn lt(self, value: impl Into<Literal>) -> Selector {
+        self.compare(CompareOperator::Less, value)
+    }
+
+    /// `property <= value`
+    pub fn le(self, value: impl Into<Literal>) -> Selector {
+        self.compare(CompareOperator::LessOrEqual, value)
+    }
+
+    /// `property > value`
+    pub fn gt(self, value: impl Into<Literal>) -> Selector {
+        self.compare(CompareOperator::Greater, value)
+    }
+
+    /// `property >= value`
+    pub fn ge(self, value: impl Into<Literal>) -> Selector {
+        self.compare(CompareOperator::GreaterOrEqual, value)
+    }
+
+    fn between_values(
+        self,
+        low: impl Into<Literal>,
+        high: impl Into<Literal>,
+        negated: bool,
+    ) -> Selector {
+        Selector::from(Expression::Between {
+            expression: self.identifier(),
+            low: Box::new(Expression::Literal(low.into())),
+            high: Box::new(Expression::Literal(high.into())),
+            negated,
+        })
+    }
+
+    /// `property BETWEEN low AND high`
+    pub fn between(self, low: impl Into<Literal>, high: impl Into<Literal>) -> Selector {
+        self.between_values(low, high, false)
+    }
+
+    /// `property NOT BETWEEN low AND high`
+    pub fn not_between(self, low: impl Into<Literal>, high: impl Into<Literal>) -> Selector {
+        self.between_values(low, high, true)
+    }
+
+    fn in_values<I, S>(self, values: I, negated: bool) -> Selector
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Selector::from(Expression::In {
+            identifier: self.name,
+            values: values.into_iter().map(Into::into).collect(),
+            negated,
+        })
+    }
+
+    /// `property IN ('a', 'b')`
+    pub fn is_in<I, S>(self, values: I) -> Selector
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.in_values(values, false)
+    }
+
+    /// `property NOT IN ('a', 'b')`
+    pub fn not_in<I, S>(self, values: I) -> Selector
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.in_values(values, true)
+    }
+
+    fn like_pattern(self, pattern: &str, escape: Option<char>, negated: bool) -> Selector {
+        Selector::from(Expression::Like {
+            identifier: self.name,
+            pattern: pattern.to_string(),
+            escape,
+            negated,
+        })
+    }
+
+    /// `property LIKE 'pattern'`, `_` matches one character and `%` any sequence
+    pub fn like(self, pattern: &str) -> Selector {
+        self.like_pattern(pattern, None, false)
+    }
+
+    /// `property LIKE 'pattern' ESCAPE 'escape'`
+    pub fn like_escape(self, pattern: &str, escape: char) -> Selector {
+        self.like_pattern(pattern, Some(escape), false)
+    }
+
+    /// `property NOT LIKE 'pattern'`
+    pub fn not_like(self, pattern: &str) -> Selector {
+        self.like_pattern(pattern, None, true)
+    }
+
+    /// `property IS NULL`
+    pub fn is_null(self) -> Selector {
+        Selector::from(Expression::IsNull {
+            identifier: self.name,
+            negated: false,
+        })
+    }
+
+    /// `property IS NOT NULL`
+    pub fn is_not_null(self) -> Selector {
+        Selector::from(Expression::IsNull {
+            identifier: self.name,
+            negated: true,
+        })
+    }
+}
+
+impl From<&str> for Literal {
+    fn from(val: &str) -> Self {
+        Literal::String(val.to_string())
+    }
+}
+
+impl From<String> for Literal {
+    fn from(val: String) -> Self {
+        Literal::String(val)
+    }
+}
+
+impl From<i32> for Literal {
+    fn from(val: i32) -> Self {
+        Literal::Long(val as i64)
+    }
+}
+
+impl From<i64> for Literal {
+    fn from(val: i64) -> Self {
+        Literal::Long(val)
+    }
+}
+
+impl TryFrom<f32> for Literal {
+    type Error = SelectorError;
+
+    fn try_from(val: f32) -> Result<Self, Self::Error> {
+        Literal::try_from(val as f64)
+    }
+}
+
+impl TryFrom<f64> for Literal {
+    type Error = SelectorError;
+
+    /// selectors have no literal for infinity or NaN, so a non finite value is rejected.
+    fn try_from(val: f64) -> Result<Self, Self::Error> {
+        if !val.is_finite() {
+            return Err(SelectorError {
+                message: format!("invalid literal {val}"),
+                position: 0,
+            });
+        }
+        Ok(Literal::Double(val))
+    }
+}
+
+impl From<bool> for Literal {
+    fn from(val: bool) -> Self {
+        Literal::Boolean(val)
+    }
+}
@@ -0,0 +1,1166 @@
+//! JMS message selectors.
+//!
+//! Parses the SQL-92 subset defined by JMS into an expression tree, so selectors
+//! can be validated before they are handed to the server and evaluated against
+//! messages on the client side.
+
+use super::{Message, TypedValue};
+use std::fmt;
+use std::io::{Error, ErrorKind};
+
+mod builder;
+pub use builder::Property;
+
+/// a parsed message selector
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    text: String,
+    expression: Expression,
+}
+
+/// selector expression tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    /// literal value
+    Literal(Literal),
+    /// message header or property
+    Identifier(String),
+    /// logical negation
+    Not(Box<Expression>),
+    /// logical conjunction
+    And(Box<Expression>, Box<Expression>),
+    /// logical disjunction
+    Or(Box<Expression>, Box<Expression>),
+    /// comparison of two values
+    Compare(Box<Expression>, CompareOperator, Box<Expression>),
+    /// arithmetic operation on two values
+    Arithmetic(Box<Expression>, ArithmeticOperator, Box<Expression>),
+    /// arithmetic negation
+    Negate(Box<Expression>),
+    /// `expression [NOT] BETWEEN low AND high`
+    Between {
+        /// tested value
+        expression: Box<Expression>,
+        /// lower bound, inclusive
+        low: Box<Expression>,
+        /// upper bound, inclusive
+        high: Box<Expression>,
+        /// NOT BETWEEN
+        negated: bool,
+    },
+    /// `identifier [NOT] IN ('a', 'b')`
+    In {
+        /// tested identifier
+        identifier: String,
+        /// allowed values
+        values: Vec<String>,
+        /// NOT IN
+        negated: bool,
+    },
+    /// `identifier [NOT] LIKE 'pattern' [ESCAPE 'c']`
+    Like {
+        /// tested identifier
+        identifier: String,
+        /// pattern, `_` matches one character and `%` any sequence
+        pattern: String,
+        /// escape character for `_` and `%`
+        escape: Option<char>,
+        /// NOT LIKE
+        negated: bool,
+    },
+    /// `identifier IS [NOT] NULL`
+    IsNull {
+        /// tested identifier
+        identifier: String,
+        /// IS NOT NULL
+        negated: bool,
+    },
+}
+
+/// literal values
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    /// string literal
+    String(String),
+    /// exact numeric literal
+    Long(i64),
+    /// approximate numeric literal
+    Double(f64),
+    /// boolean literal
+    Boolean(bool),
+}
+
+/// comparison operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOperator {
+    /// `=`
+    Equal,
+    /// `<>`
+    NotEqual,
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+}
+
+/// arithmetic operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticOperator {
+    /// `+`
+    Add,
+    /// `-`
+    Subtract,
+    /// `*`
+    Multiply,
+    /// `/`
+    Divide,
+}
+
+/// error raised for an invalid selector
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError {
+    /// description of the problem
+    pub message: String,
+    /// character position within the selector where the problem was found
+    pub position: usize,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid selector at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+impl From<SelectorError> for Error {
+    fn from(err: SelectorError) -> Self {
+        Error::new(ErrorKind::InvalidInput, err)
+    }
+}
+
+impl Selector {
+    /// parses a selector string
+    pub fn parse(text: &str) -> Result<Selector, SelectorError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end: text.chars().count(),
+        };
+        let expression = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(Selector {
+                text: text.to_string(),
+                expression,
+            }),
+            Some(token) => Err(SelectorError {
+                message: format!("unexpected {}", token.kind),
+                position: token.position,
+            }),
+        }
+    }
+
+    /// the selector string
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// the parsed expression
+    pub fn expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    /// evaluates the selector against the header and properties of a message
+    ///
+    /// expressions evaluating to unknown, e.g. comparisons with missing properties,
+    /// do not match.
+    pub fn matches(&self, message: &Message) -> bool {
+        let header = match message {
+            Message::TextMessage(msg) => msg.header.as_ref(),
+            Message::BytesMessage(msg) => msg.header.as_ref(),
+            Message::MapMessage(msg) => msg.header.as_ref(),
+            Message::ObjectMessage(msg) => msg.header.as_ref(),
+        };
+        let lookup = |name: &str| -> Value {
+            let Some(header) = header else {
+                return Value::Null;
+            };
+            let value = header.get(name).or_else(|| match name {
+                "JMSMessageID" => header.get("MessageID"),
+                "JMSCorrelationID" => header.get("CorrelationID"),
+                _ => None,
+            });
+            match value {
+                Some(TypedValue::String(val)) => Value::String(val.clone()),
+                Some(TypedValue::Integer(val)) => Value::Long(*val as i64),
+                Some(TypedValue::Long(val)) => Value::Long(*val),
+                Some(TypedValue::Float(val)) => Value::Double(*val as f64),
+                Some(TypedValue::Double(val)) => Value::Double(*val),
+                Some(TypedValue::Boolean(val)) => Value::Boolean(*val),
+                _ => Value::Null,
+            }
+        };
+        evaluate(&self.expression, &lookup) == Value::Boolean(true)
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl fmt::Display for Expression {
+    /// renders the expression as selector string, string literals are escaped
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // operands binding less tight than their operator get parenthesized
+        fn operand(f: &mut fmt::Formatter, expression: &Expression, level: u8) -> fmt::Result {
+            if precedence(expression) < level {
+                write!(f, "({expression})")
+            } else {
+                write!(f, "{expression}")
+            }
+        }
+        fn precedence(expression: &Expression) -> u8 {
+            match expression {
+                Expression::Or(_, _) => 1,
+                Expression::And(_, _) => 2,
+                Expression::Not(_) => 3,
+                Expression::Compare(_, _, _)
+                | Expression::Between { .. }
+                | Expression::In { .. }
+                | Expression::Like { .. }
+                | Expression::IsNull { .. } => 4,
+                Expression::Arithmetic(_, ArithmeticOperator::Add, _)
+                | Expression::Arithmetic(_, ArithmeticOperator::Subtract, _) => 5,
+                Expression::Arithmetic(_, _, _) => 6,
+                Expression::Negate(_) => 7,
+                Expression::Literal(_) | Expression::Identifier(_) => 8,
+            }
+        }
+        let not = |negated: bool| if negated { "NOT " } else { "" };
+        match self {
+            Expression::Literal(literal) => write!(f, "{literal}"),
+            Expression::Identifier(name) => write!(f, "{name}"),
+            Expression::Not(inner) => {
+                write!(f, "NOT ")?;
+                operand(f, inner, 3)
+            }
+            Expression::And(left, right) => {
+                operand(f, left, 2)?;
+                write!(f, " AND ")?;
+                operand(f, right, 3)
+            }
+            Expression::Or(left, right) => {
+                operand(f, left, 1)?;
+                write!(f, " OR ")?;
+                operand(f, right, 2)
+            }
+            Expression::Compare(left, operator, right) => {
+                operand(f, left, 5)?;
+                write!(f, " {operator} ")?;
+                operand(f, right, 5)
+            }
+            Expression::Arithmetic(left, operator, right) => {
+                let level = precedence(self);
+                operand(f, left, level)?;
+                write!(f, " {operator} ")?;
+                operand(f, right, level + 1)
+            }
+            Expression::Negate(inner) => {
+                write!(f, "-")?;
+                operand(f, inner, 8)
+            }
+            Expression::Between {
+                expression,
+                low,
+                high,
+                negated,
+            } => {
+                operand(f, expression, 5)?;
+                write!(f, " {}BETWEEN ", not(*negated))?;
+                operand(f, low, 5)?;
+                write!(f, " AND ")?;
+                operand(f, high, 5)
+            }
+            Expression::In {
+                identifier,
+                values,
+                negated,
+            } => {
+                write!(f, "{identifier} {}IN (", not(*negated))?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", Literal::String(value.clone()))?;
+                }
+                write!(f, ")")
+            }
+            Expression::Like {
+                identifier,
+                pattern,
+                escape,
+                negated,
+            } => {
+                write!(
+                    f,
+                    "{identifier} {}LIKE {}",
+                    not(*negated),
+                    Literal::String(pattern.clone())
+                )?;
+                match escape {
+                    Some(escape) => write!(f, " ESCAPE {}", Literal::String(escape.to_string())),
+                    None => Ok(()),
+                }
+            }
+            Expression::IsNull {
+                identifier,
+                negated,
+            } => write!(f, "{identifier} IS {}NULL", not(*negated)),
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Literal::String(val) => write!(f, "'{}'", val.replace('\'', "''")),
+            Literal::Long(val) => write!(f, "{val}"),
+            Literal::Double(val) => write!(f, "{val:?}"),
+            Literal::Boolean(true) => write!(f, "TRUE"),
+            Literal::Boolean(false) => write!(f, "FALSE"),
+        }
+    }
+}
+
+impl fmt::Display for CompareOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operator = match self {
+            CompareOperator::Equal => "=",
+            CompareOperator::NotEqual => "<>",
+            CompareOperator::Less => "<",
+            CompareOperator::LessOrEqual => "<=",
+            CompareOperator::Greater => ">",
+            CompareOperator::GreaterOrEqual => ">=",
+        };
+        write!(f, "{operator}")
+    }
+}
+
+impl fmt::Display for ArithmeticOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operator = match self {
+            ArithmeticOperator::Add => "+",
+            ArithmeticOperator::Subtract => "-",
+            ArithmeticOperator::Multiply => "*",
+            ArithmeticOperator::Divide => "/",
+        };
+        write!(f, "{operator}")
+    }
+}
+
+impl std::str::FromStr for Selector {
+    type Err = SelectorError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Selector::parse(text)
+    }
+}
+
+/// validates an optional selector string, empty selectors are treated as no selector
+pub(crate) fn validate(selector: Option<&str>) -> Result<(), Error> {
+    match selector {
+        Some(text) if !text.trim().is_empty() => {
+            Selector::parse(text)?;
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+//
+// tokenizer
+//
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Identifier(String),
+    Keyword(Keyword),
+    String(String),
+    Long(i64),
+    Double(f64),
+    Operator(&'static str),
+    OpenParen,
+    CloseParen,
+    Comma,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keyword {
+    Not,
+    And,
+    Or,
+    Between,
+    Like,
+    Escape,
+    In,
+    Is,
+    Null,
+    True,
+    False,
+}
+
+impl Keyword {
+    fn from_identifier(identifier: &str) -> Option<Keyword> {
+        let keyword = match identifier.to_ascii_uppercase().as_str() {
+            "NOT" => Keyword::Not,
+            "AND" => Keyword::And,
+            "OR" => Keyword::Or,
+            "BETWEEN" => Keyword::Between,
+            "LIKE" => Keyword::Like,
+            "ESCAPE" => Keyword::Escape,
+            "IN" => Keyword::In,
+            "IS" => Keyword::Is,
+            "NULL" => Keyword::Null,
+            "TRUE" => Keyword::True,
+            "FALSE" => Keyword::False,
+            _ => return None,
+        };
+        Some(keyword)
+    }
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenKind::Identifier(name) => write!(f, "identifier '{name}'"),
+            TokenKind::Keyword(keyword) => {
+                write!(f, "keyword {}", format!("{keyword:?}").to_uppercase())
+            }
+            TokenKind::String(val) => write!(f, "string '{val}'"),
+            TokenKind::Long(val) => write!(f, "number {val}"),
+            TokenKind::Double(val) => write!(f, "number {val}"),
+            TokenKind::Operator(op) => write!(f, "'{op}'"),
+            TokenKind::OpenParen => write!(f, "'('"),
+            TokenKind::CloseParen => write!(f, "')'"),
+            TokenKind::Comma => write!(f, "','"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn starts_number(chars: &[char], i: usize) -> bool {
+    match chars.get(i) {
+        Some(c) if c.is_ascii_digit() => true,
+        Some('.') => chars.get(i + 1).is_some_and(char::is_ascii_digit),
+        _ => false,
+    }
+}
+
+/// a minus directly before a number is part of the literal unless it is a binary operator
+fn follows_operand(tokens: &[Token]) -> bool {
+    matches!(
+        tokens.last().map(|t| &t.kind),
+        Some(
+            TokenKind::Identifier(_)
+                | TokenKind::String(_)
+                | TokenKind::Long(_)
+                | TokenKind::Double(_)
+                | TokenKind::CloseParen
+                | TokenKind::Keyword(Keyword::True | Keyword::False | Keyword::Null)
+        )
+    )
+}
+
+/// lexes a numeric literal in Java syntax, returning the token and the position after it
+///
+/// supports an optional leading minus, hexadecimal (`0x1F`) and octal (`017`)
+/// integers, exponents and the `L`, `F` and `D` type suffixes.
+fn number(chars: &[char], start: usize) -> Result<(TokenKind, usize), SelectorError> {
+    let error = |message: String, position: usize| SelectorError { message, position };
+    let mut i = start;
+    let negative = chars[i] == '-';
+    if negative {
+        i += 1;
+    }
+    let digits_start = i;
+    let hex = chars[i] == '0' && matches!(chars.get(i + 1), Some('x' | 'X'));
+    let mut approximate = false;
+    if hex {
+        i += 2;
+        while i < chars.len() && chars[i].is_ascii_hexdigit() {
+            i += 1;
+        }
+        if i == digits_start + 2 {
+            return Err(error("missing hexadecimal digits".to_string(), i));
+        }
+    } else {
+        while i < chars.len() && chars[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i < chars.len() && chars[i] == '.' {
+            approximate = true;
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+        if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+            approximate = true;
+            i += 1;
+            if i < chars.len() && (chars[i] == '+' || chars[i] == '-') {
+                i += 1;
+            }
+            if !(i < chars.len() && chars[i].is_ascii_digit()) {
+                return Err(error("missing exponent digits".to_string(), i));
+            }
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+    }
+    let digits: String = chars[digits_start..i].iter().collect();
+    let text: String = chars[start..i].iter().collect();
+    match chars.get(i) {
+        Some('l' | 'L') if !approximate => i += 1,
+        Some('f' | 'F' | 'd' | 'D') if !hex => {
+            approximate = true;
+            i += 1;
+        }
+        _ => {}
+    }
+    if chars
+        .get(i)
+        .is_some_and(|c| c.is_alphanumeric() || *c == '_' || *c == '$')
+    {
+        let text: String = chars[start..=i].iter().collect();
+        return Err(error(format!("invalid number {text}"), start));
+    }
+    if approximate {
+        return match text.parse::<f64>() {
+            Ok(val) => Ok((TokenKind::Double(val), i)),
+            Err(_) => Err(error(format!("invalid number {text}"), start)),
+        };
+    }
+    let val = if hex {
+        // like Java, hexadecimal and octal literals cover the full 64 bit range
+        u64::from_str_radix(&digits[2..], 16).map(|val| val as i64)
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        if let Some(pos) = digits.find(['8', '9']) {
+            return Err(error(
+                format!("invalid octal number {text}"),
+                digits_start + pos,
+            ));
+        }
+        u64::from_str_radix(&digits[1..], 8).map(|val| val as i64)
+    } else {
+        return match text.parse::<i64>() {
+            Ok(val) => Ok((TokenKind::Long(val), i)),
+            Err(_) => Err(error(format!("number {text} is out of range"), start)),
+        };
+    };
+    match val {
+        Ok(val) if negative => Ok((TokenKind::Long(val.wrapping_neg()), i)),
+        Ok(val) => Ok((TokenKind::Long(val), i)),
+        Err(_) => Err(error(format!("number {text} is out of range"), start)),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, SelectorError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let kind = if c.is_alphabetic() || c == '_' || c == '$' {
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+            {
+                i += 1;
+            }
+            let identifier: String = chars[start..i].iter().collect();
+            match Keyword::from_identifier(&identifier) {
+                Some(keyword) => TokenKind::Keyword(keyword),
+                None => TokenKind::Identifier(identifier),
+            }
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+            || (c == '-' && starts_number(&chars, i + 1) && !follows_operand(&tokens))
+        {
+            let (kind, end) = number(&chars, start)?;
+            i = end;
+            kind
+        } else if c == '\'' {
+            let mut val = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => {
+                        return Err(SelectorError {
+                            message: "unterminated string literal".to_string(),
+                            position: start,
+                        })
+                    }
+                    Some('\'') if chars.get(i + 1) == Some(&'\'') => {
+                        val.push('\'');
+                        i += 2;
+                    }
+                    Some('\'') => {
+                        i += 1;
+                        break;
+                    }
+                    Some(c) => {
+                        val.push(*c);
+                        i += 1;
+                    }
+                }
+            }
+            TokenKind::String(val)
+        } else {
+            let next = chars.get(i + 1).copied();
+            let (kind, len) = match (c, next) {
+                ('<', Some('>')) => (TokenKind::Operator("<>"), 2),
+                ('<', Some('=')) => (TokenKind::Operator("<="), 2),
+                ('>', Some('=')) => (TokenKind::Operator(">="), 2),
+                ('<', _) => (TokenKind::Operator("<"), 1),
+                ('>', _) => (TokenKind::Operator(">"), 1),
+                ('=', _) => (TokenKind::Operator("="), 1),
+                ('+', _) => (TokenKind::Operator("+"), 1),
+                ('-', _) => (TokenKind::Operator("-"), 1),
+                ('*', _) => (TokenKind::Operator("*"), 1),
+                ('/', _) => (TokenKind::Operator("/"), 1),
+                ('(', _) => (TokenKind::OpenParen, 1),
+                (')', _) => (TokenKind::CloseParen, 1),
+                (',', _) => (TokenKind::Comma, 1),
+                _ => {
+                    return Err(SelectorError {
+                        message: format!("unexpected character '{c}'"),
+                        position: start,
+                    })
+                }
+            };
+            i += len;
+            kind
+        };
+        tokens.push(Token {
+            kind,
+            position: start,
+        });
+    }
+    Ok(tokens)
+}
+
+//
+// parser
+//
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn current_position(&self) -> usize {
+        self.peek().map(|t| t.position).unwrap_or(self.end)
+    }
+
+    fn error(&self, expected: &str) -> SelectorError {
+        let found = match self.peek() {
+            Some(token) => format!("{}", token.kind),
+            None => "end of selector".to_string(),
+        };
+        SelectorError {
+            message: format!("expected {expected}, found {found}"),
+            position: self.current_position(),
+        }
+    }
+
+    fn accept_keyword(&mut self, keyword: Keyword) -> bool {
+        if self.peek().map(|t| &t.kind) == Some(&TokenKind::Keyword(keyword)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: Keyword) -> Result<(), SelectorError> {
+        if self.accept_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("{keyword:?}").to_uppercase()))
+        }
+    }
+
+    fn accept(&mut self, kind: &TokenKind) -> bool {
+        if self.peek().map(|t| &t.kind) == Some(kind) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_string(&mut self) -> Result<String, SelectorError> {
+        match self.peek().map(|t| t.kind.clone()) {
+            Some(TokenKind::String(val)) => {
+                self.position += 1;
+                Ok(val)
+            }
+            _ => Err(self.error("string literal")),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, SelectorError> {
+        let mut left = self.parse_and()?;
+        while self.accept_keyword(Keyword::Or) {
+            let right = self.parse_and()?;
+            left = Expression::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, SelectorError> {
+        let mut left = self.parse_not()?;
+        while self.accept_keyword(Keyword::And) {
+            let right = self.parse_not()?;
+            left = Expression::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expression, SelectorError> {
+        if self.accept_keyword(Keyword::Not) {
+            let inner = self.parse_not()?;
+            return Ok(Expression::Not(Box::new(inner)));
+        }
+        self.parse_predicate()
+    }
+
+    fn parse_predicate(&mut self) -> Result<Expression, SelectorError> {
+        let start = self.current_position();
+        let left = self.parse_arithmetic()?;
+        let identifier = |expected: &str| -> Result<String, SelectorError> {
+            match &left {
+                Expression::Identifier(name) => Ok(name.clone()),
+                _ => Err(SelectorError {
+                    message: format!("{expected} requires an identifier"),
+                    position: start,
+                }),
+            }
+        };
+        if self.accept_keyword(Keyword::Is) {
+            let identifier = identifier("IS NULL")?;
+            let negated = self.accept_keyword(Keyword::Not);
+            self.expect_keyword(Keyword::Null)?;
+            return Ok(Expression::IsNull {
+                identifier,
+                negated,
+            });
+        }
+        let negated = self.accept_keyword(Keyword::Not);
+        if self.accept_keyword(Keyword::Between) {
+            let low = self.parse_arithmetic()?;
+            self.expect_keyword(Keyword::And)?;
+            let high = self.parse_arithmetic()?;
+            return Ok(Expression::Between {
+                expression: Box::new(left),
+                low: Box::new(low),
+                high: Box::new(high),
+                negated,
+            });
+        }
+        if self.accept_keyword(Keyword::In) {
+            let identifier = identifier("IN")?;
+            if !self.accept(&TokenKind::OpenParen) {
+                return Err(self.error("'('"));
+            }
+            let mut values = vec![self.expect_string()?];
+            while self.accept(&TokenKind::Comma) {
+                values.push(self.expect_string()?);
+            }
+            if !self.accept(&TokenKind::CloseParen) {
+                return Err(self.error("',' or ')'"));
+            }
+            return Ok(Expression::In {
+                identifier,
+                values,
+                negated,
+            });
+        }
+        if self.accept_keyword(Keyword::Like) {
+            let identifier = identifier("LIKE")?;
+            let pattern = self.expect_string()?;
+            let mut escape = None;
+            if self.accept_keyword(Keyword::Escape) {
+                let position = self.current_position();
+                let escape_str = self.expect_string()?;
+                let mut escape_chars = escape_str.chars();
+                match (escape_chars.next(), escape_chars.next()) {
+                    (Some(c), None) => escape = Some(c),
+                    _ => {
+                        return Err(SelectorError {
+                            message: "escape must be a single character".to_string(),
+                            position,
+                        })
+                    }
+                }
+            }
+            return Ok(Expression::Like {
+                identifier,
+                pattern,
+                escape,
+                negated,
+            });
+        }
+        if negated {
+            return Err(self.error("BETWEEN, IN or LIKE"));
+        }
+        let operator = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Operator("=")) => CompareOperator::Equal,
+            Some(TokenKind::Operator("<>")) => CompareOperator::NotEqual,
+            Some(TokenKind::Operator("<")) => CompareOperator::Less,
+            Some(TokenKind::Operator("<=")) => CompareOperator::LessOrEqual,
+            Some(TokenKind::Operator(">")) => CompareOperator::Greater,
+            Some(TokenKind::Operator(">=")) => CompareOperator::GreaterOrEqual,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.parse_arithmetic()?;
+        Ok(Expression::Compare(
+            Box::new(left),
+            operator,
+            Box::new(right),
+        ))
+    }
+
+    fn parse_arithmetic(&mut self) -> Result<Expression, SelectorError> {
+        let mut left = self.parse_term()?;
+        loop {
+            let operator = match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::Operator("+")) => ArithmeticOperator::Add,
+                Some(TokenKind::Operator("-")) => ArithmeticOperator::Subtract,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            let right = self.parse_term()?;
+            left = Expression::Arithmetic(Box::new(left), operator, Box::new(right));
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Expression, SelectorError> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let operator = match self.peek().map(|t| &t.kind) {
+                Some(TokenKind::Operator("*")) => ArithmeticOperator::Multiply,
+                Some(TokenKind::Operator("/")) => ArithmeticOperator::Divide,
+                _ => return Ok(left),
+            };
+            self.position += 1;
+            let right = self.parse_unary()?;
+            left = Expression::Arithmetic(Box::new(left), operator, Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, SelectorError> {
+        if self.accept(&TokenKind::Operator("-")) {
+            let inner = self.parse_unary()?;
+            return Ok(match inner {
+                Expression::Literal(Literal::Long(val)) if val != i64::MIN => {
+                    Expression::Literal(Literal::Long(-val))
+                }
+                Expression::Literal(Literal::Double(val)) => {
+                    Expression::Literal(Literal::Double(-val))
+                }
+                inner => Expression::Negate(Box::new(inner)),
+            });
+        }
+        if self.accept(&TokenKind::Operator("+")) {
+            return self.parse_unary();
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, SelectorError> {
+        let expression = match self.peek().map(|t| t.kind.clone()) {
+            Some(TokenKind::Identifier(name)) => Expression::Identifier(name),
+            Some(TokenKind::String(val)) => Expression::Literal(Literal::String(val)),
+            Some(TokenKind::Long(val)) => Expression::Literal(Literal::Long(val)),
+            Some(TokenKind::Double(val)) => Expression::Literal(Literal::Double(val)),
+            Some(TokenKind::Keyword(Keyword::True)) => Expression::Literal(Literal::Boolean(true)),
+            Some(TokenKind::Keyword(Keyword::False)) => {
+                Expression::Literal(Literal::Boolean(false))
+            }
+            Some(TokenKind::OpenParen) => {
+                self.position += 1;
+                let inner = self.parse_or()?;
+                if !self.accept(&TokenKind::CloseParen) {
+                    return Err(self.error("')'"));
+                }
+                return Ok(inner);
+            }
+            _ => return Err(self.error("identifier, literal or '('")),
+        };
+        self.next();
+        Ok(expression)
+    }
+}
+
+//
+// evaluation
+//
+
+/// runtime value, Null also represents the unknown result of a condition
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Null,
+    Boolean(bool),
+    Long(i64),
+    Double(f64),
+    String(String),
+}
+
+impl Value {
+    /// numeric view of a value, the native client reports all properties as strings
+    /// so strings holding a number are accepted as well
+    fn number(&self) -> Option<Value> {
+        match self {
+            Value::Long(_) | Value::Double(_) => Some(self.clone()),
+            Value::String(val) => {
+                if let Ok(val) = val.trim().parse::<i64>() {
+                    Some(Value::Long(val))
+                } else {
+                    val.trim().parse::<f64>().ok().map(Value::Double)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> f64 {
+        match self {
+            Value::Long(val) => *val as f64,
+            Value::Double(val) => *val,
+            _ => f64::NAN,
+        }
+    }
+}
+
+fn evaluate(expression: &Expression, lookup: &dyn Fn(&str) -> Value) -> Value {
+    match expression {
+        Expression::Literal(Literal::String(val)) => Value::String(val.clone()),
+        Expression::Literal(Literal::Long(val)) => Value::Long(*val),
+        Expression::Literal(Literal::Double(val)) => Value::Double(*val),
+        Expression::Literal(Literal::Boolean(val)) => Value::Boolean(*val),
+        Expression::Identifier(name) => lookup(name),
+        Expression::Not(inner) => match evaluate(inner, lookup) {
+            Value::Boolean(val) => Value::Boolean(!val),
+            _ => Value::Null,
+        },
+        Expression::And(left, right) => match (evaluate(left, lookup), evaluate(right, lookup)) {
+            (Value::Boolean(false), _) | (_, Value::Boolean(false)) => Value::Boolean(false),
+            (Value::Boolean(true), Value::Boolean(true)) => Value::Boolean(true),
+            _ => Value::Null,
+        },
+        Expression::Or(left, right) => match (evaluate(left, lookup), evaluate(right, lookup)) {
+            (Value::Boolean(true), _) | (_, Value::Boolean(true)) => Value::Boolean(true),
+            (Value::Boolean(false), Value::Boolean(false)) => Value::Boolean(false),
+            _ => Value::Null,
+        },
+        Expression::Compare(left, operator, right) => {
+            compare(&evaluate(left, lookup), *operator, &evaluate(right, lookup))
+        }
+        Expression::Arithmetic(left, operator, right) => {
+            arithmetic(&evaluate(left, lookup), *operator, &evaluate(right, lookup))
+        }
+        Expression::Negate(inner) => match evaluate(inner, lookup).number() {
+            Some(Value::Long(val)) => Value::Long(val.wrapping_neg()),
+            Some(Value::Double(val)) => Value::Double(-val),
+            _ => Value::Null,
+        },
+        Expression::Between {
+            expression,
+            low,
+            high,
+            negated,
+        } => {
+            let value = evaluate(expression, lookup);
+            let lower = compare(
+                &value,
+                CompareOperator::GreaterOrEqual,
+                &evaluate(low, lookup),
+            );
+            let upper = compare(
+                &value,
+                CompareOperator::LessOrEqual,
+                &evaluate(high, lookup),
+            );
+            let result = match (lower, upper) {
+                (Value::Boolean(false), _) | (_, Value::Boolean(false)) => Value::Boolean(false),
+                (Value::Boolean(true), Value::Boolean(true)) => Value::Boolean(true),
+                _ => Value::Null,
+            };
+            negate_if(result, *negated)
+        }
+        Expression::In {
+            identifier,
+            values,
+            negated,
+        } => match lookup(identifier) {
+            Value::String(val) => Value::Boolean(values.contains(&val) != *negated),
+            _ => Value::Null,
+        },
+        Expression::Like {
+            identifier,
+            pattern,
+            escape,
+            negated,
+        } => match lookup(identifier) {
+            Value::String(val) => Value::Boolean(like(&val, pattern, *escape) != *negated),
+            _ => Value::Null,
+        },
+        Expression::IsNull {
+            identifier,
+            negated,
+        } => Value::Boolean((lookup(identifier) == Value::Null) != *negated),
+    }
+}
+
+fn negate_if(value: Value, negated: bool) -> Value {
+    match value {
+        Value::Boolean(val) => Value::Boolean(val != negated),
+        other => other,
+    }
+}
+
+fn compare(left: &Value, operator: CompareOperator, right: &Value) -> Value {
+    use std::cmp::Ordering;
+    let ordering = match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => return Value::Null,
+        (Value::String(l), Value::String(r)) => match operator {
+            CompareOperator::Equal | CompareOperator::NotEqual => l.cmp(r),
+            _ => return Value::Null,
+        },
+        (Value::Boolean(l), Value::Boolean(r)) => match operator {
+            CompareOperator::Equal | CompareOperator::NotEqual => l.cmp(r),
+            _ => return Value::Null,
+        },
+        _ => match (left.number(), right.number()) {
+            (Some(Value::Long(l)), Some(Value::Long(r))) => l.cmp(&r),
+            (Some(l), Some(r)) => match l.as_f64().partial_cmp(&r.as_f64()) {
+                Some(ordering) => ordering,
+                None => return Value::Null,
+            },
+            _ => return Value::Null,
+        },
+    };
+    let result = match operator {
+        CompareOperator::Equal => ordering == Ordering::Equal,
+        CompareOperator::NotEqual => ordering != Ordering::Equal,
+        CompareOperator::Less => ordering == Ordering::Less,
+        CompareOperator::LessOrEqual => ordering != Ordering::Greater,
+        CompareOperator::Greater => ordering == Ordering::Greater,
+        CompareOperator::GreaterOrEqual => ordering != Ordering::Less,
+    };
+    Value::Boolean(result)
+}
+
+fn arithmetic(left: &Value, operator: ArithmeticOperator, right: &Value) -> Value {
+    match (left.number(), right.number()) {
+        (Some(Value::Long(l)), Some(Value::Long(r))) => {
+            let result = match operator {
+                ArithmeticOperator::Add => l.checked_add(r),
+                ArithmeticOperator::Subtract => l.checked_sub(r),
+                ArithmeticOperator::Multiply => l.checked_mul(r),
+                ArithmeticOperator::Divide => l.checked_div(r),
+            };
+            result.map(Value::Long).unwrap_or(Value::Null)
+        }
+        (Some(l), Some(r)) => {
+            let (l, r) = (l.as_f64(), r.as_f64());
+            Value::Double(match operator {
+                ArithmeticOperator::Add => l + r,
+                ArithmeticOperator::Subtract => l - r,
+                ArithmeticOperator::Multiply => l * r,
+                ArithmeticOperator::Divide => l / r,
+            })
+        }
+        _ => Value::Null,
+    }
+}
+
+/// matches a value against a LIKE pattern
+fn like(value: &str, pattern: &str, escape: Option<char>) -> bool {
+    enum Part {
+        Char(char),
+        Any,
+        Many,
+    }
+    let mut parts = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if Some(c) == escape {
+            if let Some(escaped) = chars.next() {
+                parts.push(Part::Char(escaped));
+            }
+        } else if c == '_' {
+            parts.push(Part::Any);
+        } else if c == '%' {
+            parts.push(Part::Many);
+        } else {
+            parts.push(Part::Char(c));
+        }
+    }
+    let value: Vec<char> = value.chars().collect();
+    // matched[j]: the first i characters of value match the first j parts
+    let mut matched = vec![false; parts.len() + 1];
+    matched[0] = true;
+    for (j, part) in parts.iter().enumerate() {
+        if let Part::Many = part {
+            matched[j + 1] = matched[j];
+        }
+    }
+    for c in value {
+        let mut next = vec![false; parts.len() + 1];
+        for (j, part) in parts.iter().enumerate() {
+            next[j + 1] = match part {
+                Part::Char(p) => matched[j] && *p == c,
+                Part::Any => matched[j],
+                Part::Many => matched[j + 1] || next[j],
+            };
+        }
+        matched = next;
+    }
+    matched[parts.len()]
+}
@@ -82,6 +82,13 @@ mod admin {
         assert_eq!(command as u8, 16);
     }
 
+    #[test]
+    fn test_admin_commands_alter_destination() {
+        let command = AdminCommands::AlterDestination;
+        assert_eq!(command, AdminCommands::AlterDestination);
+        assert_eq!(command as u8, 17);
+    }
+
     #[test]
     fn test_admin_commands_create_destination() {
         let command = AdminCommands::CreateDestination;
@@ -96,6 +103,13 @@ mod admin {
         assert_eq!(command as u8, 19);
     }
 
+    #[test]
+    fn test_admin_commands_purge() {
+        assert_eq!(AdminCommands::PurgeDestination as u8, 21);
+        assert_eq!(AdminCommands::PurgeDurable as u8, 22);
+        assert_eq!(AdminCommands::PurgeAllQueues as u8, 23);
+    }
+
     #[test]
     fn test_admin_commands_get_server_info() {
         let command = AdminCommands::GetServerInfo;
@@ -124,18 +138,35 @@ mod admin {
         assert_eq!(command as u8, 221);
     }
 
+    #[test]
+    fn test_admin_commands_list_bridges() {
+        let command = AdminCommands::ListBridges;
+        assert_eq!(command, AdminCommands::ListBridges);
+        assert_eq!(command as u8, 222);
+    }
+
     #[test]
     fn test_server_state_standby() {
         let state = ServerState::Standby;
         assert_eq!(state, ServerState::Standby);
-        assert_eq!(state as u8, 3);
+        assert_eq!(state.code(), 3);
     }
 
     #[test]
     fn test_server_state_active() {
         let state = ServerState::Active;
         assert_eq!(state, ServerState::Active);
-        assert_eq!(state as u8, 4);
+        assert_eq!(state.code(), 4);
+    }
+
+    #[test]
+    fn test_server_state_codes() {
+        assert_eq!(ServerState::from_code(2), ServerState::Activating);
+        assert_eq!(ServerState::from_code(3), ServerState::Standby);
+        assert_eq!(ServerState::from_code(4), ServerState::Active);
+        assert_eq!(ServerState::from_code(5), ServerState::Recovering);
+        assert_eq!(ServerState::from_code(17), ServerState::Unknown(17));
+        assert_eq!(ServerState::Unknown(17).code(), 17);
     }
 }
 
@@ -248,3 +279,1619 @@ mod admin_integration {
         assert!(server_state_res.is_ok());
     }
 }
+
+#[cfg(test)]
+mod admin_mock {
+    use std::collections::HashMap;
+    use std::io::{Error, ErrorKind};
+    use std::time::Duration;
+    use tibco_ems::admin::reconcile::{self, Action, DesiredState, ReconcileOptions};
+    use tibco_ems::admin::{
+        self, AclEntry, AdminCommands, AdminError, AdminErrorKind, AdminOptions, BridgeInfo,
+        ConnectionFilter, DecodeError, DurableInfo, FactoryInfo, FactoryType, FtPairHealth,
+        GroupInfo, OverflowPolicy, Patch, Permission, Principal, QueueInfo, QueueInfoPatch,
+        RouteInfo, RouteState, ServerInfo, ServerState, TopicInfo, TopicInfoPatch, TraceMode,
+        UserInfo, ZoneType,
+    };
+    use tibco_ems::backend::{AcknowledgeMode, Backend, ConsumerKind};
+    use tibco_ems::mock::MockBroker;
+    use tibco_ems::{Destination, MapMessage, Message, TextMessage, TypedValue};
+
+    /// the mock broker posing as a Tibco EMS server
+    #[derive(Debug, Clone)]
+    struct Server(MockBroker);
+
+    impl Backend for Server {
+        fn connect(&self, url: &str, user: &str, password: &str) -> Result<usize, Error> {
+            Backend::connect(&self.0, url, user, password)
+        }
+        fn lookup_context(&self, url: &str, user: &str, password: &str) -> Result<usize, Error> {
+            self.0.lookup_context(url, user, password)
+        }
+        fn lookup_destination(&self, context: usize, name: &str) -> Result<Destination, Error> {
+            self.0.lookup_destination(context, name)
+        }
+        fn lookup_factory(&self, context: usize, name: &str) -> Result<usize, Error> {
+            self.0.lookup_factory(context, name)
+        }
+        fn close_lookup_context(&self, context: usize) {
+            self.0.close_lookup_context(context)
+        }
+        fn connect_factory(
+            &self,
+            factory: usize,
+            user: &str,
+            password: &str,
+        ) -> Result<usize, Error> {
+            self.0.connect_factory(factory, user, password)
+        }
+        fn close_factory(&self, factory: usize) {
+            self.0.close_factory(factory)
+        }
+        fn close_connection(&self, connection: usize) {
+            self.0.close_connection(connection)
+        }
+        fn active_url(&self, connection: usize) -> Result<String, Error> {
+            self.0.active_url(connection)
+        }
+        fn session(
+            &self,
+            connection: usize,
+            mode: AcknowledgeMode,
+        ) -> Result<(usize, usize), Error> {
+            self.0.session(connection, mode)
+        }
+        fn close_session(&self, session: usize, producer: usize) {
+            self.0.close_session(session, producer)
+        }
+        fn consumer(
+            &self,
+            session: usize,
+            destination: &Destination,
+            kind: ConsumerKind,
+            selector: Option<&str>,
+        ) -> Result<usize, Error> {
+            self.0.consumer(session, destination, kind, selector)
+        }
+        fn send(
+            &self,
+            session: usize,
+            producer: usize,
+            destination: &Destination,
+            message: &Message,
+        ) -> Result<(), Error> {
+            self.0.send(session, producer, destination, message)
+        }
+        fn receive(
+            &self,
+            consumer: usize,
+            wait_time_ms: Option<i64>,
+        ) -> Result<Option<Message>, Error> {
+            self.0.receive(consumer, wait_time_ms)
+        }
+        fn request_reply(
+            &self,
+            session: usize,
+            destination: &Destination,
+            message: &Message,
+            timeout: i64,
+        ) -> Result<Option<Message>, Error> {
+            self.0.request_reply(session, destination, message, timeout)
+        }
+        fn acknowledge(&self, message: usize) {
+            self.0.acknowledge(message)
+        }
+        fn recover(&self, message: usize) {
+            self.0.recover(message)
+        }
+        fn destroy(&self, message: usize) {
+            self.0.destroy(message)
+        }
+    }
+
+    #[test]
+    fn unverified_commands_are_not_sent_to_servers() {
+        let broker = MockBroker::new();
+        let server = Server(broker.clone());
+        let conn = admin::connect_with_backend(server, broker.url(), "admin", "").unwrap();
+        let session = conn.session().unwrap();
+
+        let queue = QueueInfo {
+            name: "server.verified".to_string(),
+            ..Default::default()
+        };
+        admin::create_queue(&session, &queue).unwrap();
+        assert!(admin::get_queue(&session, "server.verified")
+            .unwrap()
+            .is_some());
+        assert!(admin::get_server_info(&session).is_ok());
+        // return codes of other servers are not classified
+        let err = admin::create_queue(&session, &queue).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Other);
+        assert_eq!(
+            AdminError::from_error(&err).map(|e| e.kind),
+            Some(AdminErrorKind::Other)
+        );
+
+        let err = admin::list_users(&session).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        let err = admin::purge_queue(&session, "server.verified").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        // destinations alone are reconciled without listing bridges or permissions
+        let desired = DesiredState {
+            queues: vec![queue.clone()],
+            ..Default::default()
+        };
+        let plan = reconcile::plan(&session, &desired, &ReconcileOptions::default()).unwrap();
+        assert!(plan.is_empty());
+        assert!(!AdminCommands::ListUsers.is_verified());
+        assert!(AdminCommands::CreateDestination.is_verified());
+    }
+
+    #[test]
+    fn create_list_and_delete_queue() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = QueueInfo {
+            name: "admin.queue".to_string(),
+            max_messages: Some(100),
+            global: Some(true),
+            prefetch: Some(5),
+            ..Default::default()
+        };
+        admin::create_queue(&session, &queue).unwrap();
+        let destination = Destination::Queue(queue.name.clone());
+        let msg = TextMessage {
+            body: "pending".to_string(),
+            ..Default::default()
+        };
+        session.send_message(&destination, msg).unwrap();
+        let _consumer = session.queue_consumer(&destination, None).unwrap();
+
+        let queues = admin::list_all_queues(&session).unwrap();
+        assert_eq!(queues.len(), 1);
+        assert_eq!(queues[0].name, "admin.queue");
+        assert_eq!(queues[0].max_messages, Some(100));
+        assert_eq!(queues[0].global, Some(true));
+        assert_eq!(queues[0].prefetch, Some(5));
+        assert_eq!(queues[0].overflow_policy, Some(OverflowPolicy::Default));
+        assert_eq!(queues[0].pending_messages, Some(1));
+        assert_eq!(queues[0].consumer_count, Some(1));
+
+        admin::delete_queue(&session, "admin.queue").unwrap();
+        assert!(admin::list_all_queues(&session).unwrap().is_empty());
+        assert_eq!(broker.pending_messages(&destination), 0);
+    }
+
+    #[test]
+    fn create_list_and_delete_topic() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let topic = TopicInfo {
+            name: "admin.topic".to_string(),
+            max_bytes: Some(1024),
+            ..Default::default()
+        };
+        admin::create_topic(&session, &topic).unwrap();
+        let destination = Destination::Topic(topic.name.clone());
+        session
+            .topic_durable_consumer(&destination, "durable", None)
+            .unwrap();
+
+        let topics = admin::list_all_topics(&session).unwrap();
+        assert_eq!(topics.len(), 1);
+        assert_eq!(topics[0].name, "admin.topic");
+        assert_eq!(topics[0].max_bytes, Some(1024));
+        assert_eq!(topics[0].durable_count, Some(1));
+        assert_eq!(topics[0].subscriber_count, Some(1));
+        assert!(admin::list_all_queues(&session).unwrap().is_empty());
+
+        admin::delete_topic(&session, "admin.topic").unwrap();
+        assert!(admin::list_all_topics(&session).unwrap().is_empty());
+    }
+
+    #[test]
+    fn admin_connect_uses_same_broker() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = QueueInfo {
+            name: "admin.connect".to_string(),
+            ..Default::default()
+        };
+        admin::create_queue(&session, &queue).unwrap();
+        let admin_conn =
+            admin::connect_with_backend(broker.clone(), broker.url(), "admin", "").unwrap();
+        let admin_session = admin_conn.session().unwrap();
+        let queues = admin::list_all_queues(&admin_session).unwrap();
+        assert_eq!(queues.len(), 1);
+    }
+
+    #[test]
+    fn server_state_is_reported() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        assert_eq!(
+            admin::get_server_state(&session).unwrap(),
+            ServerState::Active
+        );
+        broker.set_server_state(ServerState::Standby);
+        assert_eq!(
+            admin::get_server_state(&session).unwrap(),
+            ServerState::Standby
+        );
+    }
+
+    #[test]
+    fn rejected_commands_fail_with_admin_error() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = QueueInfo {
+            name: "admin.duplicate".to_string(),
+            ..Default::default()
+        };
+        admin::create_queue(&session, &queue).unwrap();
+        let err = admin::create_queue(&session, &queue).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        let admin_err = AdminError::from_error(&err).unwrap();
+        assert_eq!(admin_err.kind, AdminErrorKind::AlreadyExists);
+        assert!(admin_err.message.contains("admin.duplicate"));
+
+        let err = admin::delete_topic(&session, "admin.missing").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert_eq!(
+            AdminError::from_error(&err).map(|e| e.kind),
+            Some(AdminErrorKind::NotFound)
+        );
+    }
+
+    #[test]
+    fn bridges_are_confirmed() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let bridge = BridgeInfo {
+            source: Destination::Topic("admin.source".to_string()),
+            target: Destination::Queue("admin.target".to_string()),
+            selector: None,
+        };
+        admin::create_bridge(&session, &bridge).unwrap();
+        let err = admin::create_bridge(&session, &bridge).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        admin::delete_bridge(&session, &bridge).unwrap();
+        let err = admin::delete_bridge(&session, &bridge).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn create_applies_all_properties() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = QueueInfo {
+            name: "admin.properties".to_string(),
+            max_bytes: Some(4096),
+            max_messages: Some(10),
+            overflow_policy: Some(OverflowPolicy::RejectIncoming),
+            failsafe: Some(true),
+            secure: Some(true),
+            global: Some(false),
+            sender_name: Some(true),
+            sender_name_enforced: Some(true),
+            prefetch: Some(5),
+            expiry_override: Some(60000),
+            redelivery_delay: Some(1000),
+            max_redelivery: Some(3),
+            exclusive: Some(true),
+            flow_control: Some(65536),
+            store: Some("$sys.failsafe".to_string()),
+            trace: Some(TraceMode::Body),
+            import_channels: Some(vec!["RV".to_string(), "SS".to_string()]),
+            ..Default::default()
+        };
+        admin::create_queue(&session, &queue).unwrap();
+        let created = admin::list_all_queues(&session).unwrap().remove(0);
+        assert_eq!(
+            created,
+            QueueInfo {
+                pending_messages: Some(0),
+                consumer_count: Some(0),
+                incoming_total_count: Some(0),
+                outgoing_total_count: Some(0),
+                ..queue
+            }
+        );
+
+        let topic = TopicInfo {
+            name: "admin.properties".to_string(),
+            overflow_policy: Some(OverflowPolicy::DiscardOld),
+            failsafe: Some(true),
+            secure: Some(false),
+            sender_name: Some(true),
+            sender_name_enforced: Some(false),
+            flow_control: Some(1024),
+            store: Some("$sys.nonfailsafe".to_string()),
+            trace: Some(TraceMode::Default),
+            import_channels: Some(vec!["RV".to_string()]),
+            export_channels: Some(vec!["SS".to_string()]),
+            ..Default::default()
+        };
+        admin::create_topic(&session, &topic).unwrap();
+        let created = admin::list_all_topics(&session).unwrap().remove(0);
+        assert_eq!(created.overflow_policy, Some(OverflowPolicy::DiscardOld));
+        assert_eq!(created.failsafe, Some(true));
+        assert_eq!(created.secure, Some(false));
+        assert_eq!(created.sender_name, Some(true));
+        assert_eq!(created.flow_control, Some(1024));
+        assert_eq!(created.store, topic.store);
+        assert_eq!(created.trace, Some(TraceMode::Default));
+        assert_eq!(created.import_channels, topic.import_channels);
+        assert_eq!(created.export_channels, topic.export_channels);
+    }
+
+    #[test]
+    fn update_and_patch_keep_pending_messages() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = QueueInfo {
+            name: "admin.update".to_string(),
+            max_bytes: Some(1024),
+            global: Some(true),
+            prefetch: Some(5),
+            ..Default::default()
+        };
+        admin::create_queue(&session, &queue).unwrap();
+        let destination = Destination::Queue(queue.name.clone());
+        let msg = TextMessage {
+            body: "pending".to_string(),
+            ..Default::default()
+        };
+        session.send_message(&destination, msg).unwrap();
+
+        let update = QueueInfo {
+            name: "admin.update".to_string(),
+            max_messages: Some(50),
+            ..Default::default()
+        };
+        admin::update_queue(&session, &update).unwrap();
+        let patch = QueueInfoPatch {
+            name: "admin.update".to_string(),
+            max_bytes: Patch::Set(2048),
+            global: Patch::Clear,
+            ..Default::default()
+        };
+        admin::patch_queue(&session, &patch).unwrap();
+
+        let updated = admin::list_all_queues(&session).unwrap().remove(0);
+        assert_eq!(updated.max_bytes, Some(2048));
+        assert_eq!(updated.max_messages, Some(50));
+        assert_eq!(updated.global, Some(false));
+        assert_eq!(updated.prefetch, Some(5));
+        assert_eq!(updated.pending_messages, Some(1));
+    }
+
+    #[test]
+    fn update_topic_changes_properties() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let topic = TopicInfo {
+            name: "admin.update".to_string(),
+            ..Default::default()
+        };
+        let err = admin::update_topic(&session, &topic).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        admin::create_topic(&session, &topic).unwrap();
+        let patch = TopicInfoPatch {
+            name: "admin.update".to_string(),
+            export_channels: Patch::Set(vec!["RV".to_string()]),
+            secure: Patch::Set(true),
+            ..Default::default()
+        };
+        admin::patch_topic(&session, &patch).unwrap();
+        let updated = admin::list_all_topics(&session).unwrap().remove(0);
+        assert_eq!(updated.export_channels, Some(vec!["RV".to_string()]));
+        assert_eq!(updated.secure, Some(true));
+    }
+
+    #[test]
+    fn purge_removes_pending_messages() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let send = |destination: &Destination, count: usize| {
+            for i in 0..count {
+                let msg = TextMessage {
+                    body: i.to_string(),
+                    ..Default::default()
+                };
+                session.send_message(destination, msg).unwrap();
+            }
+        };
+        let orders = Destination::Queue("purge.orders".to_string());
+        let invoices = Destination::Queue("purge.invoices".to_string());
+        let other = Destination::Queue("other.queue".to_string());
+        send(&orders, 3);
+        send(&invoices, 2);
+        send(&other, 1);
+        assert_eq!(
+            admin::purge_queue(&session, "purge.orders").unwrap(),
+            Some(3)
+        );
+        assert_eq!(broker.pending_messages(&orders), 0);
+        send(&orders, 1);
+        assert_eq!(
+            admin::purge_all_queues(&session, "purge.>").unwrap(),
+            Some(3)
+        );
+        assert_eq!(broker.pending_messages(&other), 1);
+        let err = admin::purge_queue(&session, "purge.missing").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let topic = Destination::Topic("purge.topic".to_string());
+        let _shared = session.topic_consumer(&topic, "shared", None).unwrap();
+        session
+            .topic_durable_consumer(&topic, "durable", None)
+            .unwrap();
+        send(&topic, 2);
+        assert_eq!(
+            admin::purge_durable(&session, "durable", None).unwrap(),
+            Some(2)
+        );
+        assert_eq!(broker.pending_messages(&topic), 2);
+        assert_eq!(
+            admin::purge_topic(&session, "purge.topic").unwrap(),
+            Some(2)
+        );
+        assert_eq!(broker.pending_messages(&topic), 0);
+    }
+
+    #[test]
+    fn get_and_list_by_pattern() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        for name in ["app.orders", "app.invoices", "app.eu.orders", "other"] {
+            let queue = QueueInfo {
+                name: name.to_string(),
+                ..Default::default()
+            };
+            admin::create_queue(&session, &queue).unwrap();
+            let topic = TopicInfo {
+                name: name.to_string(),
+                ..Default::default()
+            };
+            admin::create_topic(&session, &topic).unwrap();
+        }
+        let msg = TextMessage {
+            body: "pending".to_string(),
+            ..Default::default()
+        };
+        session
+            .send_message(&Destination::Queue("app.orders".to_string()), msg)
+            .unwrap();
+
+        let queue = admin::get_queue(&session, "app.orders").unwrap().unwrap();
+        assert_eq!(queue.pending_messages, Some(1));
+        assert!(admin::get_queue(&session, "app.missing").unwrap().is_none());
+        assert!(admin::get_topic(&session, "other").unwrap().is_some());
+
+        let mut names: Vec<String> = admin::list_queues(&session, "app.*")
+            .unwrap()
+            .into_iter()
+            .map(|queue| queue.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["app.invoices", "app.orders"]);
+        assert_eq!(admin::list_topics(&session, "app.>").unwrap().len(), 3);
+        assert_eq!(admin::list_all_topics(&session).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn iterate_destinations_page_by_page() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        for i in 0..5 {
+            let queue = QueueInfo {
+                name: format!("paged.{i}"),
+                ..Default::default()
+            };
+            admin::create_queue(&session, &queue).unwrap();
+            let topic = TopicInfo {
+                name: format!("paged.{i}"),
+                ..Default::default()
+            };
+            admin::create_topic(&session, &topic).unwrap();
+        }
+        let options = AdminOptions {
+            page_size: 2,
+            ..Default::default()
+        };
+        let names: Vec<String> = admin::iter_queues(&session, "paged.*", &options)
+            .map(|queue| queue.unwrap().name)
+            .collect();
+        assert_eq!(
+            names,
+            vec!["paged.0", "paged.1", "paged.2", "paged.3", "paged.4"]
+        );
+        let topics: Result<Vec<TopicInfo>, _> =
+            admin::iter_topics(&session, ">", &options).collect();
+        assert_eq!(topics.unwrap().len(), 5);
+        let first = admin::iter_queues(&session, ">", &options).next();
+        assert_eq!(first.unwrap().unwrap().name, "paged.0");
+    }
+
+    #[test]
+    fn iterating_ends_when_the_server_ignores_the_cursor() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        for i in 0..3 {
+            let queue = QueueInfo {
+                name: format!("paged.{i}"),
+                ..Default::default()
+            };
+            admin::create_queue(&session, &queue).unwrap();
+        }
+        broker.set_cursor_ignored(true);
+        let options = AdminOptions {
+            page_size: 2,
+            ..Default::default()
+        };
+        let mut queues = admin::iter_queues(&session, ">", &options);
+        assert_eq!(queues.next().unwrap().unwrap().name, "paged.0");
+        assert_eq!(queues.next().unwrap().unwrap().name, "paged.1");
+        assert!(queues.next().is_none());
+
+        // everything fits on one page, so the cursor is never needed
+        let options = AdminOptions {
+            page_size: 10,
+            ..Default::default()
+        };
+        assert_eq!(admin::iter_queues(&session, ">", &options).count(), 3);
+    }
+
+    #[test]
+    fn servers_are_sent_a_single_page_request() {
+        let broker = MockBroker::new();
+        let conn =
+            admin::connect_with_backend(Server(broker.clone()), broker.url(), "admin", "").unwrap();
+        let session = conn.session().unwrap();
+        for i in 0..3 {
+            let queue = QueueInfo {
+                name: format!("paged.{i}"),
+                ..Default::default()
+            };
+            admin::create_queue(&session, &queue).unwrap();
+        }
+        let options = AdminOptions {
+            page_size: 2,
+            ..Default::default()
+        };
+        let names: Vec<String> = admin::iter_queues(&session, ">", &options)
+            .map(|queue| queue.unwrap().name)
+            .collect();
+        assert_eq!(names, ["paged.0", "paged.1"]);
+    }
+
+    fn create_raw_queue(session: &tibco_ems::Session, fields: &[(&str, TypedValue)]) {
+        let mut msg = MapMessage::default();
+        for (name, value) in fields {
+            msg.body.insert(name.to_string(), value.clone());
+        }
+        msg.body.insert("dt".to_string(), TypedValue::Integer(1));
+        msg.header = Some(HashMap::from([(
+            "code".to_string(),
+            TypedValue::Integer(AdminCommands::CreateDestination as i32),
+        )]));
+        let admin_queue = Destination::Queue("$sys.admin".to_string());
+        session
+            .request_reply(&admin_queue, msg, 1000)
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn decoding_keeps_unknown_fields() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        create_raw_queue(
+            &session,
+            &[
+                ("dn", TypedValue::String("decode.extra".to_string())),
+                ("mm", TypedValue::Long(10)),
+                ("newfield", TypedValue::String("abc".to_string())),
+            ],
+        );
+        let queue = admin::get_queue(&session, "decode.extra").unwrap().unwrap();
+        assert_eq!(queue.max_messages, Some(10));
+        assert_eq!(queue.max_redelivery, None);
+        assert_eq!(
+            queue.extra.get("newfield"),
+            Some(&TypedValue::String("abc".to_string()))
+        );
+    }
+
+    #[test]
+    fn decoding_keeps_unknown_route_fields() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let mut msg = MapMessage::default();
+        msg.body
+            .insert("rn".to_string(), TypedValue::String("EMS-EU".to_string()));
+        msg.body.insert(
+            "url".to_string(),
+            TypedValue::String("tcp://routes.eu:7222".to_string()),
+        );
+        msg.body.insert(
+            "newfield".to_string(),
+            TypedValue::String("abc".to_string()),
+        );
+        msg.header = Some(HashMap::from([(
+            "code".to_string(),
+            TypedValue::Integer(AdminCommands::CreateRoute as i32),
+        )]));
+        let admin_queue = Destination::Queue("$sys.admin".to_string());
+        session
+            .request_reply(&admin_queue, msg, 1000)
+            .unwrap()
+            .unwrap();
+        let routes = admin::list_routes(&session).unwrap();
+        assert_eq!(routes[0].url, "tcp://routes.eu:7222");
+        assert_eq!(
+            routes[0].extra,
+            HashMap::from([(
+                "newfield".to_string(),
+                TypedValue::String("abc".to_string())
+            )])
+        );
+    }
+
+    #[test]
+    fn decoding_reports_invalid_fields() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        create_raw_queue(
+            &session,
+            &[
+                ("dn", TypedValue::String("decode.invalid".to_string())),
+                ("mb", TypedValue::String("lots".to_string())),
+            ],
+        );
+        let err = admin::list_all_queues(&session).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let decode_err = DecodeError::from_error(&err).unwrap();
+        assert_eq!(decode_err.name, "decode.invalid");
+        assert_eq!(decode_err.field, "mb");
+    }
+
+    #[test]
+    fn list_bridges_and_bridges_for_source() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let source = Destination::Topic("admin.bridged".to_string());
+        let to_queue = BridgeInfo {
+            source: source.clone(),
+            target: Destination::Queue("admin.bridged".to_string()),
+            selector: Some("region = 'EU'".to_string()),
+        };
+        let to_topic = BridgeInfo {
+            source: source.clone(),
+            target: Destination::Topic("admin.copy".to_string()),
+            selector: None,
+        };
+        let other = BridgeInfo {
+            source: Destination::Queue("admin.other".to_string()),
+            target: Destination::Queue("admin.bridged".to_string()),
+            selector: None,
+        };
+        assert!(admin::list_bridges(&session).unwrap().is_empty());
+        for bridge in [&to_queue, &to_topic, &other] {
+            admin::create_bridge(&session, bridge).unwrap();
+        }
+        assert_eq!(
+            admin::list_bridges(&session).unwrap(),
+            vec![to_queue.clone(), to_topic.clone(), other.clone()]
+        );
+        assert_eq!(
+            admin::get_bridges_for(&session, &source).unwrap(),
+            vec![to_queue.clone(), to_topic]
+        );
+        assert!(
+            admin::get_bridges_for(&session, &Destination::Queue("admin.bridged".to_string()))
+                .unwrap()
+                .is_empty()
+        );
+        admin::delete_bridge(&session, &other).unwrap();
+        assert_eq!(
+            admin::get_bridges_for(&session, &other.source).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn manage_users_and_groups() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let alice = UserInfo {
+            name: "alice".to_string(),
+            description: Some("orders application".to_string()),
+        };
+        let bob = UserInfo {
+            name: "bob".to_string(),
+            description: None,
+        };
+        admin::create_user(&session, &alice, Some("secret")).unwrap();
+        admin::create_user(&session, &bob, None).unwrap();
+        let err = admin::create_user(&session, &bob, None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        assert_eq!(
+            admin::list_users(&session).unwrap(),
+            vec![alice.clone(), bob.clone()]
+        );
+        admin::set_password(&session, "bob", Some("changed")).unwrap();
+        let err = admin::set_password(&session, "carol", None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let group = GroupInfo {
+            name: "orders".to_string(),
+            description: Some("order processing".to_string()),
+            members: vec!["alice".to_string()],
+        };
+        admin::create_group(&session, &group).unwrap();
+        assert_eq!(admin::list_groups(&session).unwrap(), vec![group.clone()]);
+        admin::add_user_to_group(&session, "bob", "orders").unwrap();
+        assert_eq!(
+            admin::list_groups(&session).unwrap()[0].members,
+            vec!["alice".to_string(), "bob".to_string()]
+        );
+        admin::remove_user_from_group(&session, "alice", "orders").unwrap();
+        let err = admin::remove_user_from_group(&session, "alice", "orders").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        let err = admin::add_user_to_group(&session, "carol", "orders").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // deleting a user removes it from its groups
+        admin::delete_user(&session, "bob").unwrap();
+        assert!(admin::list_groups(&session).unwrap()[0].members.is_empty());
+        assert_eq!(admin::list_users(&session).unwrap(), vec![alice]);
+        admin::delete_group(&session, "orders").unwrap();
+        assert!(admin::list_groups(&session).unwrap().is_empty());
+        let err = admin::delete_group(&session, "orders").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn grant_revoke_and_list_permissions() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let user = UserInfo {
+            name: "orders-app".to_string(),
+            description: None,
+        };
+        admin::create_user(&session, &user, Some("secret")).unwrap();
+        admin::create_group(
+            &session,
+            &GroupInfo {
+                name: "operators".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let queue = Destination::Queue("admin.secured".to_string());
+        let topic = Destination::Topic("admin.secured".to_string());
+        let app = Principal::User("orders-app".to_string());
+        let operators = Principal::Group("operators".to_string());
+
+        admin::grant(&session, &queue, &app, &[Permission::Send]).unwrap();
+        admin::grant(
+            &session,
+            &queue,
+            &app,
+            &[Permission::Receive, Permission::Send],
+        )
+        .unwrap();
+        admin::grant(&session, &topic, &app, &[Permission::Subscribe]).unwrap();
+        admin::grant(
+            &session,
+            &queue,
+            &operators,
+            &[Permission::Browse, Permission::Purge],
+        )
+        .unwrap();
+        // topic permissions do not apply to queues
+        let err = admin::grant(&session, &queue, &app, &[Permission::Publish]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        let err = admin::grant(
+            &session,
+            &queue,
+            &Principal::User("unknown".to_string()),
+            &[Permission::Send],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        assert_eq!(
+            admin::list_acls(&session, queue.clone()).unwrap(),
+            vec![
+                AclEntry {
+                    destination: queue.clone(),
+                    principal: app.clone(),
+                    permissions: vec![Permission::Receive, Permission::Send],
+                },
+                AclEntry {
+                    destination: queue.clone(),
+                    principal: operators.clone(),
+                    permissions: vec![Permission::Browse, Permission::Purge],
+                },
+            ]
+        );
+        let acls = admin::list_acls(&session, app.clone()).unwrap();
+        assert_eq!(acls.len(), 2);
+        assert_eq!(acls[1].destination, topic);
+
+        admin::revoke(&session, &queue, &app, &[Permission::Send]).unwrap();
+        admin::revoke(&session, &topic, &app, &[Permission::Subscribe]).unwrap();
+        assert_eq!(
+            admin::list_acls(&session, app.clone()).unwrap(),
+            vec![AclEntry {
+                destination: queue.clone(),
+                principal: app.clone(),
+                permissions: vec![Permission::Receive],
+            }]
+        );
+        let err = admin::revoke(&session, &topic, &app, &[Permission::Subscribe]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // permissions of deleted groups are removed
+        admin::delete_group(&session, "operators").unwrap();
+        assert_eq!(admin::list_acls(&session, queue).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn create_list_and_delete_durables() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let durable = DurableInfo {
+            name: "billing".to_string(),
+            topic: "durables.orders".to_string(),
+            selector: Some("region = 'EU'".to_string()),
+            ..Default::default()
+        };
+        admin::create_durable(&session, &durable).unwrap();
+        let err = admin::create_durable(&session, &durable).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+        let err = admin::create_durable(
+            &session,
+            &DurableInfo {
+                selector: Some("region =".to_string()),
+                ..durable.clone()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        // messages are kept for the durable before the first consumer connects
+        let topic = Destination::Topic("durables.orders".to_string());
+        for region in ["EU", "US"] {
+            let mut msg = TextMessage {
+                body: region.to_string(),
+                ..Default::default()
+            };
+            msg.header = Some(HashMap::from([(
+                "region".to_string(),
+                TypedValue::String(region.to_string()),
+            )]));
+            session.send_message(&topic, msg).unwrap();
+        }
+        let durables = admin::list_durables(&session, "durables.>").unwrap();
+        assert_eq!(durables.len(), 1);
+        assert_eq!(durables[0].name, "billing");
+        assert_eq!(durables[0].topic, "durables.orders");
+        assert_eq!(durables[0].selector, durable.selector);
+        assert_eq!(durables[0].pending_messages, Some(1));
+        assert_eq!(durables[0].connected, Some(false));
+        assert!(admin::list_durables(&session, "other.>")
+            .unwrap()
+            .is_empty());
+
+        // a durable in use can not be deleted
+        let consumer_session = conn.session().unwrap();
+        consumer_session
+            .topic_durable_consumer(&topic, "billing", Some("region = 'EU'"))
+            .unwrap();
+        let durables = admin::list_durables(&session, ">").unwrap();
+        assert_eq!(durables[0].connected, Some(true));
+        let err = admin::delete_durable(&session, "durables.orders", "billing", None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        drop(consumer_session);
+
+        admin::delete_durable(&session, "durables.orders", "billing", None).unwrap();
+        assert!(admin::list_durables(&session, ">").unwrap().is_empty());
+        let err = admin::delete_durable(&session, "durables.orders", "billing", None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn list_and_destroy_connections() {
+        let broker = MockBroker::new();
+        let admin_conn = broker.connect().unwrap();
+        let session = admin_conn.session().unwrap();
+        let app_conn =
+            tibco_ems::connect_with_backend(broker.clone(), broker.url(), "orders-app", "")
+                .unwrap();
+        let app_session = app_conn.session().unwrap();
+        let queue = Destination::Queue("connections.flooded".to_string());
+        let consumer = app_session.queue_consumer(&queue, None).unwrap();
+
+        let connections = admin::list_connections(&session, &ConnectionFilter::default()).unwrap();
+        assert_eq!(connections.len(), 2);
+        let filter = ConnectionFilter {
+            user: Some("orders-app".to_string()),
+            ..Default::default()
+        };
+        let connections = admin::list_connections(&session, &filter).unwrap();
+        assert_eq!(connections.len(), 1);
+        let app = &connections[0];
+        assert_eq!(app.user.as_deref(), Some("orders-app"));
+        assert_eq!(app.connection_type.as_deref(), Some("jms"));
+        assert_eq!(app.session_count, Some(1));
+        assert_eq!(app.consumer_count, Some(1));
+        assert!(app.uptime.is_some());
+
+        admin::destroy_connection(&session, app.id).unwrap();
+        assert!(admin::list_connections(&session, &filter)
+            .unwrap()
+            .is_empty());
+        // the sessions of a destroyed connection are closed
+        assert!(consumer.receive_message(Some(1)).is_err());
+        assert!(app_conn.session().is_err());
+        let err = admin::destroy_connection(&session, app.id).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // dropped connections are closed
+        let dropped =
+            tibco_ems::connect_with_backend(broker.clone(), broker.url(), "batch", "").unwrap();
+        let filter = ConnectionFilter {
+            user: Some("batch".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(admin::list_connections(&session, &filter).unwrap().len(), 1);
+        drop(dropped);
+        assert!(admin::list_connections(&session, &filter)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn list_consumers_and_producers() {
+        let broker = MockBroker::new();
+        let admin_conn = broker.connect().unwrap();
+        let session = admin_conn.session().unwrap();
+        let app_conn =
+            tibco_ems::connect_with_backend(broker.clone(), broker.url(), "billing", "").unwrap();
+        let app_session = app_conn.transacted_session().unwrap();
+        let queue = Destination::Queue("clients.orders".to_string());
+        let topic = Destination::Topic("clients.events".to_string());
+        let consumer = app_session
+            .queue_consumer(&queue, Some("region = 'EU'"))
+            .unwrap();
+        app_session
+            .topic_durable_consumer(&topic, "audit", None)
+            .unwrap();
+        for region in ["EU", "EU", "US"] {
+            let msg = TextMessage {
+                body: region.to_string(),
+                header: Some(HashMap::from([(
+                    "region".to_string(),
+                    TypedValue::String(region.to_string()),
+                )])),
+                ..Default::default()
+            };
+            session.send_message(&queue, msg).unwrap();
+        }
+        consumer.receive_message(Some(1)).unwrap().unwrap();
+
+        let consumers = admin::list_consumers(&session, &queue).unwrap();
+        assert_eq!(consumers.len(), 1);
+        let info = &consumers[0];
+        assert_eq!(info.destination, queue);
+        assert_eq!(info.user.as_deref(), Some("billing"));
+        assert_eq!(info.selector.as_deref(), Some("region = 'EU'"));
+        assert_eq!(info.durable_name, None);
+        assert_eq!(info.delivered_count, Some(1));
+        assert_eq!(info.pending_ack_count, Some(1));
+        let connections = admin::list_connections(
+            &session,
+            &ConnectionFilter {
+                user: Some("billing".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(info.connection_id, Some(connections[0].id));
+
+        let consumers = admin::list_consumers(&session, &topic).unwrap();
+        assert_eq!(consumers[0].durable_name.as_deref(), Some("audit"));
+
+        let producers = admin::list_producers(&session, &queue).unwrap();
+        assert_eq!(producers.len(), 1);
+        assert_eq!(producers[0].destination, queue);
+        assert_eq!(producers[0].sent_count, Some(3));
+        assert!(admin::list_producers(&session, &topic).unwrap().is_empty());
+
+        // closing the session removes its consumers
+        drop(app_session);
+        assert!(admin::list_consumers(&session, &queue).unwrap().is_empty());
+    }
+
+    #[test]
+    fn server_info_is_reported() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("server.info".to_string());
+        let msg = TextMessage {
+            body: "pending".to_string(),
+            ..Default::default()
+        };
+        session.send_message(&queue, msg).unwrap();
+        broker.set_server_state(ServerState::Standby);
+
+        let info = admin::get_server_info(&session).unwrap();
+        assert_eq!(info.state, Some(ServerState::Standby));
+        assert_eq!(info.connection_count, Some(1));
+        assert_eq!(info.session_count, Some(1));
+        assert_eq!(info.pending_message_count, Some(1));
+        assert!(info.uptime.is_some());
+        assert!(info.version_at_least(8, 0, 0));
+        assert!(!info.version_at_least(99, 0, 0));
+    }
+
+    #[test]
+    fn server_version_comparison() {
+        let info = |version: Option<&str>| ServerInfo {
+            version: version.map(str::to_string),
+            ..Default::default()
+        };
+        assert!(info(Some("10.2.1")).version_at_least(10, 2, 1));
+        assert!(info(Some("10.2.1")).version_at_least(8, 6, 0));
+        assert!(!info(Some("10.2.1")).version_at_least(10, 3, 0));
+        assert!(info(Some("10.3")).version_at_least(10, 3, 0));
+        assert!(info(Some("8.6.0 V5 2021-03-01")).version_at_least(8, 6, 0));
+        assert!(!info(None).version_at_least(0, 0, 0));
+    }
+
+    #[test]
+    fn all_server_states_are_reported() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        for state in [
+            ServerState::Activating,
+            ServerState::Recovering,
+            ServerState::Unknown(42),
+            ServerState::Active,
+        ] {
+            broker.set_server_state(state.clone());
+            assert_eq!(admin::get_server_state(&session).unwrap(), state);
+        }
+    }
+
+    #[test]
+    fn ft_pair_health() {
+        use ServerState::{Active, Recovering, Standby};
+        let health = |a: Option<ServerState>, b: Option<ServerState>| {
+            FtPairHealth::of(a.as_ref(), b.as_ref())
+        };
+        assert_eq!(health(Some(Active), Some(Standby)), FtPairHealth::Healthy);
+        assert_eq!(health(Some(Standby), Some(Active)), FtPairHealth::Healthy);
+        assert_eq!(health(Some(Active), None), FtPairHealth::Degraded);
+        assert_eq!(
+            health(Some(Recovering), Some(Active)),
+            FtPairHealth::Degraded
+        );
+        assert_eq!(health(Some(Active), Some(Active)), FtPairHealth::SplitBrain);
+        assert_eq!(
+            health(Some(Standby), Some(Standby)),
+            FtPairHealth::BothStandby
+        );
+        assert_eq!(health(Some(Standby), None), FtPairHealth::NoActive);
+        assert_eq!(health(None, None), FtPairHealth::NoActive);
+    }
+
+    #[test]
+    fn check_ft_pair_reports_the_active_member() {
+        let primary = "tcp://ft.primary:7222";
+        let secondary = "tcp://ft.secondary:7222";
+        let check = || {
+            admin::check_ft_pair_with_backend(primary, secondary, "admin", "", MockBroker::current)
+        };
+        MockBroker::current(secondary).set_server_state(ServerState::Standby);
+        let status = check().unwrap();
+        assert_eq!(status.health, FtPairHealth::Healthy);
+        assert_eq!(status.active().unwrap().url, primary);
+        assert_eq!(status.members[1].state, Some(ServerState::Standby));
+
+        MockBroker::current(secondary).set_server_state(ServerState::Active);
+        let status = check().unwrap();
+        assert_eq!(status.health, FtPairHealth::SplitBrain);
+        assert!(status.active().is_none());
+
+        MockBroker::current(primary).set_server_state(ServerState::Standby);
+        MockBroker::current(secondary).set_server_state(ServerState::Standby);
+        let status = check().unwrap();
+        assert_eq!(status.health, FtPairHealth::BothStandby);
+    }
+
+    #[test]
+    fn check_ft_pair_reports_unreachable_members() {
+        let primary = "tcp://ft.down.primary:7222";
+        let secondary = "tcp://ft.down.secondary:7222";
+        let check = || {
+            admin::check_ft_pair_with_backend(primary, secondary, "admin", "", MockBroker::current)
+        };
+        MockBroker::current(secondary).set_reachable(false);
+        let status = check().unwrap();
+        assert_eq!(status.health, FtPairHealth::Degraded);
+        assert_eq!(status.members[0].state, Some(ServerState::Active));
+        assert_eq!(status.members[1].state, None);
+        assert!(status.members[1].error.is_some());
+
+        MockBroker::current(primary).set_reachable(false);
+        let err = check().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+    }
+
+    #[test]
+    fn create_list_and_delete_routes() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let connected = RouteInfo {
+            name: "EMS-EU".to_string(),
+            url: "tcp://routes.eu:7222".to_string(),
+            zone_name: Some("europe".to_string()),
+            zone_type: Some(ZoneType::MultiHop),
+            ..Default::default()
+        };
+        let disconnected = RouteInfo {
+            name: "EMS-US".to_string(),
+            url: "tcp://routes.us:7222".to_string(),
+            ..Default::default()
+        };
+        // the mock connects routes to brokers of the current thread
+        MockBroker::current("tcp://routes.eu:7222");
+        admin::create_route(&session, &connected).unwrap();
+        admin::create_route(&session, &disconnected).unwrap();
+        let err = admin::create_route(&session, &disconnected).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        assert_eq!(
+            admin::list_routes(&session).unwrap(),
+            vec![
+                RouteInfo {
+                    state: Some(RouteState::Connected),
+                    ..connected
+                },
+                RouteInfo {
+                    state: Some(RouteState::Disconnected),
+                    ..disconnected
+                },
+            ]
+        );
+        admin::delete_route(&session, "EMS-US").unwrap();
+        assert_eq!(admin::list_routes(&session).unwrap().len(), 1);
+        let err = admin::delete_route(&session, "EMS-US").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn create_list_and_delete_factories() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let factory = FactoryInfo {
+            name: "OrdersFactory".to_string(),
+            factory_type: FactoryType::Queue,
+            url: "tcp://ems1:7222,tcp://ems2:7222".to_string(),
+            client_id: Some("orders".to_string()),
+            connect_attempt_count: Some(3),
+            connect_attempt_delay: Some(Duration::from_millis(250)),
+            reconnect_attempt_count: Some(10),
+            reconnect_attempt_delay: Some(Duration::from_secs(1)),
+            extra: HashMap::new(),
+        };
+        let generic = FactoryInfo {
+            name: "GenericFactory".to_string(),
+            url: "tcp://ems1:7222".to_string(),
+            ..Default::default()
+        };
+        admin::create_factory(&session, &factory).unwrap();
+        admin::create_factory(&session, &generic).unwrap();
+        let err = admin::create_factory(&session, &generic).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+        assert_eq!(
+            admin::list_factories(&session).unwrap(),
+            vec![generic, factory]
+        );
+        admin::delete_factory(&session, "GenericFactory").unwrap();
+        assert_eq!(admin::list_factories(&session).unwrap().len(), 1);
+        let err = admin::delete_factory(&session, "GenericFactory").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn lookup_factory_connects_with_its_settings() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let factory = FactoryInfo {
+            name: "OrdersFactory".to_string(),
+            url: broker.url().to_string(),
+            client_id: Some("orders".to_string()),
+            ..Default::default()
+        };
+        admin::create_factory(&session, &factory).unwrap();
+
+        let err = tibco_ems::lookup_factory_with_backend(broker.clone(), broker.url(), "Missing")
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        let factory =
+            tibco_ems::lookup_factory_with_backend(broker.clone(), broker.url(), "OrdersFactory")
+                .unwrap();
+        assert_eq!(factory.name(), "OrdersFactory");
+        let app_conn = factory.connect("orders-app", "").unwrap();
+        let app_session = app_conn.session().unwrap();
+        let queue = Destination::Queue("factory.orders".to_string());
+        app_session
+            .send_message(&queue, TextMessage::default())
+            .unwrap();
+        assert_eq!(broker.pending_messages(&queue), 1);
+
+        // connections of the factory carry its client id
+        let filter = ConnectionFilter {
+            client_id: Some("orders".to_string()),
+            ..Default::default()
+        };
+        let connections = admin::list_connections(&session, &filter).unwrap();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].user.as_deref(), Some("orders-app"));
+    }
+
+    #[test]
+    fn lookup_factory_uses_the_broker_of_the_thread() {
+        let url = "tcp://factories:7222";
+        let conn = MockBroker::current(url).connect().unwrap();
+        let session = conn.session().unwrap();
+        let factory = FactoryInfo {
+            name: "ThreadFactory".to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        };
+        admin::create_factory(&session, &factory).unwrap();
+        let factory =
+            tibco_ems::lookup_factory_with_backend(MockBroker::current(url), url, "ThreadFactory")
+                .unwrap();
+        assert!(factory.connect("app", "").is_ok());
+    }
+
+    #[test]
+    fn reconcile_plans_and_applies_the_desired_state() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let orders = Destination::Queue("orders".to_string());
+        let legacy = Destination::Queue("legacy".to_string());
+        let app = Principal::User("app".to_string());
+        admin::create_user(
+            &session,
+            &UserInfo {
+                name: "app".to_string(),
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+        admin::create_queue(
+            &session,
+            &QueueInfo {
+                name: "orders".to_string(),
+                max_messages: Some(100),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        admin::create_queue(
+            &session,
+            &QueueInfo {
+                name: "legacy".to_string(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let old_bridge = BridgeInfo {
+            source: legacy.clone(),
+            target: orders.clone(),
+            selector: None,
+        };
+        admin::create_bridge(&session, &old_bridge).unwrap();
+        admin::grant(
+            &session,
+            &orders,
+            &app,
+            &[Permission::Send, Permission::Browse],
+        )
+        .unwrap();
+        admin::grant(&session, &legacy, &app, &[Permission::Receive]).unwrap();
+
+        let new_bridge = BridgeInfo {
+            source: Destination::Topic("orders.events".to_string()),
+            target: orders.clone(),
+            selector: Some("region = 'EU'".to_string()),
+        };
+        let desired = DesiredState {
+            queues: vec![QueueInfo {
+                name: "orders".to_string(),
+                max_messages: Some(1000),
+                overflow_policy: Some(OverflowPolicy::RejectIncoming),
+                import_channels: Some(vec!["tibrv".to_string(), "smarts".to_string()]),
+                ..Default::default()
+            }],
+            topics: vec![TopicInfo {
+                name: "orders.events".to_string(),
+                ..Default::default()
+            }],
+            bridges: vec![new_bridge.clone()],
+            acls: vec![AclEntry {
+                destination: orders.clone(),
+                principal: app.clone(),
+                permissions: vec![Permission::Send, Permission::Receive],
+            }],
+        };
+
+        // without pruning nothing is deleted
+        let plan = reconcile::plan(&session, &desired, &ReconcileOptions::default()).unwrap();
+        assert_eq!(
+            plan.to_string(),
+            "~ update queue orders: max_messages 100 -> 1000, \
+             overflow_policy default -> rejectIncoming, import_channels unset -> tibrv,smarts\n\
+             + create topic orders.events\n\
+             + create bridge topic orders.events -> queue orders with selector region = 'EU'\n\
+             + grant receive on queue orders to user app\n"
+        );
+
+        let options = ReconcileOptions {
+            dry_run: true,
+            prune: true,
+        };
+        let plan = reconcile::reconcile(&session, &desired, &options).unwrap();
+        assert_eq!(plan.actions.len(), 8);
+        // permissions on destinations missing from the desired state are pruned too
+        assert_eq!(
+            plan.actions[4..],
+            [
+                Action::Revoke(AclEntry {
+                    destination: orders.clone(),
+                    principal: app.clone(),
+                    permissions: vec![Permission::Browse],
+                }),
+                Action::Revoke(AclEntry {
+                    destination: legacy.clone(),
+                    principal: app.clone(),
+                    permissions: vec![Permission::Receive],
+                }),
+                Action::DeleteBridge(old_bridge),
+                Action::DeleteQueue("legacy".to_string()),
+            ]
+        );
+        // a dry run leaves the server untouched
+        assert_eq!(admin::list_all_queues(&session).unwrap().len(), 2);
+        assert!(admin::list_all_topics(&session).unwrap().is_empty());
+
+        let options = ReconcileOptions {
+            dry_run: false,
+            prune: true,
+        };
+        reconcile::reconcile(&session, &desired, &options).unwrap();
+        let queues = admin::list_all_queues(&session).unwrap();
+        assert_eq!(queues.len(), 1);
+        assert_eq!(queues[0].max_messages, Some(1000));
+        assert_eq!(admin::list_bridges(&session).unwrap(), vec![new_bridge]);
+        let acls = admin::list_acls(&session, orders.clone()).unwrap();
+        assert_eq!(acls.len(), 1);
+        assert_eq!(
+            acls[0].permissions,
+            vec![Permission::Receive, Permission::Send]
+        );
+        let plan = reconcile::plan(&session, &desired, &options).unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(plan.to_string(), "no changes\n");
+    }
+
+    #[test]
+    fn reconcile_rejects_duplicate_destinations() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let desired = DesiredState {
+            queues: vec![
+                QueueInfo {
+                    name: "twice".to_string(),
+                    ..Default::default()
+                },
+                QueueInfo {
+                    name: "twice".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let err = reconcile::plan(&session, &desired, &ReconcileOptions::default()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    /// fills a broker with one object of every kind
+    fn populate(session: &tibco_ems::Session) {
+        let orders = Destination::Queue("config.orders".to_string());
+        let events = Destination::Topic("config.events".to_string());
+        let user = UserInfo {
+            name: "app".to_string(),
+            description: Some("order service".to_string()),
+        };
+        admin::create_user(session, &user, Some("secret")).unwrap();
+        let group = GroupInfo {
+            name: "services".to_string(),
+            description: None,
+            members: vec!["app".to_string()],
+        };
+        admin::create_group(session, &group).unwrap();
+        let queue = QueueInfo {
+            name: "config.orders".to_string(),
+            max_messages: Some(1000),
+            failsafe: Some(true),
+            ..Default::default()
+        };
+        admin::create_queue(session, &queue).unwrap();
+        let topic = TopicInfo {
+            name: "config.events".to_string(),
+            global: Some(true),
+            ..Default::default()
+        };
+        admin::create_topic(session, &topic).unwrap();
+        let bridge = BridgeInfo {
+            source: events.clone(),
+            target: orders.clone(),
+            selector: None,
+        };
+        admin::create_bridge(session, &bridge).unwrap();
+        let durable = DurableInfo {
+            name: "audit".to_string(),
+            topic: "config.events".to_string(),
+            ..Default::default()
+        };
+        admin::create_durable(session, &durable).unwrap();
+        admin::grant(
+            session,
+            &orders,
+            &Principal::User("app".to_string()),
+            &[Permission::Send],
+        )
+        .unwrap();
+        admin::grant(
+            session,
+            &events,
+            &Principal::Group("services".to_string()),
+            &[Permission::Subscribe],
+        )
+        .unwrap();
+        let route = RouteInfo {
+            name: "EMS-DR".to_string(),
+            url: "tcp://dr:7222".to_string(),
+            ..Default::default()
+        };
+        admin::create_route(session, &route).unwrap();
+        let factory = FactoryInfo {
+            name: "OrdersFactory".to_string(),
+            url: "tcp://ems:7222".to_string(),
+            ..Default::default()
+        };
+        admin::create_factory(session, &factory).unwrap();
+    }
+
+    #[test]
+    fn export_and_import_config() {
+        let source = MockBroker::new();
+        let source_conn = source.connect().unwrap();
+        let source_session = source_conn.session().unwrap();
+        populate(&source_session);
+        // permissions on wildcards are exported although no such destination is listed
+        admin::grant(
+            &source_session,
+            &Destination::Topic("config.>".to_string()),
+            &Principal::User("app".to_string()),
+            &[Permission::Subscribe],
+        )
+        .unwrap();
+        // statistics are not part of the configuration
+        source_session
+            .send_message(
+                &Destination::Queue("config.orders".to_string()),
+                TextMessage::default(),
+            )
+            .unwrap();
+
+        let config = admin::export_config(&source_session).unwrap();
+        assert_eq!(config.version.as_deref(), Some("10.2.1"));
+        assert_eq!(config.queues.len(), 1);
+        assert_eq!(config.queues[0].pending_messages, None);
+        assert_eq!(config.queues[0].max_messages, Some(1000));
+        assert_eq!(config.topics.len(), 1);
+        assert_eq!(config.bridges.len(), 1);
+        assert_eq!(config.durables.len(), 1);
+        assert_eq!(config.users.len(), 1);
+        assert_eq!(config.groups[0].members, vec!["app".to_string()]);
+        assert_eq!(config.acls.len(), 3);
+        assert_eq!(config.routes[0].state, None);
+        assert_eq!(config.factories.len(), 1);
+
+        let target = MockBroker::new();
+        let target_conn = target.connect().unwrap();
+        let target_session = target_conn.session().unwrap();
+        // existing destinations get the exported properties
+        admin::create_queue(
+            &target_session,
+            &QueueInfo {
+                name: "config.orders".to_string(),
+                max_messages: Some(10),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        admin::import_config(&target_session, &config).unwrap();
+        assert_eq!(admin::export_config(&target_session).unwrap(), config);
+
+        // an import can be repeated, e.g. after it failed part way
+        admin::import_config(&target_session, &config).unwrap();
+        assert_eq!(admin::export_config(&target_session).unwrap(), config);
+    }
+
+    #[test]
+    fn import_config_adds_missing_group_members() {
+        let source = MockBroker::new();
+        let source_conn = source.connect().unwrap();
+        let source_session = source_conn.session().unwrap();
+        populate(&source_session);
+        let user = UserInfo {
+            name: "batch".to_string(),
+            description: None,
+        };
+        admin::create_user(&source_session, &user, None).unwrap();
+        admin::add_user_to_group(&source_session, "batch", "services").unwrap();
+        let config = admin::export_config(&source_session).unwrap();
+
+        let target = MockBroker::new();
+        let target_conn = target.connect().unwrap();
+        let target_session = target_conn.session().unwrap();
+        populate(&target_session);
+        admin::import_config(&target_session, &config).unwrap();
+        assert_eq!(
+            admin::list_groups(&target_session).unwrap()[0].members,
+            vec!["app".to_string(), "batch".to_string()]
+        );
+    }
+}
@@ -2,6 +2,7 @@
 #[cfg(feature = "test_with_ems")]
 mod local_ems {
     use std::collections::HashMap;
+    use tibco_ems::admin::{self, AdminError, AdminErrorKind, QueueInfo};
     use tibco_ems::Destination;
     use tibco_ems::Message;
     use tibco_ems::TextMessage;
@@ -53,6 +54,31 @@ mod local_ems {
         }
     }
 
+    #[test]
+    fn admin_commands_are_confirmed() {
+        let url = "tcp://localhost:7222";
+        let user = "admin";
+        let password = "admin";
+
+        let connection = admin::connect(url, user, password).unwrap();
+        let session = connection.session().unwrap();
+        let queue = QueueInfo {
+            name: "local_ems.admin.confirmed".to_string(),
+            ..Default::default()
+        };
+        let _ignore = admin::delete_queue(&session, &queue.name);
+
+        admin::create_queue(&session, &queue).unwrap();
+        let err = admin::create_queue(&session, &queue).unwrap_err();
+        let admin_err = AdminError::from_error(&err).unwrap();
+        assert_ne!(admin_err.kind, AdminErrorKind::Unconfirmed);
+        assert!(admin_err.code.is_some());
+
+        admin::delete_queue(&session, &queue.name).unwrap();
+        let err = admin::delete_queue(&session, &queue.name).unwrap_err();
+        assert!(AdminError::from_error(&err).unwrap().code.is_some());
+    }
+
     #[test]
     fn test_correlation_id_none() {
         env_logger::init();
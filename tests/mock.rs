@@ -1,10 +1,31 @@
 #[cfg(test)]
-#[cfg(not(feature = "ems-sys"))]
 mod mock {
+    use std::thread;
+    use tibco_ems::mock::MockBroker;
+    use tibco_ems::{Connection, Destination, Message, TextMessage};
+
+    fn connect(url: &str, user: &str, password: &str) -> std::io::Result<Connection> {
+        tibco_ems::connect_with_backend(MockBroker::current(url), url, user, password)
+    }
+
+    fn text(body: &str) -> TextMessage {
+        TextMessage {
+            body: body.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn body(msg: Option<Message>) -> Option<String> {
+        match msg {
+            Some(Message::TextMessage(ref m)) => Some(m.body.clone()),
+            Some(_) => panic!("Expected TextMessage"),
+            None => None,
+        }
+    }
 
     #[test]
     fn send_text_message() -> Result<(), String> {
-        let conn = tibco_ems::connect("tcp://example.org:7222", "admin", "admin").unwrap();
+        let conn = connect("tcp://example.org:7222", "admin", "admin").unwrap();
         let session = conn.session().unwrap();
         let queue = tibco_ems::Destination::Queue("test.queue".to_string());
         let msg = tibco_ems::TextMessage {
@@ -18,7 +39,7 @@ mod mock {
 
     #[test]
     fn receive_text_message() -> Result<(), String> {
-        let conn = tibco_ems::connect("tcp://example.org:7222", "admin", "admin").unwrap();
+        let conn = connect("tcp://example.org:7222", "admin", "admin").unwrap();
         let session = conn.session().unwrap();
         let queue = tibco_ems::Destination::Queue("test.queue".to_string());
         let msg = tibco_ems::TextMessage {
@@ -39,4 +60,364 @@ mod mock {
         }
         Ok(())
     }
+
+    #[test]
+    fn queue_is_fifo_and_removes_on_receive() {
+        let conn = connect("tcp://example.org:7222", "admin", "admin").unwrap();
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("fifo".to_string());
+        session.send_message(&queue, text("one")).unwrap();
+        session.send_message(&queue, text("two")).unwrap();
+
+        let consumer = session.queue_consumer(&queue, None).unwrap();
+        assert_eq!(
+            body(consumer.receive_message(Some(1)).unwrap()),
+            Some("one".into())
+        );
+        assert_eq!(
+            body(consumer.receive_message(Some(1)).unwrap()),
+            Some("two".into())
+        );
+        assert_eq!(body(consumer.receive_message(Some(1)).unwrap()), None);
+    }
+
+    #[test]
+    fn brokers_are_isolated_per_thread() {
+        let conn = connect("tcp://example.org:7222", "admin", "admin").unwrap();
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("isolated".to_string());
+        session.send_message(&queue, text("mine")).unwrap();
+
+        let pending = thread::spawn(|| {
+            MockBroker::current("tcp://example.org:7222")
+                .pending_messages(&Destination::Queue("isolated".to_string()))
+        })
+        .join()
+        .unwrap();
+        assert_eq!(pending, 0);
+        assert_eq!(
+            MockBroker::current("tcp://example.org:7222").pending_messages(&queue),
+            1
+        );
+    }
+
+    #[test]
+    fn lookup_context_uses_the_broker_of_the_thread() {
+        let url = "tcp://naming.example.org:7222";
+        MockBroker::current(url).bind("orders", Destination::Queue("prod.orders".to_string()));
+        let context =
+            tibco_ems::lookup_context_with_backend(MockBroker::current(url), url, "app", "secret")
+                .unwrap();
+        let queue = context.lookup_destination("orders").unwrap();
+
+        let conn = connect(url, "app", "secret").unwrap();
+        let session = conn.session().unwrap();
+        session.send_message(&queue, text("resolved")).unwrap();
+        assert_eq!(MockBroker::current(url).pending_messages(&queue), 1);
+    }
+}
+
+#[cfg(test)]
+mod mock_broker {
+    use std::io::ErrorKind;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use tibco_ems::mock::MockBroker;
+    use tibco_ems::{Destination, Message, TextMessage};
+
+    #[test]
+    fn connect_with_backend_uses_given_broker() {
+        let broker = MockBroker::new();
+        let conn =
+            tibco_ems::connect_with_backend(broker.clone(), "tcp://example.org:7222", "", "")
+                .unwrap();
+        assert_eq!(conn.get_active_url().unwrap(), "tcp://localhost:7222");
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("routed".to_string());
+        session.send_message(&queue, text("via backend")).unwrap();
+        assert_eq!(broker.pending_messages(&queue), 1);
+    }
+
+    fn text(body: &str) -> TextMessage {
+        TextMessage {
+            body: body.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn body(msg: Option<Message>) -> Option<String> {
+        match msg {
+            Some(Message::TextMessage(ref m)) => Some(m.body.clone()),
+            Some(_) => panic!("Expected TextMessage"),
+            None => None,
+        }
+    }
+
+    #[test]
+    fn topic_fans_out_to_every_subscriber() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let topic = Destination::Topic("news".to_string());
+        let first = session.queue_consumer(&topic, None).unwrap();
+        let second = session.topic_consumer(&topic, "shared", None).unwrap();
+        let wildcard = session
+            .queue_consumer(&Destination::Topic("*".to_string()), None)
+            .unwrap();
+
+        session.send_message(&topic, text("hello")).unwrap();
+
+        assert_eq!(
+            body(first.receive_message(Some(1)).unwrap()),
+            Some("hello".into())
+        );
+        assert_eq!(
+            body(second.receive_message(Some(1)).unwrap()),
+            Some("hello".into())
+        );
+        assert_eq!(
+            body(wildcard.receive_message(Some(1)).unwrap()),
+            Some("hello".into())
+        );
+        assert_eq!(body(first.receive_message(Some(1)).unwrap()), None);
+    }
+
+    #[test]
+    fn topic_without_subscribers_drops_messages() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let topic = Destination::Topic("void".to_string());
+        session.send_message(&topic, text("lost")).unwrap();
+
+        let consumer = session.queue_consumer(&topic, None).unwrap();
+        assert_eq!(body(consumer.receive_message(Some(1)).unwrap()), None);
+    }
+
+    #[test]
+    fn durable_subscription_outlives_session() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let topic = Destination::Topic("orders".to_string());
+        {
+            let session = conn.session().unwrap();
+            session
+                .topic_durable_consumer(&topic, "billing", None)
+                .unwrap();
+            session.topic_consumer(&topic, "transient", None).unwrap();
+        }
+        let session = conn.session().unwrap();
+        session.send_message(&topic, text("order-1")).unwrap();
+        assert_eq!(broker.pending_messages(&topic), 1);
+
+        let durable = session
+            .topic_durable_consumer(&topic, "billing", None)
+            .unwrap();
+        assert_eq!(
+            body(durable.receive_message(Some(1)).unwrap()),
+            Some("order-1".into())
+        );
+        let transient = session.topic_consumer(&topic, "transient", None).unwrap();
+        assert_eq!(body(transient.receive_message(Some(1)).unwrap()), None);
+    }
+
+    #[test]
+    fn durable_subscription_with_new_selector_starts_over() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let topic = Destination::Topic("orders".to_string());
+        {
+            let session = conn.session().unwrap();
+            session
+                .topic_durable_consumer(&topic, "billing", None)
+                .unwrap();
+            session.send_message(&topic, text("order-1")).unwrap();
+        }
+        let session = conn.session().unwrap();
+        let durable = session
+            .topic_durable_consumer(&topic, "billing", Some("region = 'EU'"))
+            .unwrap();
+        assert_eq!(body(durable.receive_message(Some(1)).unwrap()), None);
+    }
+
+    #[test]
+    fn shared_subscription_keeps_messages_on_selector_mismatch() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let topic = Destination::Topic("orders".to_string());
+        let session = conn.session().unwrap();
+        let shared = session.topic_consumer(&topic, "billing", None).unwrap();
+        session.send_message(&topic, text("order-1")).unwrap();
+
+        let other = conn.session().unwrap();
+        let err = other
+            .topic_consumer(&topic, "billing", Some("region = 'EU'"))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(
+            body(shared.receive_message(Some(1)).unwrap()),
+            Some("order-1".into())
+        );
+    }
+
+    #[test]
+    fn dropping_the_connection_closes_its_sessions() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let clone = conn.clone();
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("dropped".to_string());
+        let consumer = session.queue_consumer(&queue, None).unwrap();
+        drop(conn);
+        assert!(clone.session().is_ok());
+
+        drop(clone);
+        assert!(consumer.receive_message(Some(1)).is_err());
+        assert!(session.send_message(&queue, text("late")).is_err());
+    }
+
+    #[test]
+    fn topic_consumer_rejects_queue() {
+        let conn = MockBroker::new().connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("not-a-topic".to_string());
+        assert!(session.topic_consumer(&queue, "sub", None).is_err());
+        assert!(session.topic_durable_consumer(&queue, "sub", None).is_err());
+    }
+
+    #[test]
+    fn rollback_redelivers_message() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.transacted_session().unwrap();
+        let queue = Destination::Queue("tx".to_string());
+        session.send_message(&queue, text("retry")).unwrap();
+
+        let consumer = session.queue_consumer(&queue, None).unwrap();
+        let msg = consumer.receive_message(Some(1)).unwrap().unwrap();
+        assert_eq!(consumer.receive_message(Some(1)).unwrap(), None);
+        msg.rollback();
+
+        let msg = consumer.receive_message(Some(1)).unwrap().unwrap();
+        match &msg {
+            Message::TextMessage(m) => {
+                assert_eq!(m.body, "retry");
+                let header = m.header.as_ref().unwrap();
+                assert_eq!(
+                    header.get("JMSXDeliveryCount"),
+                    Some(&tibco_ems::TypedValue::Integer(2))
+                );
+            }
+            _ => panic!("Expected TextMessage"),
+        }
+        msg.confirm();
+        assert_eq!(broker.pending_messages(&queue), 0);
+    }
+
+    #[test]
+    fn closing_session_redelivers_unacknowledged_messages() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let queue = Destination::Queue("unacked".to_string());
+        {
+            let session = conn.transacted_session().unwrap();
+            session.send_message(&queue, text("pending")).unwrap();
+            let consumer = session.queue_consumer(&queue, None).unwrap();
+            let _msg = consumer.receive_message(Some(1)).unwrap().unwrap();
+            assert_eq!(broker.pending_messages(&queue), 1);
+        }
+        let session = conn.session().unwrap();
+        let consumer = session.queue_consumer(&queue, None).unwrap();
+        assert_eq!(
+            body(consumer.receive_message(Some(1)).unwrap()),
+            Some("pending".into())
+        );
+        assert_eq!(broker.pending_messages(&queue), 0);
+    }
+
+    #[test]
+    fn receive_honours_wait_time() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("slow".to_string());
+        let consumer = session.queue_consumer(&queue, None).unwrap();
+
+        let start = Instant::now();
+        assert_eq!(consumer.receive_message(Some(50)).unwrap(), None);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        let producer = conn.clone();
+        let sender = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            let session = producer.session().unwrap();
+            session
+                .send_message(&Destination::Queue("slow".to_string()), text("late"))
+                .unwrap();
+        });
+        assert_eq!(
+            body(consumer.receive_message(None).unwrap()),
+            Some("late".into())
+        );
+        sender.join().unwrap();
+    }
+
+    #[test]
+    fn request_reply_receives_answer() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let service = Destination::Queue("service".to_string());
+
+        let responder_conn = conn.clone();
+        let responder = thread::spawn(move || {
+            let session = responder_conn.session().unwrap();
+            let consumer = session
+                .queue_consumer(&Destination::Queue("service".to_string()), None)
+                .unwrap();
+            let request = consumer.receive_message(Some(5000)).unwrap().unwrap();
+            if let Message::TextMessage(request) = &request {
+                let reply_to = request.reply_to.clone().unwrap();
+                session
+                    .send_message(&reply_to, text(&format!("re: {}", request.body)))
+                    .unwrap();
+            }
+        });
+
+        let session = conn.session().unwrap();
+        let reply = session.request_reply(&service, text("ping"), 5000).unwrap();
+        assert_eq!(body(reply), Some("re: ping".into()));
+        responder.join().unwrap();
+    }
+
+    #[test]
+    fn request_reply_times_out() {
+        let conn = MockBroker::new().connect().unwrap();
+        let session = conn.session().unwrap();
+        let service = Destination::Queue("nobody".to_string());
+        let reply = session.request_reply(&service, text("ping"), 10).unwrap();
+        assert_eq!(reply, None);
+    }
+
+    #[test]
+    fn lookup_context_resolves_bound_destinations() {
+        let broker = MockBroker::new();
+        broker.bind("orders", Destination::Queue("prod.orders.v2".to_string()));
+        broker.bind("prices", Destination::Topic("prod.prices".to_string()));
+        let context =
+            tibco_ems::lookup_context_with_backend(broker.clone(), broker.url(), "", "").unwrap();
+        assert_eq!(
+            context.lookup_destination("orders").unwrap(),
+            Destination::Queue("prod.orders.v2".to_string())
+        );
+        assert_eq!(
+            context.lookup_destination("prices").unwrap(),
+            Destination::Topic("prod.prices".to_string())
+        );
+
+        broker.unbind("orders");
+        let err = context.lookup_destination("orders").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        let err = context.lookup_factory("OrdersFactory").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    }
 }
@@ -0,0 +1,314 @@
+#[cfg(test)]
+mod selector {
+    use std::collections::HashMap;
+    use std::io::{Error, ErrorKind};
+    use tibco_ems::mock::MockBroker;
+    use tibco_ems::selector::{Expression, Literal, Property, Selector};
+    use tibco_ems::{Destination, Message, TextMessage, TypedValue};
+
+    fn message(properties: &[(&str, TypedValue)]) -> Message {
+        let mut header = HashMap::new();
+        for (name, value) in properties {
+            header.insert(name.to_string(), value.clone());
+        }
+        Message::TextMessage(TextMessage {
+            body: "body".to_string(),
+            header: Some(header),
+            ..Default::default()
+        })
+    }
+
+    fn matches(selector: &str, msg: &Message) -> bool {
+        Selector::parse(selector).unwrap().matches(msg)
+    }
+
+    #[test]
+    fn parse_builds_expression() {
+        let selector = Selector::parse("region = 'EU' AND NOT priority > 3").unwrap();
+        assert_eq!(selector.as_str(), "region = 'EU' AND NOT priority > 3");
+        assert!(matches!(selector.expression(), Expression::And(_, _)));
+    }
+
+    #[test]
+    fn parse_reports_error_position() {
+        let err = Selector::parse("region = 'EU' AND AND x = 1").unwrap_err();
+        assert_eq!(err.position, 18);
+        let err = Selector::parse("region = 'EU").unwrap_err();
+        assert_eq!(err.position, 9);
+        let err = Selector::parse("region IN (1, 2)").unwrap_err();
+        assert_eq!(err.position, 11);
+        let err = Selector::parse("(a = 1").unwrap_err();
+        assert_eq!(err.position, 6);
+        let err = Selector::parse("a = 1 #").unwrap_err();
+        assert_eq!(err.position, 6);
+    }
+
+    #[test]
+    fn comparisons_and_arithmetic() {
+        let msg = message(&[
+            ("region", TypedValue::String("EU".to_string())),
+            ("priority", TypedValue::Integer(4)),
+            ("price", TypedValue::Double(9.5)),
+            ("urgent", TypedValue::Boolean(true)),
+        ]);
+        assert!(matches("region = 'EU'", &msg));
+        assert!(matches("region <> 'US'", &msg));
+        assert!(matches("priority >= 4 AND priority < 5", &msg));
+        assert!(matches("price * 2 = 19", &msg));
+        assert!(matches("priority + 1 > price - 5", &msg));
+        assert!(matches("-priority = -4", &msg));
+        assert!(matches("urgent = TRUE", &msg));
+        assert!(matches("urgent", &msg));
+        assert!(!matches("region = 'US' OR priority > 10", &msg));
+    }
+
+    #[test]
+    fn between_in_like_and_null() {
+        let msg = message(&[
+            ("region", TypedValue::String("EU-west".to_string())),
+            ("priority", TypedValue::Long(4)),
+            ("code", TypedValue::String("10%_off".to_string())),
+        ]);
+        assert!(matches("priority BETWEEN 1 AND 4", &msg));
+        assert!(matches("priority NOT BETWEEN 5 AND 9", &msg));
+        assert!(matches("region IN ('EU-west', 'US')", &msg));
+        assert!(matches("region NOT IN ('US')", &msg));
+        assert!(matches("region LIKE 'EU%'", &msg));
+        assert!(matches("region LIKE 'EU_west'", &msg));
+        assert!(!matches("region LIKE 'EU'", &msg));
+        assert!(matches("code LIKE '10!%!_%' ESCAPE '!'", &msg));
+        assert!(!matches("code LIKE '10!%!_' ESCAPE '!'", &msg));
+        assert!(matches("missing IS NULL", &msg));
+        assert!(matches("region IS NOT NULL", &msg));
+    }
+
+    #[test]
+    fn unknown_values_do_not_match() {
+        let msg = message(&[("region", TypedValue::String("EU".to_string()))]);
+        assert!(!matches("missing = 1", &msg));
+        assert!(!matches("NOT missing = 1", &msg));
+        assert!(!matches("region > 'A'", &msg));
+        assert!(matches("missing = 1 OR region = 'EU'", &msg));
+        assert!(!matches("missing = 1 AND region = 'EU'", &msg));
+    }
+
+    #[test]
+    fn header_fields_are_available() {
+        let msg = message(&[
+            ("MessageID", TypedValue::String("ID:1".to_string())),
+            ("CorrelationID", TypedValue::String("abc".to_string())),
+        ]);
+        assert!(matches("JMSMessageID = 'ID:1'", &msg));
+        assert!(matches("JMSCorrelationID = 'abc'", &msg));
+    }
+
+    #[test]
+    fn java_numeric_literals() {
+        let msg = message(&[
+            ("priority", TypedValue::Long(6)),
+            ("x", TypedValue::Long(16)),
+            ("rate", TypedValue::Double(2.0)),
+            ("min", TypedValue::Long(i64::MIN)),
+        ]);
+        assert!(matches("priority > 5L", &msg));
+        assert!(matches("x = 0x10", &msg));
+        assert!(matches("x = 0X10L", &msg));
+        assert!(matches("x = 020", &msg));
+        assert!(matches("rate > 1.5f", &msg));
+        assert!(matches("rate = 2d", &msg));
+        assert!(matches("rate = 2E0D", &msg));
+        assert!(matches("min = -9223372036854775808", &msg));
+        assert!(matches("min = -9223372036854775808L", &msg));
+        assert!(matches("x-1 = 15", &msg));
+        assert!(matches("x - -1 = 17", &msg));
+        assert!(matches("-x = -16", &msg));
+    }
+
+    #[test]
+    fn invalid_numeric_literals_are_rejected() {
+        assert!(Selector::parse("x = 9223372036854775808").is_err());
+        assert!(Selector::parse("x = 0x").is_err());
+        assert!(Selector::parse("x = 019").is_err());
+        assert!(Selector::parse("x = 1.5L").is_err());
+        assert!(Selector::parse("x = 5q").is_err());
+    }
+
+    #[test]
+    fn java_numeric_literals_are_accepted_on_consumer_creation() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("selector.literals".to_string());
+        for selector in [
+            "priority > 5L",
+            "x = 0x10",
+            "x > 1.5f",
+            "x = -9223372036854775808",
+        ] {
+            session.queue_consumer(&queue, Some(selector)).unwrap();
+        }
+    }
+
+    #[test]
+    fn invalid_selector_is_rejected_on_consumer_creation() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("selector.invalid".to_string());
+        let err = session
+            .queue_consumer(&queue, Some("region = "))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn queue_consumer_skips_non_matching_messages() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("selector.queue".to_string());
+        let eu = session
+            .queue_consumer(&queue, Some("region = 'EU'"))
+            .unwrap();
+        for region in ["US", "EU"] {
+            let mut header = HashMap::new();
+            header.insert("region".to_string(), TypedValue::String(region.to_string()));
+            let msg = TextMessage {
+                body: region.to_string(),
+                header: Some(header),
+                ..Default::default()
+            };
+            session.send_message(&queue, msg).unwrap();
+        }
+        match eu.receive_message(Some(10)).unwrap() {
+            Some(Message::TextMessage(ref msg)) => assert_eq!(msg.body, "EU"),
+            other => panic!("unexpected message {other:?}"),
+        }
+        assert!(eu.receive_message(Some(10)).unwrap().is_none());
+        assert_eq!(broker.pending_messages(&queue), 1);
+    }
+
+    #[test]
+    fn topic_subscription_only_keeps_matching_messages() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let topic = Destination::Topic("selector.topic".to_string());
+        let consumer = session
+            .topic_consumer(&topic, "sub", Some("priority > 5"))
+            .unwrap();
+        for priority in [3, 7] {
+            let mut header = HashMap::new();
+            header.insert("priority".to_string(), TypedValue::Integer(priority));
+            let msg = TextMessage {
+                body: priority.to_string(),
+                header: Some(header),
+                ..Default::default()
+            };
+            session.send_message(&topic, msg).unwrap();
+        }
+        assert_eq!(broker.pending_messages(&topic), 1);
+        match consumer.receive_message(Some(10)).unwrap() {
+            Some(Message::TextMessage(ref msg)) => assert_eq!(msg.body, "7"),
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    fn prop(name: &str) -> Property {
+        Selector::prop(name).unwrap()
+    }
+
+    fn double(val: f64) -> Literal {
+        Literal::try_from(val).unwrap()
+    }
+
+    #[test]
+    fn builder_renders_selector() {
+        let selector = prop("region").eq("EU").and(prop("priority").gt(4));
+        assert_eq!(selector.as_str(), "region = 'EU' AND priority > 4");
+        let selector = prop("region")
+            .is_in(["EU", "US"])
+            .or(prop("priority").between(1, double(2.5)))
+            .and(!prop("name").like_escape("a!_%", '!'))
+            .and(prop("missing").is_null());
+        assert_eq!(
+            selector.as_str(),
+            "(region IN ('EU', 'US') OR priority BETWEEN 1 AND 2.5) \
+             AND NOT name LIKE 'a!_%' ESCAPE '!' AND missing IS NULL"
+        );
+        assert_eq!(Selector::parse(selector.as_str()).unwrap(), selector);
+    }
+
+    #[test]
+    fn builder_escapes_values() {
+        let selector = prop("name").eq("O'Brien' OR 1 = 1 OR name = '");
+        assert_eq!(
+            selector.as_str(),
+            "name = 'O''Brien'' OR 1 = 1 OR name = '''"
+        );
+        let msg = message(&[("name", TypedValue::String("O'Brien".to_string()))]);
+        assert!(!selector.matches(&msg));
+        assert!(prop("name").eq("O'Brien").matches(&msg));
+    }
+
+    #[test]
+    fn builder_rejects_invalid_identifier() {
+        for name in ["a = 1 OR b", "", "1a", "AND", "null"] {
+            let err = Selector::prop(name).unwrap_err();
+            assert!(err.message.contains("invalid identifier"), "{err}");
+        }
+        let err: Error = Selector::prop("a b").unwrap_err().into();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn builder_renders_extreme_values_which_parse_back() {
+        let selectors = [
+            prop("x").eq(i64::MIN),
+            prop("x").eq(i64::MAX),
+            prop("x").between(i64::MIN, -1),
+            prop("x").eq(double(f64::MAX)),
+            prop("x").eq(double(f64::MIN)),
+            prop("x").eq(double(f64::MIN_POSITIVE)),
+            prop("x").eq(double(-0.0)),
+            prop("x").eq(Literal::try_from(f32::MAX).unwrap()),
+        ];
+        for selector in selectors {
+            let parsed = Selector::parse(selector.as_str()).unwrap();
+            assert_eq!(parsed.expression(), selector.expression());
+        }
+        assert_eq!(prop("x").eq(i64::MIN).as_str(), "x = -9223372036854775808");
+    }
+
+    #[test]
+    fn builder_rejects_non_finite_values() {
+        for val in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+            assert!(Literal::try_from(val).is_err());
+        }
+        for val in [f32::INFINITY, f32::NEG_INFINITY, f32::NAN] {
+            assert!(Literal::try_from(val).is_err());
+        }
+    }
+
+    #[test]
+    fn builder_selector_is_accepted_by_consumers_and_bridges() {
+        let broker = MockBroker::new();
+        let conn = broker.connect().unwrap();
+        let session = conn.session().unwrap();
+        let queue = Destination::Queue("selector.builder".to_string());
+        let selector = prop("region").ne("US");
+        session
+            .queue_consumer(&queue, Some(selector.as_str()))
+            .unwrap();
+        let topic = Destination::Topic("selector.builder".to_string());
+        session
+            .topic_durable_consumer(&topic, "durable", Some(selector.as_str()))
+            .unwrap();
+        let bridge = tibco_ems::admin::BridgeInfo {
+            source: topic,
+            target: queue,
+            selector: Some(selector.into()),
+        };
+        assert_eq!(bridge.selector.as_deref(), Some("region <> 'US'"));
+    }
+}
@@ -3,8 +3,16 @@
 mod serde {
     use std::collections::HashMap;
 
-    use tibco_ems::admin::{BridgeInfo, OverflowPolicy};
-    use tibco_ems::admin::{QueueInfo, TopicInfo};
+    use tibco_ems::admin::reconcile::DesiredState;
+    use tibco_ems::admin::{AclEntry, ConsumerInfo, Permission, Principal, ProducerInfo};
+    use tibco_ems::admin::{
+        BridgeInfo, ConnectionInfo, DurableInfo, GroupInfo, OverflowPolicy, UserInfo,
+    };
+    use tibco_ems::admin::{FactoryInfo, FactoryType};
+    use tibco_ems::admin::{
+        QueueInfo, ServerConfig, ServerInfo, ServerState, TopicInfo, TraceMode,
+    };
+    use tibco_ems::admin::{RouteInfo, RouteState, ZoneType};
     use tibco_ems::{
         BytesMessage, Destination, MapMessage, Message, ObjectMessage, TextMessage, TypedValue,
     };
@@ -28,6 +36,16 @@ mod serde {
             consumer_count: Some(5),
             incoming_total_count: Some(1000),
             outgoing_total_count: Some(2000),
+            max_redelivery: Some(3),
+            exclusive: Some(true),
+            flow_control: Some(65536),
+            store: Some("$sys.failsafe".to_string()),
+            trace: Some(TraceMode::Body),
+            import_channels: Some(vec!["RV".to_string()]),
+            extra: HashMap::from([(
+                "custom".to_string(),
+                TypedValue::String("value".to_string()),
+            )]),
         };
 
         // Serialize the QueueInfo to JSON
@@ -55,6 +73,16 @@ mod serde {
             pending_messages: Some(20),
             incoming_total_count: Some(1000),
             outgoing_total_count: Some(2000),
+            failsafe: Some(false),
+            secure: Some(true),
+            sender_name: Some(false),
+            sender_name_enforced: Some(false),
+            flow_control: Some(65536),
+            store: Some("$sys.nonfailsafe".to_string()),
+            trace: Some(TraceMode::Default),
+            import_channels: Some(vec!["RV".to_string()]),
+            export_channels: Some(vec!["RV".to_string(), "SS".to_string()]),
+            extra: HashMap::new(),
         };
 
         // Serialize the TopicInfo to JSON
@@ -157,6 +185,224 @@ mod serde {
         assert_eq!(deserialized, bridge_info);
     }
 
+    #[test]
+    fn test_user_and_group_info_serde() {
+        let user_info = UserInfo {
+            name: "user".to_string(),
+            description: Some("description".to_string()),
+        };
+        let json = serde_json::to_string(&user_info).unwrap();
+        let deserialized: UserInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, user_info);
+
+        let group_info = GroupInfo {
+            name: "group".to_string(),
+            description: None,
+            members: vec!["user".to_string()],
+        };
+        let json = serde_json::to_string(&group_info).unwrap();
+        let deserialized: GroupInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, group_info);
+    }
+
+    #[test]
+    fn test_acl_entry_serde() {
+        let acl_entry = AclEntry {
+            destination: Destination::Queue("queue".to_string()),
+            principal: Principal::Group("group".to_string()),
+            permissions: vec![Permission::Receive, Permission::UseDurable],
+        };
+        let json = serde_json::to_string(&acl_entry).unwrap();
+        assert!(json.contains("\"use_durable\""));
+        let deserialized: AclEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, acl_entry);
+    }
+
+    #[test]
+    fn test_durable_info_serde() {
+        let durable_info = DurableInfo {
+            name: "durable".to_string(),
+            topic: "topic".to_string(),
+            client_id: Some("client".to_string()),
+            selector: None,
+            pending_messages: Some(3),
+            connected: Some(true),
+            consumer_user: Some("user".to_string()),
+            shared: Some(false),
+            extra: HashMap::new(),
+        };
+        let json = serde_json::to_string(&durable_info).unwrap();
+        let deserialized: DurableInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, durable_info);
+    }
+
+    #[test]
+    fn test_connection_info_serde() {
+        let connection_info = ConnectionInfo {
+            id: 42,
+            user: Some("user".to_string()),
+            host: Some("host".to_string()),
+            client_id: None,
+            connection_type: Some("jms".to_string()),
+            session_count: Some(2),
+            consumer_count: Some(1),
+            producer_count: Some(1),
+            uptime: Some(std::time::Duration::from_millis(1500)),
+            extra: HashMap::new(),
+        };
+        let json = serde_json::to_string(&connection_info).unwrap();
+        let deserialized: ConnectionInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, connection_info);
+    }
+
+    #[test]
+    fn test_consumer_and_producer_info_serde() {
+        let consumer_info = ConsumerInfo {
+            id: 7,
+            destination: Destination::Queue("queue".to_string()),
+            connection_id: Some(42),
+            user: Some("user".to_string()),
+            host: None,
+            selector: Some("a = 1".to_string()),
+            durable_name: None,
+            delivered_count: Some(10),
+            pending_ack_count: Some(2),
+            message_rate: Some(1.5),
+            extra: HashMap::new(),
+        };
+        let json = serde_json::to_string(&consumer_info).unwrap();
+        let deserialized: ConsumerInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, consumer_info);
+
+        let producer_info = ProducerInfo {
+            id: 8,
+            destination: Destination::Topic("topic".to_string()),
+            connection_id: Some(42),
+            user: None,
+            host: Some("host".to_string()),
+            sent_count: Some(3),
+            message_rate: None,
+            extra: HashMap::new(),
+        };
+        let json = serde_json::to_string(&producer_info).unwrap();
+        let deserialized: ProducerInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, producer_info);
+    }
+
+    #[test]
+    fn test_server_info_serde() {
+        let server_info = ServerInfo {
+            server_name: Some("EMS-SERVER".to_string()),
+            version: Some("10.2.1".to_string()),
+            state: Some(ServerState::Active),
+            uptime: Some(std::time::Duration::from_secs(3600)),
+            connection_count: Some(12),
+            inbound_message_rate: Some(2.5),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&server_info).unwrap();
+        let deserialized: ServerInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, server_info);
+    }
+
+    #[test]
+    fn test_route_info_serde() {
+        let route_info = RouteInfo {
+            name: "EMS-EU".to_string(),
+            url: "tcp://eu:7222".to_string(),
+            zone_name: Some("europe".to_string()),
+            zone_type: Some(ZoneType::OneHop),
+            state: Some(RouteState::Connected),
+            extra: HashMap::from([("tls".to_string(), TypedValue::Boolean(true))]),
+        };
+        let json = serde_json::to_string(&route_info).unwrap();
+        let deserialized: RouteInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, route_info);
+    }
+
+    #[test]
+    fn test_factory_info_serde() {
+        let factory_info = FactoryInfo {
+            name: "OrdersFactory".to_string(),
+            factory_type: FactoryType::Queue,
+            url: "tcp://ems1:7222,tcp://ems2:7222".to_string(),
+            client_id: Some("orders".to_string()),
+            reconnect_attempt_count: Some(10),
+            reconnect_attempt_delay: Some(std::time::Duration::from_millis(500)),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&factory_info).unwrap();
+        assert!(json.contains(r#""factoryType":"queue""#));
+        let deserialized: FactoryInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, factory_info);
+        // the factory type defaults to generic
+        let minimal: FactoryInfo =
+            serde_json::from_str(r#"{"name":"Factory","url":"tcp://ems:7222"}"#).unwrap();
+        assert_eq!(minimal.factory_type, FactoryType::Generic);
+    }
+
+    #[test]
+    fn test_desired_state_from_json() {
+        let document = r#"{
+            "queues": [{"name": "orders", "maxMessages": 1000, "failsafe": true}],
+            "bridges": [{"source": {"Topic": "orders.events"}, "target": {"Queue": "orders"}}],
+            "acls": [{
+                "destination": {"Queue": "orders"},
+                "principal": {"user": "app"},
+                "permissions": ["send", "receive"]
+            }]
+        }"#;
+        let desired: DesiredState = serde_json::from_str(document).unwrap();
+        assert_eq!(desired.queues[0].name, "orders");
+        assert_eq!(desired.queues[0].max_messages, Some(1000));
+        assert!(desired.topics.is_empty());
+        assert_eq!(desired.bridges[0].selector, None);
+        assert_eq!(
+            desired.acls[0].principal,
+            Principal::User("app".to_string())
+        );
+        let json = serde_json::to_string(&desired).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DesiredState>(&json).unwrap(),
+            desired
+        );
+    }
+
+    #[test]
+    fn test_server_config_serde() {
+        let config = ServerConfig {
+            server_name: Some("EMS-PROD".to_string()),
+            version: Some("10.2.1".to_string()),
+            queues: vec![QueueInfo {
+                name: "orders".to_string(),
+                max_messages: Some(1000),
+                ..Default::default()
+            }],
+            users: vec![UserInfo {
+                name: "app".to_string(),
+                description: None,
+            }],
+            acls: vec![AclEntry {
+                destination: Destination::Queue("orders".to_string()),
+                principal: Principal::User("app".to_string()),
+                permissions: vec![Permission::Send],
+            }],
+            routes: vec![RouteInfo {
+                name: "EMS-DR".to_string(),
+                url: "tcp://dr:7222".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        let deserialized: ServerConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, config);
+        // sections missing from a document are empty
+        let partial: ServerConfig = serde_json::from_str(r#"{"users":[{"name":"app"}]}"#).unwrap();
+        assert_eq!(partial.users.len(), 1);
+        assert!(partial.queues.is_empty());
+    }
+
     #[test]
     fn test_bytes_message_serde() {
         let mut header = HashMap::new();